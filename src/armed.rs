@@ -0,0 +1,572 @@
+//! Pre-armed TCDs for deterministic restarts
+//!
+//! A protocol engine often knows its next transfer long before the current
+//! transfer completes. Instead of programming the next transfer register by
+//! register in the interrupt handler, prepare it ahead of time as an
+//! [`ArmedTcd`], a memory image of a transfer control descriptor. Then, restart
+//! the channel with one of two short paths:
+//!
+//! - [`link`](ArmedTcd::link) the image while the current transfer runs. The
+//!   driver writes DLAST_SGA and sets ESG; when the current major loop
+//!   completes, the DMA controller loads the image itself, without any CPU
+//!   involvement.
+//! - [`load`](ArmedTcd::load) the image after the current transfer completes.
+//!   The driver copies the image into the channel's TCD, and enables the
+//!   channel.
+//!
+//! Both paths perform a fixed number of register accesses, independent of the
+//! transfer's configuration. [`ArmedPool`] holds a fixed number of images.
+//!
+//! ```no_run
+//! use imxrt_dma::{armed::ArmedPool, channel};
+//! # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+//! static mut FRAMES: [[u8; 64]; 2] = [[0; 64]; 2];
+//! static mut POOL: ArmedPool<2> = ArmedPool::new();
+//!
+//! # fn f() {
+//! let mut channel = DMA.try_channel(7).unwrap();
+//! // Safety: the pool and frames are only used here, and they're static.
+//! let (pool, frames) = unsafe {
+//!     (
+//!         &mut *core::ptr::addr_of_mut!(POOL),
+//!         &*core::ptr::addr_of!(FRAMES),
+//!     )
+//! };
+//!
+//! // Program each transfer with the usual channel APIs, and capture it.
+//! for (slot, frame) in frames.iter().enumerate() {
+//!     unsafe { channel::set_source_linear_buffer(&mut channel, frame) };
+//!     // Configure the destination, minor loop, and iterations...
+//!     pool.get_mut(slot).unwrap().arm(&channel);
+//! }
+//!
+//! // Later, in the channel's interrupt handler, restart with the next frame.
+//! let next = pool.get(1).unwrap();
+//! // Safety: the image and its buffers are static.
+//! if !unsafe { next.link(&mut channel) } {
+//!     // The current transfer already completed; load the image instead.
+//!     unsafe { next.load(&mut channel) };
+//! }
+//! # }
+//! ```
+//!
+//! # Const transfers
+//!
+//! If a transfer never changes, like a display refresh from a framebuffer at a
+//! known address, build its image in a const context. The image is computed at
+//! compile time, and it may live in flash; the runtime only loads it.
+//!
+//! ```no_run
+//! use imxrt_dma::armed::ArmedTcd;
+//! # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+//!
+//! const FRAMEBUFFER: u32 = 0x8000_0000;
+//! const DISPLAY: u32 = 0x8010_0000;
+//! const ROW_BYTES: u32 = 320 * 2;
+//! const ROWS: u16 = 240;
+//! const FRAME_BYTES: i32 = ROW_BYTES as i32 * ROWS as i32;
+//!
+//! static REFRESH: ArmedTcd = ArmedTcd::new()
+//!     .source::<u32>(FRAMEBUFFER, 4)
+//!     .destination::<u32>(DISPLAY, 4)
+//!     .minor_loop_bytes(ROW_BYTES)
+//!     .iterations(ROWS)
+//!     .source_last_adjustment(-FRAME_BYTES)
+//!     .destination_last_adjustment(-FRAME_BYTES)
+//!     .interrupt_on_completion(true)
+//!     .disable_on_completion(true);
+//!
+//! # fn f() {
+//! let mut channel = DMA.try_channel(7).unwrap();
+//! // Safety: the framebuffer and display memory are always valid.
+//! unsafe { REFRESH.load(&mut channel) };
+//! channel.start();
+//! # }
+//! ```
+//!
+//! # Memory
+//!
+//! When you link an image, the DMA controller reads the image from memory.
+//! Place the pool in memory that the DMA controller can access. With the
+//! `"cache"` feature, `link` cleans the image from the data cache.
+
+use crate::{
+    channel::{circular_buffer_modulo, Channel},
+    ral::{self, tcd},
+    Element, TcdSnapshot,
+};
+
+/// A transfer control descriptor, prepared in memory
+///
+/// The image has the TCD's memory layout and alignment, so the DMA
+/// controller can load it with scatter / gather. Use [`arm`](Self::arm) to
+/// capture a channel's programmed transfer, or [`from_snapshot`](Self::from_snapshot)
+/// to build an image from register values. The builder methods, like
+/// [`source`](Self::source) and [`iterations`](Self::iterations), are `const`:
+///
+/// ```
+/// use imxrt_dma::armed::ArmedTcd;
+///
+/// const COPY: ArmedTcd = ArmedTcd::new()
+///     .source::<u32>(0x2020_0000, 4)
+///     .destination::<u32>(0x2024_0000, 4)
+///     .minor_loop_bytes(64)
+///     .iterations(16)
+///     .start(true);
+///
+/// let tcd = COPY.snapshot();
+/// assert_eq!((tcd.SADDR, tcd.SOFF, tcd.SATTR), (0x2020_0000, 4, 2));
+/// assert_eq!((tcd.CITER, tcd.BITER, tcd.CSR), (16, 16, 1));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C, align(32))]
+pub struct ArmedTcd {
+    saddr: u32,
+    soff: i16,
+    dattr: u8,
+    sattr: u8,
+    nbytes: u32,
+    slast: i32,
+    daddr: u32,
+    doff: i16,
+    citer: u16,
+    dlast_sga: i32,
+    csr: u16,
+    biter: u16,
+}
+
+const _STATIC_ASSERT_IMAGE_MATCHES_TCD: [u32; 1] = [0; (core::mem::size_of::<ArmedTcd>()
+    == core::mem::size_of::<tcd::RegisterBlock>()
+    && core::mem::align_of::<ArmedTcd>() == core::mem::align_of::<tcd::RegisterBlock>())
+    as usize];
+
+/// Status flags that don't belong in a prepared TCD
+const CSR_STATUS: u16 = tcd::CSR::ACTIVE::mask | tcd::CSR::DONE::mask;
+
+impl ArmedTcd {
+    /// Create an empty image
+    ///
+    /// An empty image doesn't describe a transfer; don't link or load it.
+    pub const fn new() -> Self {
+        Self::from_snapshot(&TcdSnapshot {
+            SADDR: 0,
+            SOFF: 0,
+            DATTR: 0,
+            SATTR: 0,
+            NBYTES: 0,
+            SLAST: 0,
+            DADDR: 0,
+            DOFF: 0,
+            CITER: 0,
+            DLAST_SGA: 0,
+            CSR: 0,
+            BITER: 0,
+        })
+    }
+
+    /// Create an image from TCD register values
+    ///
+    /// The ACTIVE and DONE status flags are cleared. Set START in `snapshot`'s
+    /// CSR if the transfer should start as soon as it's linked, like a
+    /// memory-to-memory transfer.
+    pub const fn from_snapshot(snapshot: &TcdSnapshot) -> Self {
+        ArmedTcd {
+            saddr: snapshot.SADDR,
+            soff: snapshot.SOFF,
+            dattr: snapshot.DATTR,
+            sattr: snapshot.SATTR,
+            nbytes: snapshot.NBYTES,
+            slast: snapshot.SLAST,
+            daddr: snapshot.DADDR,
+            doff: snapshot.DOFF,
+            citer: snapshot.CITER,
+            dlast_sga: snapshot.DLAST_SGA,
+            csr: snapshot.CSR & !CSR_STATUS,
+            biter: snapshot.BITER,
+        }
+    }
+
+    /// Returns the image's TCD register values
+    pub const fn snapshot(&self) -> TcdSnapshot {
+        TcdSnapshot {
+            SADDR: self.saddr,
+            SOFF: self.soff,
+            DATTR: self.dattr,
+            SATTR: self.sattr,
+            NBYTES: self.nbytes,
+            SLAST: self.slast,
+            DADDR: self.daddr,
+            DOFF: self.doff,
+            CITER: self.citer,
+            DLAST_SGA: self.dlast_sga,
+            CSR: self.csr,
+            BITER: self.biter,
+        }
+    }
+
+    /// Read elements of type `E` starting at `address`
+    ///
+    /// After each read, the DMA controller adds `offset` bytes to the source
+    /// address. Use the element size to read a linear buffer, or zero to read
+    /// a peripheral register.
+    pub const fn source<E: Element>(mut self, address: u32, offset: i16) -> Self {
+        let () = E::LAYOUT;
+        self.saddr = address;
+        self.soff = offset;
+        self.sattr = E::DATA_TRANSFER_ID;
+        self
+    }
+
+    /// Write elements of type `E` starting at `address`
+    ///
+    /// After each write, the DMA controller adds `offset` bytes to the
+    /// destination address.
+    pub const fn destination<E: Element>(mut self, address: u32, offset: i16) -> Self {
+        let () = E::LAYOUT;
+        self.daddr = address;
+        self.doff = offset;
+        self.dattr = E::DATA_TRANSFER_ID;
+        self
+    }
+
+    /// Read a circular buffer of `len` elements of type `E` starting at `address`
+    ///
+    /// This is the image's equivalent of
+    /// [`set_source_circular_buffer`](crate::channel::set_source_circular_buffer).
+    /// The image sets the source modulo from the buffer's size, so the source
+    /// address wraps around the buffer. The last source adjustment is zero, so
+    /// the next transfer continues at the next element.
+    ///
+    /// ```
+    /// use imxrt_dma::armed::ArmedTcd;
+    ///
+    /// const RING: ArmedTcd = ArmedTcd::new().source_circular_buffer::<u32>(0x2020_0000, 16);
+    ///
+    /// let tcd = RING.snapshot();
+    /// // 64 bytes, so the source modulo is 6.
+    /// assert_eq!((tcd.SADDR, tcd.SOFF, tcd.SATTR), (0x2020_0000, 4, 6 << 3 | 2));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` isn't a power of two, or if `address` isn't a multiple of
+    /// the buffer's size in bytes. In a const context, this fails to compile.
+    pub const fn source_circular_buffer<E: Element>(self, address: u32, len: usize) -> Self {
+        let modulo = circular_buffer_modulo::<E>(address as usize, len);
+        let mut this = self.source::<E>(address, core::mem::size_of::<E>() as i16);
+        this.sattr |= modulo << tcd::SATTR::MOD::offset;
+        this.slast = 0;
+        this
+    }
+
+    /// Write a circular buffer of `len` elements of type `E` starting at `address`
+    ///
+    /// This is the image's equivalent of
+    /// [`set_destination_circular_buffer`](crate::channel::set_destination_circular_buffer).
+    /// See [`source_circular_buffer`](Self::source_circular_buffer) for more
+    /// information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` isn't a power of two, or if `address` isn't a multiple of
+    /// the buffer's size in bytes. In a const context, this fails to compile.
+    pub const fn destination_circular_buffer<E: Element>(self, address: u32, len: usize) -> Self {
+        let modulo = circular_buffer_modulo::<E>(address as usize, len);
+        let mut this = self.destination::<E>(address, core::mem::size_of::<E>() as i16);
+        this.dattr |= modulo << tcd::DATTR::MOD::offset;
+        this.dlast_sga = 0;
+        this
+    }
+
+    /// Set the number of bytes moved by each minor loop
+    ///
+    /// The image doesn't know the controller's minor loop mapping setting.
+    /// With minor loop mapping, the count must be less than 2^30.
+    pub const fn minor_loop_bytes(mut self, nbytes: u32) -> Self {
+        self.nbytes = nbytes;
+        self
+    }
+
+    /// Set the number of minor loops in the major loop
+    ///
+    /// The count is limited to 2^15 - 1; larger counts are truncated. This
+    /// removes any [minor loop link](Self::minor_loop_link), so set the link
+    /// after the iterations.
+    pub const fn iterations(mut self, iterations: u16) -> Self {
+        self.citer = iterations & tcd::CITER::CITER::mask;
+        self.biter = iterations & tcd::BITER::BITER::mask;
+        self
+    }
+
+    /// Send a service request to another channel when each minor loop, except
+    /// for the last, completes
+    ///
+    /// See [`Channel::set_minor_loop_link`](crate::channel::Channel::set_minor_loop_link)
+    /// for more information. With a link, the iteration count is limited to
+    /// 2^9 - 1, and this truncates a larger count. `None` removes the link.
+    ///
+    /// ```
+    /// use imxrt_dma::armed::ArmedTcd;
+    ///
+    /// const COMMANDS: ArmedTcd = ArmedTcd::new().iterations(4).minor_loop_link(Some(3));
+    ///
+    /// let tcd = COMMANDS.snapshot();
+    /// // ELINK, then channel 3, then 4 iterations.
+    /// assert_eq!((tcd.CITER, tcd.BITER), (1 << 15 | 3 << 9 | 4, 1 << 15 | 3 << 9 | 4));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than or equal to 32. In a const context,
+    /// this fails to compile.
+    pub const fn minor_loop_link(mut self, channel: Option<usize>) -> Self {
+        let iterations = if self.biter & tcd::BITER::ELINK::mask != 0 {
+            self.biter & tcd::BITER::BITER_ELINK::mask
+        } else {
+            self.biter & tcd::BITER::BITER::mask
+        };
+        if let Some(channel) = channel {
+            assert!(
+                channel < 32,
+                "DMA channels can't link to channels 32 and above"
+            );
+            let link = tcd::CITER::ELINK::mask
+                | (channel as u16) << tcd::CITER::LINKCH::offset & tcd::CITER::LINKCH::mask;
+            self.citer = link | iterations & tcd::CITER::CITER_ELINK::mask;
+            self.biter = link | iterations & tcd::BITER::BITER_ELINK::mask;
+        } else {
+            self.citer = iterations;
+            self.biter = iterations;
+        }
+        self
+    }
+
+    /// Set the source address adjustment, in bytes, when the major loop completes
+    pub const fn source_last_adjustment(mut self, adjustment: i32) -> Self {
+        self.slast = adjustment;
+        self
+    }
+
+    /// Set the destination address adjustment, in bytes, when the major loop completes
+    ///
+    /// Linking another image replaces this adjustment.
+    pub const fn destination_last_adjustment(mut self, adjustment: i32) -> Self {
+        self.dlast_sga = adjustment;
+        self
+    }
+
+    /// Interrupt when the major loop completes
+    pub const fn interrupt_on_completion(self, intr: bool) -> Self {
+        self.csr_bit(tcd::CSR::INTMAJOR::mask, intr)
+    }
+
+    /// Disable the channel when the major loop completes
+    pub const fn disable_on_completion(self, dreq: bool) -> Self {
+        self.csr_bit(tcd::CSR::DREQ::mask, dreq)
+    }
+
+    /// Start the transfer as soon as the DMA controller loads the image
+    ///
+    /// Use this for memory-to-memory transfers that you [`link`](Self::link).
+    /// [`load`](Self::load) ignores this setting.
+    pub const fn start(self, start: bool) -> Self {
+        self.csr_bit(tcd::CSR::START::mask, start)
+    }
+
+    const fn csr_bit(mut self, mask: u16, set: bool) -> Self {
+        if set {
+            self.csr |= mask;
+        } else {
+            self.csr &= !mask;
+        }
+        self
+    }
+
+    /// Load `next` when this image's major loop completes
+    ///
+    /// `chain` sets the image's last destination adjustment to `next`'s address,
+    /// and enables scatter / gather. Chain images into a loop to run transfers
+    /// indefinitely. Both images must stay at their current addresses while the
+    /// DMA controller can load them.
+    pub fn chain(&mut self, next: &ArmedTcd) {
+        self.dlast_sga = next as *const ArmedTcd as usize as i32;
+        self.csr |= tcd::CSR::ESG::mask;
+    }
+
+    /// Capture the transfer that's programmed in `channel`
+    ///
+    /// Program the transfer with the channel APIs, then arm the image. The
+    /// channel isn't changed, so you may reprogram it and arm another image.
+    pub fn arm(&mut self, channel: &Channel) {
+        *self = Self::from_snapshot(&channel.tcd().snapshot());
+    }
+
+    /// Load this image when `channel`'s current major loop completes
+    ///
+    /// `link` writes the image's address to the channel's DLAST_SGA, and
+    /// enables scatter / gather. It returns `true` if the link took effect
+    /// before the major loop completed. In that case, the DMA controller
+    /// loads the image when the current transfer completes, and the channel
+    /// continues with the image's transfer.
+    ///
+    /// If `link` returns `false`, the current transfer already completed, and
+    /// the link was too late. The channel's last destination adjustment is
+    /// now the image's address; [`load`](Self::load) the image, or reprogram
+    /// the channel, before you enable the channel again.
+    ///
+    /// # Safety
+    ///
+    /// The image, and the memory described by the image, must stay valid
+    /// until the transfer described by the image completes. Don't change the
+    /// image until the DMA controller loads it. You must not use `channel`'s
+    /// current transfer for anything that relies on the last destination
+    /// adjustment.
+    pub unsafe fn link(&self, channel: &mut Channel) -> bool {
+        #[cfg(feature = "cache")]
+        crate::cache::clean((self as *const Self).cast(), core::mem::size_of::<Self>());
+
+        let tcd = channel.tcd();
+        ral::write_reg!(
+            crate::ral::tcd,
+            tcd,
+            DLAST_SGA,
+            self as *const Self as usize as i32
+        );
+        // The hardware ignores ESG once the major loop completes, so the
+        // read-back tells us if the link made it in time.
+        ral::modify_reg!(crate::ral::tcd, tcd, CSR, ESG: 1);
+        ral::read_reg!(crate::ral::tcd, tcd, CSR, ESG == 1)
+    }
+
+    /// Copy this image into `channel`, and enable the channel
+    ///
+    /// Use `load` to restart a channel after its transfer completes. `load`
+    /// disables the channel, clears its completion flag, writes every TCD
+    /// register, then enables the channel. A hardware request starts the
+    /// next minor loop. If there's no hardware request, like in a
+    /// memory-to-memory transfer, [`start`](Channel::start) the channel.
+    ///
+    /// # Safety
+    ///
+    /// The memory described by the image must stay valid until the transfer
+    /// completes. See [`Channel::enable`] for more requirements.
+    pub unsafe fn load(&self, channel: &mut Channel) {
+        channel.disable();
+        channel.claim_clock();
+        channel.clear_complete();
+        channel.tcd().restore(&self.snapshot());
+        // Safety: caller upholds the transfer's requirements.
+        unsafe { channel.enable() };
+    }
+}
+
+/// A fixed number of pre-armed TCDs
+///
+/// Prepare each slot's image with [`ArmedTcd::arm`], or with the `const`
+/// builder methods, then link or load it when the channel should run the next
+/// transfer. Every image is 32-byte aligned, so a pool can hold scatter /
+/// gather descriptors; see [`TcdPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArmedPool<const N: usize> {
+    tcds: [ArmedTcd; N],
+}
+
+impl<const N: usize> ArmedPool<N> {
+    /// Create a pool of empty images
+    pub const fn new() -> Self {
+        ArmedPool {
+            tcds: [ArmedTcd::new(); N],
+        }
+    }
+
+    /// Create a pool that holds `tcds`
+    ///
+    /// Use this to build a pool in a const context.
+    pub const fn from_tcds(tcds: [ArmedTcd; N]) -> Self {
+        ArmedPool { tcds }
+    }
+
+    /// Returns the image in `slot`
+    ///
+    /// Returns `None` if `slot` is outside of the pool.
+    pub const fn get(&self, slot: usize) -> Option<&ArmedTcd> {
+        if slot < N {
+            Some(&self.tcds[slot])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the image in `slot`, for arming
+    ///
+    /// Returns `None` if `slot` is outside of the pool.
+    pub fn get_mut(&mut self, slot: usize) -> Option<&mut ArmedTcd> {
+        self.tcds.get_mut(slot)
+    }
+
+    /// Returns all of the images
+    pub const fn tcds(&self) -> &[ArmedTcd; N] {
+        &self.tcds
+    }
+
+    /// Returns all of the images, for arming
+    pub fn tcds_mut(&mut self) -> &mut [ArmedTcd; N] {
+        &mut self.tcds
+    }
+
+    /// [Chain](ArmedTcd::chain) each image to the image in the next slot
+    ///
+    /// The last image isn't chained. Once it's loaded, a channel runs every
+    /// image, in slot order, without CPU involvement. The pool must stay at
+    /// its current address while the DMA controller can load its images.
+    pub fn chain(&mut self) {
+        for slot in 1..N {
+            let (current, next) = self.tcds.split_at_mut(slot);
+            current[slot - 1].chain(&next[0]);
+        }
+    }
+}
+
+/// A pool of scatter / gather descriptors
+///
+/// This is an [`ArmedPool`]. Each descriptor is 32-byte aligned, and it has the
+/// TCD's memory layout, so the DMA controller can load it with scatter / gather.
+/// Populate each descriptor with the [`ArmedTcd`] builder methods, then
+/// [`chain`](ArmedPool::chain) the descriptors. You don't need your own
+/// `#[repr(align(32))]` types.
+///
+/// ```no_run
+/// use imxrt_dma::armed::{ArmedTcd, TcdPool};
+/// # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+///
+/// const fn copy(source: u32, destination: u32) -> ArmedTcd {
+///     ArmedTcd::new()
+///         .source::<u32>(source, 4)
+///         .destination::<u32>(destination, 4)
+///         .minor_loop_bytes(256)
+///         .iterations(1)
+///         .start(true)
+/// }
+///
+/// static mut POOL: TcdPool<2> = TcdPool::from_tcds([
+///     copy(0x2020_0000, 0x2024_0000),
+///     copy(0x2020_0100, 0x2024_0100).disable_on_completion(true),
+/// ]);
+///
+/// # fn f() {
+/// let mut channel = DMA.try_channel(7).unwrap();
+/// // Safety: the pool is only used here, and it's static.
+/// let pool = unsafe { &mut *core::ptr::addr_of_mut!(POOL) };
+/// pool.chain();
+/// // Safety: the pool and the memory that it describes are static.
+/// unsafe { pool.get(0).unwrap().load(&mut channel) };
+/// channel.start();
+/// # }
+/// ```
+pub type TcdPool<const N: usize> = ArmedPool<N>;
+
+impl<const N: usize> Default for ArmedPool<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,80 @@
+//! A channel that's ready for async transfers
+//!
+//! A transfer future only wakes when [`on_interrupt`](crate::Dma::on_interrupt)
+//! runs for its channel. If the channel doesn't interrupt on completion, or if
+//! its NVIC interrupt is masked, the future never wakes, and the task awaiting
+//! it hangs. A [`BoundChannel`] is a channel that interrupts on completion, and
+//! that has a waker. Require a `BoundChannel` in your own async APIs to catch
+//! these mistakes at construction.
+//!
+//! ```no_run
+//! use imxrt_dma::{bound::BoundChannel, Dma};
+//! # const DMA_PTR: *const () = core::ptr::null() as _;
+//! # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+//!
+//! static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+//!
+//! let mut channel = BoundChannel::new(DMA.try_channel(7).unwrap());
+//! // TODO unmask the DMA7 interrupt, and call DMA.on_interrupt(7) from its handler!
+//! assert!(channel.is_interrupt_on_completion());
+//!
+//! // Use `&mut channel` with memcpy, or the peripheral transfers...
+//! # let _: &mut imxrt_dma::channel::Channel = &mut channel;
+//! ```
+//!
+//! With a chip feature, the chip module's `bind` function also unmasks the
+//! channel's NVIC interrupt. See the [chip documentation](crate::chips).
+
+use crate::channel::Channel;
+
+use core::ops::{Deref, DerefMut};
+
+/// A channel that interrupts on completion, and that has a waker
+///
+/// See the [module documentation](crate::bound) for more information.
+///
+/// `BoundChannel` dereferences to a [`Channel`], so you can use it anywhere that
+/// you'd use a `Channel`. Don't turn off the channel's interrupt on completion.
+pub struct BoundChannel {
+    channel: Channel,
+}
+
+impl BoundChannel {
+    /// Enable `channel`'s interrupt on completion
+    ///
+    /// You're responsible for unmasking the channel's NVIC interrupt, and
+    /// calling [`on_interrupt`](crate::Dma::on_interrupt) from its handler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel doesn't have a waker. A [stolen](Channel::steal)
+    /// channel, or a channel from a [`PolledDma`](crate::PolledDma), can't wake
+    /// a transfer future.
+    pub fn new(mut channel: Channel) -> Self {
+        assert!(
+            channel.waker.is_some(),
+            "DMA channel {} doesn't have a waker",
+            channel.channel()
+        );
+        channel.set_interrupt_on_completion(true);
+        BoundChannel { channel }
+    }
+
+    /// Returns the channel
+    pub fn into_inner(self) -> Channel {
+        self.channel
+    }
+}
+
+impl Deref for BoundChannel {
+    type Target = Channel;
+    fn deref(&self) -> &Channel {
+        &self.channel
+    }
+}
+
+impl DerefMut for BoundChannel {
+    fn deref_mut(&mut self) -> &mut Channel {
+        &mut self.channel
+    }
+}
@@ -0,0 +1,160 @@
+//! Bridges to `embassy-sync` pipes and channels
+//!
+//! Use these adapters to connect DMA streams to an embassy application's
+//! inter-task plumbing.
+//!
+//! - [`receive_into_pipe`] and [`receive_into_channel`] move unread elements
+//!   from a ring [`Reader`] into a [`Pipe`] or a [`Channel`](SyncChannel).
+//! - [`transmit_from_pipe`] and [`transmit_from_channel`] take a chunk of
+//!   elements from a pipe or channel, and send it to a peripheral.
+//!
+//! Each adapter moves one chunk. Call it in a loop, usually in its own task.
+//!
+//! ```no_run
+//! use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, pipe::Pipe};
+//! use imxrt_dma::{bridge, ring::{DmaRingBuffer, Reader}};
+//! # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+//!
+//! static RX: Pipe<CriticalSectionRawMutex, 128> = Pipe::new();
+//!
+//! # async fn f(ring: &DmaRingBuffer<u8, 256>) {
+//! let channel = // DMA channel 7, writing into the ring...
+//!     # unsafe { DMA.channel(7) };
+//! // Safety: the channel writes into the ring.
+//! let mut reader = unsafe { Reader::new(&channel, ring) };
+//! loop {
+//!     bridge::receive_into_pipe(&mut reader, &RX).await;
+//! }
+//! # }
+//! ```
+//!
+//! The adapters wait for room in the pipe or channel. While they wait, the
+//! DMA controller keeps writing into the ring, and it may overwrite elements
+//! that haven't been moved. Size the ring, and the pipe or channel, so that the
+//! receiving task keeps up.
+//!
+//! This module requires the `"embassy-sync"` feature.
+
+use crate::{
+    channel::Channel,
+    peripheral::{self, Destination},
+    ring::{Length, Reader, ValidLength},
+    Element, Result,
+};
+
+use embassy_sync::{blocking_mutex::raw::RawMutex, channel::Channel as SyncChannel, pipe::Pipe};
+
+/// The most bytes that [`receive_into_pipe`] moves at once
+const CHUNK: usize = 32;
+
+/// Move unread bytes from `reader` into `pipe`
+///
+/// Waits until the ring has at least one unread byte, and until the pipe has
+/// room for at least one byte. Returns the number of bytes moved, which is
+/// never zero. Moves at most 32 bytes, which it copies out of the ring on the
+/// stack.
+pub async fn receive_into_pipe<M, const R: usize, const N: usize>(
+    reader: &mut Reader<'_, u8, R>,
+    pipe: &Pipe<M, N>,
+) -> usize
+where
+    M: RawMutex,
+    Length<u8, R>: ValidLength,
+{
+    reader.wait_available(1).await;
+    let (first, second) = reader.peek_slices();
+    let mut chunk = [0; CHUNK];
+    let len = first.copy_to_slice(&mut chunk);
+    let len = len + second.copy_to_slice(&mut chunk[len..]);
+    let moved = pipe.write(&chunk[..len]).await;
+    reader.consume(moved)
+}
+
+/// Read a chunk of bytes from `pipe`, and send them to `destination`
+///
+/// Waits until the pipe has at least one byte. The chunk size is the smaller
+/// of the `buffer` length and the number of bytes in the pipe. Returns the
+/// number of bytes sent. If `buffer` is empty, this returns `Ok(0)` without
+/// starting a transfer.
+///
+/// Bytes are read from the pipe before the DMA transfer starts. If the
+/// transfer fails, the bytes are lost.
+pub async fn transmit_from_pipe<D, M, const N: usize>(
+    channel: &mut Channel,
+    pipe: &Pipe<M, N>,
+    buffer: &mut [u8],
+    destination: &mut D,
+) -> Result<usize>
+where
+    D: Destination<u8>,
+    M: RawMutex,
+{
+    if buffer.is_empty() {
+        return Ok(0);
+    }
+    let len = pipe.read(buffer).await;
+    peripheral::write(channel, &buffer[..len], destination).await?;
+    Ok(len)
+}
+
+/// Move unread elements from `reader` into `queue`
+///
+/// Waits until the ring has at least one unread element, and until the
+/// channel has room for at least one element. Returns the number of elements
+/// moved, which is never zero.
+pub async fn receive_into_channel<E, M, const R: usize, const N: usize>(
+    reader: &mut Reader<'_, E, R>,
+    queue: &SyncChannel<M, E, N>,
+) -> usize
+where
+    E: Element,
+    M: RawMutex,
+    Length<E, R>: ValidLength,
+{
+    reader.wait_available(1).await;
+    let (first, second) = reader.peek_slices();
+    let mut elems = first.iter().chain(second.iter());
+    if let Some(elem) = elems.next() {
+        queue.send(elem).await;
+    }
+    let moved = 1 + elems
+        .take_while(|&elem| queue.try_send(elem).is_ok())
+        .count();
+    reader.consume(moved)
+}
+
+/// Receive a chunk of elements from `queue`, and send them to `destination`
+///
+/// Waits until the channel has at least one element. The chunk size is the
+/// smaller of the `buffer` length and the number of elements in the channel.
+/// Returns the number of elements sent. If `buffer` is empty, this returns
+/// `Ok(0)` without starting a transfer.
+///
+/// Elements are received before the DMA transfer starts. If the transfer
+/// fails, the elements are lost.
+pub async fn transmit_from_channel<D, E, M, const N: usize>(
+    channel: &mut Channel,
+    queue: &SyncChannel<M, E, N>,
+    buffer: &mut [E],
+    destination: &mut D,
+) -> Result<usize>
+where
+    D: Destination<E>,
+    E: Element,
+    M: RawMutex,
+{
+    let Some((head, tail)) = buffer.split_first_mut() else {
+        return Ok(0);
+    };
+    *head = queue.receive().await;
+    let mut len = 1;
+    for (slot, elem) in tail
+        .iter_mut()
+        .zip(core::iter::from_fn(|| queue.try_receive().ok()))
+    {
+        *slot = elem;
+        len += 1;
+    }
+    peripheral::write(channel, &buffer[..len], destination).await?;
+    Ok(len)
+}
@@ -0,0 +1,225 @@
+//! DMA-safe linear buffers
+//!
+//! A [`DmaBuffer`] is aligned to the Cortex-M7 data cache line, and its size
+//! is padded to a whole number of cache lines. It never shares a cache line
+//! with other data, so cache maintenance on the buffer can't affect its
+//! neighbors. See the `"cache"` feature for more information.
+//!
+//! Use [`dma_buffer!`](crate::dma_buffer) to place a `DmaBuffer` in a
+//! dedicated link section. Your linker script decides where that section
+//! lives. For example, you could place it in DTCM, or in a region that
+//! your MPU configuration marks as non-cacheable. Then, you don't need
+//! any cache maintenance at all.
+
+use core::ops::{Deref, DerefMut};
+
+/// The alignment of a [`DmaBuffer`], in bytes
+///
+/// This is the size of a Cortex-M7 data cache line.
+pub const ALIGNMENT: usize = 32;
+
+/// The default link section used by [`dma_buffer!`](crate::dma_buffer)
+pub const LINK_SECTION: &str = ".dma_buffers";
+
+/// A linear buffer that's aligned to a cache line
+///
+/// `DmaBuffer` holds `N` elements of type `T`. Its alignment is
+/// [`ALIGNMENT`], and its size is a multiple of `ALIGNMENT`.
+/// `DmaBuffer` dereferences to a slice, so you can use it anywhere
+/// you'd use a slice of elements.
+///
+/// ```
+/// use imxrt_dma::buffer::{DmaBuffer, ALIGNMENT};
+///
+/// let buffer: DmaBuffer<u8, 40> = DmaBuffer::new(0);
+/// assert_eq!(buffer.as_ptr() as usize % ALIGNMENT, 0);
+/// assert_eq!(core::mem::size_of_val(&buffer), 2 * ALIGNMENT);
+/// assert_eq!(buffer.len(), 40);
+/// ```
+#[repr(C, align(32))]
+pub struct DmaBuffer<T, const N: usize> {
+    buffer: [T; N],
+}
+
+impl<T, const N: usize> DmaBuffer<T, N> {
+    /// Catches a mismatch between `ALIGNMENT` and the `repr(align)` attribute.
+    const LAYOUT: () = assert!(
+        core::mem::align_of::<Self>() == ALIGNMENT,
+        "DMA buffer alignment does not match a cache line"
+    );
+
+    /// Create a buffer that holds `array`
+    pub const fn from_array(array: [T; N]) -> Self {
+        let () = Self::LAYOUT;
+        Self { buffer: array }
+    }
+
+    /// Returns the elements of the buffer
+    pub fn into_array(self) -> [T; N] {
+        self.buffer
+    }
+
+    /// Returns the number of elements in the buffer
+    ///
+    /// This is always `N`.
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Returns `true` if the buffer holds no elements
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Returns a pointer to the start of the buffer
+    pub const fn as_ptr(&self) -> *const T {
+        self.buffer.as_ptr()
+    }
+
+    /// Returns a mutable pointer to the start of the buffer
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.buffer.as_mut_ptr()
+    }
+
+    /// Returns the buffer as a slice
+    pub const fn as_slice(&self) -> &[T] {
+        &self.buffer
+    }
+
+    /// Returns the buffer as a mutable slice
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.buffer
+    }
+}
+
+impl<T: Copy, const N: usize> DmaBuffer<T, N> {
+    /// Create a buffer, with all elements set to `elem`
+    pub const fn new(elem: T) -> Self {
+        Self::from_array([elem; N])
+    }
+}
+
+impl<T, const N: usize> Deref for DmaBuffer<T, N> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.buffer
+    }
+}
+
+impl<T, const N: usize> DerefMut for DmaBuffer<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.buffer
+    }
+}
+
+/// Two buffers for double-buffered streaming
+///
+/// A `PingPongBuffer` holds two halves of `N` elements. While a double-buffered
+/// transfer, like [`read_ping_pong`](crate::peripheral::read_ping_pong), runs,
+/// the transfer borrows the whole buffer. It hands you one half at a time, and
+/// it only takes a half back when you ask for the next one; the DMA controller
+/// owns the other half. When there's no transfer, use
+/// [`halves`](Self::halves) and [`halves_mut`](Self::halves_mut) to access
+/// both halves.
+///
+/// Like a [`DmaBuffer`], a `PingPongBuffer` is aligned to [`ALIGNMENT`]. If
+/// each half is a multiple of `ALIGNMENT` bytes, then the halves never share
+/// a cache line.
+///
+/// ```
+/// use imxrt_dma::buffer::{PingPongBuffer, ALIGNMENT};
+///
+/// let buffer: PingPongBuffer<u16, 64> = PingPongBuffer::new(0);
+/// let [ping, pong] = buffer.halves();
+/// assert_eq!(ping.as_ptr() as usize % ALIGNMENT, 0);
+/// assert_eq!(pong.as_ptr() as usize % ALIGNMENT, 0);
+/// ```
+#[repr(C, align(32))]
+pub struct PingPongBuffer<T, const N: usize> {
+    halves: [[T; N]; 2],
+}
+
+impl<T, const N: usize> PingPongBuffer<T, N> {
+    /// Catches a mismatch between `ALIGNMENT` and the `repr(align)` attribute.
+    const LAYOUT: () = assert!(
+        core::mem::align_of::<Self>() == ALIGNMENT,
+        "DMA buffer alignment does not match a cache line"
+    );
+
+    /// Create a buffer that holds `halves`
+    pub const fn from_array(halves: [[T; N]; 2]) -> Self {
+        let () = Self::LAYOUT;
+        Self { halves }
+    }
+
+    /// Returns the halves of the buffer
+    pub fn into_array(self) -> [[T; N]; 2] {
+        self.halves
+    }
+
+    /// Returns both halves
+    pub const fn halves(&self) -> &[[T; N]; 2] {
+        &self.halves
+    }
+
+    /// Returns both halves, for writing
+    pub fn halves_mut(&mut self) -> &mut [[T; N]; 2] {
+        &mut self.halves
+    }
+}
+
+impl<T: Copy, const N: usize> PingPongBuffer<T, N> {
+    /// Create a buffer, with all elements of both halves set to `elem`
+    pub const fn new(elem: T) -> Self {
+        Self::from_array([[elem; N]; 2])
+    }
+}
+
+#[cfg(feature = "cache")]
+const _: () = assert!(ALIGNMENT == crate::cache::LINE_SIZE);
+
+/// Define a `static mut` [`DmaBuffer`](crate::buffer::DmaBuffer) in a DMA link section
+///
+/// By default, the buffer is placed in the [`LINK_SECTION`](crate::buffer::LINK_SECTION)
+/// section. To use a different section, start the macro with `section = "..."`.
+///
+/// The section holds initialized data. Make sure that your linker script places the
+/// section in DMA-accessible memory, and that your runtime initializes it like
+/// `.data`. If the section isn't in non-cacheable memory, enable the `"cache"`
+/// feature.
+///
+/// ```
+/// use imxrt_dma::{buffer::DmaBuffer, dma_buffer};
+///
+/// dma_buffer! {
+///     /// Receive buffer for LPUART2.
+///     static mut RX: DmaBuffer<u8, 64> = DmaBuffer::new(0);
+/// }
+///
+/// dma_buffer! {
+///     section = ".ocram_nocache";
+///     static mut TX: DmaBuffer<u32, 16> = DmaBuffer::new(0);
+/// }
+/// ```
+#[macro_export]
+macro_rules! dma_buffer {
+    (
+        section = $section:literal;
+        $(#[$attr:meta])*
+        $vis:vis static mut $name:ident: $ty:ty = $init:expr;
+    ) => {
+        $(#[$attr])*
+        #[link_section = $section]
+        $vis static mut $name: $ty = $init;
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis static mut $name:ident: $ty:ty = $init:expr;
+    ) => {
+        $crate::dma_buffer! {
+            section = ".dma_buffers";
+            $(#[$attr])*
+            $vis static mut $name: $ty = $init;
+        }
+    };
+}
@@ -0,0 +1,175 @@
+//! Data cache maintenance
+//!
+//! The DMA controller doesn't see the Cortex-M7 data cache. If a buffer
+//! lives in cacheable memory, software must clean the cache before the
+//! DMA controller reads the buffer, and invalidate the cache before the
+//! CPU reads data that the DMA controller wrote.
+//!
+//! This module requires the `"cache"` feature.
+//!
+//! The [`memcpy`](crate::memcpy) and [`peripheral`](crate::peripheral)
+//! transfers use a [`Region`] to maintain the cache for their buffers. They
+//! clean the source buffer before the transfer starts. They clean and
+//! invalidate the destination buffer before the transfer starts, then
+//! invalidate it again when the transfer completes.
+//!
+//! Cache maintenance works on whole cache lines. If a destination buffer
+//! shares a cache line with other data, and the CPU writes that data during
+//! the transfer, either the CPU's write or the DMA controller's write may be
+//! lost. Align destination buffers to [`LINE_SIZE`] bytes, and pad them to a
+//! multiple of [`LINE_SIZE`] bytes, to avoid sharing cache lines.
+
+use cortex_m::{asm, peripheral::CBP};
+
+/// The Cortex-M7 data cache line size, in bytes
+pub(crate) const LINE_SIZE: usize = 32;
+
+const LINE_MASK: usize = LINE_SIZE - 1;
+
+/// Hosts don't have a Cortex-M7 data cache
+const SIMULATED: bool = cfg!(not(target_os = "none"));
+
+/// Clean (write back) all cache lines that cover `len` bytes at `addr`
+///
+/// Use this after the CPU writes memory that the DMA controller will read.
+pub(crate) fn clean(addr: *const u8, len: usize) {
+    if len == 0 || SIMULATED {
+        return;
+    }
+    let start = addr as usize & !LINE_MASK;
+    let end = addr as usize + len;
+
+    asm::dsb();
+    for line in (start..end).step_by(LINE_SIZE) {
+        // Safety: CBP registers are write-only and stateless.
+        unsafe { (*CBP::PTR).dccmvac.write(line as u32) };
+    }
+    asm::dsb();
+    asm::isb();
+}
+
+/// Clean and invalidate all cache lines that cover `len` bytes at `addr`
+///
+/// Use this before the DMA controller writes memory. Afterwards, the cache
+/// holds no lines for the range, so an eviction can't overwrite the DMA
+/// controller's data.
+pub(crate) fn clean_invalidate(addr: *const u8, len: usize) {
+    if len == 0 || SIMULATED {
+        return;
+    }
+    let start = addr as usize & !LINE_MASK;
+    let end = addr as usize + len;
+
+    asm::dsb();
+    for line in (start..end).step_by(LINE_SIZE) {
+        // Safety: CBP registers are write-only and stateless. Dirty
+        // lines are written back, so no data is lost.
+        unsafe { (*CBP::PTR).dccimvac.write(line as u32) };
+    }
+    asm::dsb();
+    asm::isb();
+}
+
+/// Invalidate all cache lines that cover `len` bytes at `addr`
+///
+/// Use this before the CPU reads memory that the DMA controller wrote.
+///
+/// A cache line that's only partially covered by the range may hold other
+/// data. Those lines are cleaned before they're invalidated, so their
+/// neighbors' data isn't lost.
+///
+/// # Safety
+///
+/// Any CPU writes to the range that are still in the cache are lost.
+pub(crate) unsafe fn invalidate(addr: *const u8, len: usize) {
+    if len == 0 || SIMULATED {
+        return;
+    }
+    let start = addr as usize;
+    let end = start + len;
+
+    asm::dsb();
+    for line in ((start & !LINE_MASK)..end).step_by(LINE_SIZE) {
+        let partial = line < start || line + LINE_SIZE > end;
+        // Safety: CBP registers are write-only and stateless. Caller
+        // accepts that we'll lose dirty lines in this range.
+        unsafe {
+            if partial {
+                (*CBP::PTR).dccimvac.write(line as u32);
+            } else {
+                (*CBP::PTR).dcimvac.write(line as u32);
+            }
+        }
+    }
+    asm::dsb();
+    asm::isb();
+}
+
+/// Invalidate all cache lines that cover `len` bytes at `addr`, without cleaning them
+///
+/// Unlike [`invalidate`], this never writes back a line that the range only
+/// partially covers. Use this when the DMA controller may be writing the
+/// rest of the line.
+///
+/// # Safety
+///
+/// The caller must own every line that covers the range; no other data may
+/// share those lines. Any CPU writes to those lines that are still in the
+/// cache are lost.
+pub(crate) unsafe fn invalidate_lines(addr: *const u8, len: usize) {
+    if len == 0 || SIMULATED {
+        return;
+    }
+    let start = addr as usize & !LINE_MASK;
+    let end = addr as usize + len;
+
+    asm::dsb();
+    for line in (start..end).step_by(LINE_SIZE) {
+        // Safety: CBP registers are write-only and stateless. Caller
+        // owns the lines, and accepts that we'll lose dirty lines.
+        unsafe { (*CBP::PTR).dcimvac.write(line as u32) };
+    }
+    asm::dsb();
+    asm::isb();
+}
+
+/// The memory of a transfer's buffer
+///
+/// A `Region` doesn't borrow the buffer. The transfer that owns the region
+/// also holds the buffer's lifetime.
+#[derive(Clone, Copy)]
+pub(crate) struct Region {
+    addr: usize,
+    len: usize,
+}
+
+impl Region {
+    /// The memory of `buffer`
+    pub(crate) fn of<E>(buffer: &[E]) -> Self {
+        Region {
+            addr: buffer.as_ptr() as usize,
+            len: core::mem::size_of_val(buffer),
+        }
+    }
+
+    /// Prepare the region for the DMA controller to read
+    pub(crate) fn before_dma_read(self) {
+        clean(self.addr as *const u8, self.len);
+    }
+
+    /// Prepare the region for the DMA controller to write
+    pub(crate) fn before_dma_write(self) {
+        clean_invalidate(self.addr as *const u8, self.len);
+    }
+
+    /// Prepare the region for the CPU to read what the DMA controller wrote
+    ///
+    /// # Safety
+    ///
+    /// The DMA controller must be done writing the region. See
+    /// [`invalidate`] for more requirements.
+    pub(crate) unsafe fn after_dma_write(self) {
+        // Safety: caller upholds the requirements.
+        unsafe { invalidate(self.addr as *const u8, self.len) };
+    }
+}
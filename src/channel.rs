@@ -9,11 +9,19 @@
 
 use crate::{
     element::Element,
-    ral::{self, dma, dmamux, tcd::BandwidthControl, Static},
-    Error,
+    ral::{
+        self, dma, dmamux,
+        tcd::{BandwidthControl, BITER},
+        Static,
+    },
+    Controller, Error, Multiplexer as Mux,
 };
 
-impl<const CHANNELS: usize> super::Dma<CHANNELS> {
+use core::sync::atomic::{AtomicU32, Ordering};
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    super::Dma<CHANNELS, WAKERS, WATCHERS>
+{
     /// Creates the DMA channel described by `index`.
     ///
     /// # Safety
@@ -25,14 +33,200 @@ impl<const CHANNELS: usize> super::Dma<CHANNELS> {
     /// # Panics
     ///
     /// Panics if `index` is greater than or equal to the maximum number of channels.
+    ///
+    /// `channel` doesn't participate in the allocation tracking performed by
+    /// [`try_channel`](Self::try_channel).
     pub unsafe fn channel(&'static self, index: usize) -> Channel {
+        // Safety: caller upholds the same requirements.
+        unsafe { self.erase().channel(index) }
+    }
+
+    /// Creates the DMA channel described by `index`, or returns an error if
+    /// `index` is invalid.
+    ///
+    /// Unlike [`channel`](Self::channel), this returns
+    /// [`ConfigurationError::InvalidChannel`](crate::ConfigurationError::InvalidChannel)
+    /// if `index` is greater than or equal to the maximum number of channels.
+    ///
+    /// # Safety
+    ///
+    /// See [`channel`](Self::channel).
+    pub unsafe fn checked_channel(&'static self, index: usize) -> Result<Channel, Error> {
+        // Safety: caller upholds the same requirements.
+        unsafe { self.erase().checked_channel(index) }
+    }
+
+    /// Returns the eDMA3 / eDMA4 channel registers for `index`
+    ///
+    /// Returns `None` if this driver doesn't manage an eDMA3 or eDMA4 controller.
+    #[cfg(feature = "edma34")]
+    pub(crate) fn edma34_channel(
+        &self,
+        index: usize,
+    ) -> Option<Static<ral::edma34::ChannelRegisterBlock>> {
         assert!(index < CHANNELS);
+        match Registers::new(self.controller, index) {
+            Registers::EDma34 { channel, .. } => Some(channel),
+            _ => None,
+        }
+    }
+
+    /// Allocates the DMA channel described by `index`, if it's available.
+    ///
+    /// Returns `None` if `index` is greater than or equal to the maximum number of
+    /// channels, or if the channel is already allocated by `try_channel`. An
+    /// allocated channel isn't returned by `try_channel` again until it's
+    /// released. Only a [`PooledChannel`](crate::pool::PooledChannel) releases
+    /// its channel, when it's dropped; other channels stay allocated for the
+    /// rest of the program.
+    ///
+    /// Unlike [`channel`](Self::channel), `try_channel` is safe. However, it can't
+    /// detect channels created by `channel`. If you mix the two methods, make sure
+    /// that you don't create aliasing channels.
+    ///
+    /// ```
+    /// use imxrt_dma::Dma;
+    /// # const DMA_PTR: *const () = core::ptr::null() as _;
+    /// # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+    ///
+    /// static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+    ///
+    /// let channel = DMA.try_channel(7);
+    /// assert!(channel.is_some());
+    /// assert!(DMA.try_channel(7).is_none());
+    /// assert!(DMA.try_channel(32).is_none());
+    /// ```
+    pub fn try_channel(&'static self, index: usize) -> Option<Channel> {
+        self.erase().try_channel(index)
+    }
+
+    /// Return a channel allocated by `try_channel` or `channels` to the allocator
+    ///
+    /// The caller must have dropped, or must never again use, the channel.
+    pub(crate) fn release(&self, index: usize) {
+        let mask = 1 << (index % 32);
+        self.allocated[index / 32].fetch_and(!mask, Ordering::Release);
+    }
+
+    /// Allocates all available DMA channels.
+    ///
+    /// The array is indexed by channel number. An element is `None` if that
+    /// channel was already allocated by [`try_channel`](Self::try_channel) or
+    /// by a previous call to `channels`. The first call returns every channel
+    /// that hasn't been allocated; subsequent calls return an array of `None`.
+    ///
+    /// Like `try_channel`, `channels` can't detect channels created by the
+    /// unsafe [`channel`](Self::channel) method.
+    ///
+    /// ```
+    /// use imxrt_dma::Dma;
+    /// # const DMA_PTR: *const () = core::ptr::null() as _;
+    /// # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+    ///
+    /// static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+    ///
+    /// let channel_3 = DMA.try_channel(3).unwrap();
+    ///
+    /// let mut channels = DMA.channels();
+    /// assert!(channels[3].is_none());
+    /// let channel_7 = channels[7].take().unwrap();
+    ///
+    /// assert!(DMA.channels().iter().all(Option::is_none));
+    /// ```
+    pub fn channels(&'static self) -> [Option<Channel>; CHANNELS] {
+        let allocated: [u32; 2] = core::array::from_fn(|word| {
+            let channels = CHANNELS.saturating_sub(32 * word);
+            let mask = if channels >= 32 {
+                u32::MAX
+            } else {
+                (1 << channels) - 1
+            };
+            self.allocated[word].fetch_or(mask, Ordering::AcqRel)
+        });
+        core::array::from_fn(|index| {
+            if allocated[index / 32] & (1 << (index % 32)) != 0 {
+                None
+            } else {
+                // Safety: this call marked the channel as allocated. No
+                // other call to try_channel or channels will create it.
+                Some(unsafe { self.channel(index) })
+            }
+        })
+    }
+}
+
+impl crate::DmaRef {
+    /// Creates the DMA channel described by `index`.
+    ///
+    /// See [`Dma::channel`](crate::Dma::channel) for more information.
+    ///
+    /// # Safety
+    ///
+    /// This will create a handle that may alias global, mutable state. You should only create
+    /// one channel per index. If there are multiple channels for the same index, you're
+    /// responsible for ensuring synchronized access.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than or equal to the maximum number of channels.
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.channel"
+    )]
+    pub unsafe fn channel(&self, index: usize) -> Channel {
+        assert!(index < self.channel_count());
+        self.clock.enable_for_channel();
+        let registers = Registers::new(self.controller, index);
         Channel {
             index,
-            registers: self.controller,
-            multiplexer: self.multiplexer,
-            waker: &self.wakers[index],
+            registers,
+            mask: 1 << (index % 32),
+            tcd: registers.tcd(index),
+            waker: self.wakers.get(index),
+            latched_error: self.errors.get(index),
+            transfer_id: self.transfer_ids.get(index),
+            #[cfg(feature = "telemetry")]
+            telemetry: self.telemetry.get(index),
+            memory_map: Some(self.memory_map),
+            #[cfg(feature = "clock-gating")]
+            clock: Some(self.clock),
+        }
+    }
+
+    /// Creates the DMA channel described by `index`, or returns an error if
+    /// `index` is invalid.
+    ///
+    /// See [`Dma::checked_channel`](crate::Dma::checked_channel) for more information.
+    ///
+    /// # Safety
+    ///
+    /// See [`channel`](Self::channel).
+    pub unsafe fn checked_channel(&self, index: usize) -> Result<Channel, Error> {
+        if index < self.channel_count() {
+            // Safety: caller upholds the same requirements. Index is valid.
+            Ok(unsafe { self.channel(index) })
+        } else {
+            Err(Error::configuration(
+                crate::ConfigurationError::InvalidChannel,
+            ))
+        }
+    }
+
+    /// Allocates the DMA channel described by `index`, if it's available.
+    ///
+    /// See [`Dma::try_channel`](crate::Dma::try_channel) for more information.
+    pub fn try_channel(&self, index: usize) -> Option<Channel> {
+        if index >= self.channel_count() {
+            return None;
+        }
+        let mask = 1 << (index % 32);
+        let allocated = self.allocated[index / 32].fetch_or(mask, Ordering::AcqRel);
+        if allocated & mask != 0 {
+            return None;
         }
+        // Safety: we just marked this channel as allocated. No other call
+        // to try_channel will create this channel.
+        Some(unsafe { self.channel(index) })
     }
 }
 
@@ -44,17 +238,231 @@ impl<const CHANNELS: usize> super::Dma<CHANNELS> {
 /// The `Channel` stores memory addresses independent of the memory lifetime. You must make
 /// sure that the channel's state is valid before enabling a transfer!
 pub struct Channel {
-    /// Our channel number, expected to be between [0, 32)
+    /// Our channel number, expected to be between [0, 32), or [0, 64) for eDMA4
     index: usize,
     /// Reference to the DMA registers
-    registers: Static<dma::RegisterBlock>,
-    /// Reference to the DMA multiplexer
-    multiplexer: Static<dmamux::RegisterBlock>,
-    /// This channel's waker.
-    pub(crate) waker: &'static super::SharedWaker,
+    registers: Registers,
+    /// This channel's bit in the controller's 32-bit status registers
+    mask: u32,
+    /// This channel's transfer control descriptor
+    tcd: Static<crate::ral::tcd::RegisterBlock>,
+    /// This channel's waker, if the driver has wakers.
+    pub(crate) waker: Option<&'static super::SharedWaker>,
+    /// This channel's latched error status, if the driver has wakers.
+    latched_error: Option<&'static AtomicU32>,
+    /// The ID of this channel's most recent transfer, if the driver has wakers.
+    transfer_id: Option<&'static AtomicU32>,
+    /// This channel's activity counters, if the channel belongs to a driver.
+    #[cfg(feature = "telemetry")]
+    pub(crate) telemetry: Option<&'static crate::telemetry::SharedTelemetry>,
+    /// The memory that the DMA controller can reach, if the channel belongs to a driver.
+    pub(crate) memory_map: Option<&'static crate::memory::SharedMemoryMap>,
+    /// The driver's clock, if the channel belongs to a driver.
+    #[cfg(feature = "clock-gating")]
+    clock: Option<&'static crate::clock::Clock>,
+}
+
+/// A channel's transfer status, from [`Channel::transfer_status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TransferStatus {
+    /// The channel has an error
+    Error,
+    /// The major loop completed
+    Complete,
+    /// The channel is enabled, and the transfer is in progress
+    Enabled,
+    /// The channel is disabled
+    Disabled,
+}
+
+/// The registers used by a channel
+///
+/// eDMA channels use the controller's registers, and a separate multiplexer.
+/// eDMA3 and eDMA4 channels have their own page of registers.
+#[derive(Clone, Copy)]
+enum Registers {
+    EDma {
+        controller: Static<dma::RegisterBlock>,
+        multiplexer: Mux,
+    },
+    #[cfg(feature = "edma34")]
+    EDma34 {
+        management: Static<ral::edma34::RegisterBlock>,
+        channel: Static<ral::edma34::ChannelRegisterBlock>,
+        /// The hardware request status register that holds this channel's bit
+        hrs: Static<ral::RORegister<u32>>,
+    },
+}
+
+impl Registers {
+    #[cfg_attr(not(feature = "edma34"), allow(unused_variables))]
+    const fn new(controller: Controller, index: usize) -> Self {
+        match controller {
+            Controller::EDma {
+                controller,
+                multiplexer,
+            } => Registers::EDma {
+                controller,
+                multiplexer,
+            },
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) => Self::edma34(
+                management,
+                index,
+                ral::edma34::EDMA3_CHANNEL_STRIDE,
+                ral::edma34::EDMA3_HRS_OFFSET,
+            ),
+            #[cfg(feature = "edma34")]
+            Controller::EDma4(management) => Self::edma34(
+                management,
+                index,
+                ral::edma34::EDMA4_CHANNEL_STRIDE,
+                ral::edma34::EDMA4_HRS_OFFSET + 4 * (index / 32),
+            ),
+        }
+    }
+
+    #[cfg(feature = "edma34")]
+    const fn edma34(
+        management: Static<ral::edma34::RegisterBlock>,
+        index: usize,
+        stride: usize,
+        hrs_offset: usize,
+    ) -> Self {
+        let base = management.0 as *const u8;
+        // Safety: offsets stay within the controller's register block.
+        unsafe {
+            Registers::EDma34 {
+                management,
+                channel: Static(
+                    base.add(ral::edma34::CHANNEL_OFFSET + index * stride)
+                        .cast(),
+                ),
+                hrs: Static(base.add(hrs_offset).cast()),
+            }
+        }
+    }
+
+    /// Returns the transfer control descriptor for channel `index`
+    fn tcd(&self, index: usize) -> Static<crate::ral::tcd::RegisterBlock> {
+        match self {
+            // Pointer arithmetic, so that creating a channel never touches the
+            // registers.
+            Registers::EDma { controller, .. } => Static(
+                controller
+                    .0
+                    .cast::<u8>()
+                    .wrapping_add(
+                        core::mem::offset_of!(dma::RegisterBlock, TCD)
+                            + index * core::mem::size_of::<crate::ral::tcd::RegisterBlock>(),
+                    )
+                    .cast(),
+            ),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => Static(
+                channel
+                    .0
+                    .cast::<u8>()
+                    .wrapping_add(core::mem::offset_of!(
+                        ral::edma34::ChannelRegisterBlock,
+                        TCD
+                    ))
+                    .cast(),
+            ),
+        }
+    }
+
+    /// Identifies the controller that owns these registers
+    fn controller_address(&self) -> *const () {
+        match self {
+            Registers::EDma { controller, .. } => controller.0.cast(),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { management, .. } => management.0.cast(),
+        }
+    }
 }
 
 impl Channel {
+    /// Steal the eDMA channel described by `index`, without a `Dma` driver
+    ///
+    /// Use this in a panic or fault handler that needs to forcibly stop a DMA
+    /// transfer, like one that might be scribbling over memory while you dump state.
+    /// `controller` and `multiplexer` are the same pointers you'd provide to
+    /// [`Dma::new`](crate::Dma::new).
+    ///
+    /// ```no_run
+    /// use imxrt_dma::channel::Channel;
+    /// # const DMA_PTR: *const () = core::ptr::null() as _;
+    /// # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+    ///
+    /// // In a panic handler...
+    /// for index in 0..32 {
+    ///     // Safety: the rest of the program is no longer running.
+    ///     let channel = unsafe { Channel::steal(DMA_PTR, DMAMUX_PTR, index) };
+    ///     channel.disable();
+    /// }
+    /// ```
+    ///
+    /// A stolen channel isn't associated with a driver's waker. Don't use it with
+    /// transfer futures, or with [`on_interrupt`](crate::Dma::on_interrupt).
+    ///
+    /// # Safety
+    ///
+    /// This creates a channel that aliases any other channel with the same index,
+    /// and it bypasses channel allocation. Caller must make sure that the pointers
+    /// are valid for the MCU, and that the rest of the program won't observe
+    /// the channel's changes, or can tolerate them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than or equal to 32. See [`checked_steal`](Self::checked_steal)
+    /// for a function that doesn't panic.
+    pub unsafe fn steal(controller: *const (), multiplexer: *const (), index: usize) -> Channel {
+        assert!(index < 32);
+        let registers = Registers::EDma {
+            controller: Static(controller.cast()),
+            multiplexer: Mux::Dmamux(Static(multiplexer.cast())),
+        };
+        Channel {
+            index,
+            registers,
+            mask: 1 << index,
+            tcd: registers.tcd(index),
+            waker: None,
+            latched_error: None,
+            transfer_id: None,
+            #[cfg(feature = "telemetry")]
+            telemetry: None,
+            memory_map: None,
+            #[cfg(feature = "clock-gating")]
+            clock: None,
+        }
+    }
+
+    /// Creates the DMA channel described by `index`, without a driver, or
+    /// returns an error if `index` is invalid
+    ///
+    /// Unlike [`steal`](Self::steal), this returns
+    /// [`ConfigurationError::InvalidChannel`](crate::ConfigurationError::InvalidChannel)
+    /// if `index` is greater than or equal to 32.
+    ///
+    /// # Safety
+    ///
+    /// See [`steal`](Self::steal).
+    pub unsafe fn checked_steal(
+        controller: *const (),
+        multiplexer: *const (),
+        index: usize,
+    ) -> Result<Channel, Error> {
+        if index >= 32 {
+            return Err(Error::configuration(
+                crate::ConfigurationError::InvalidChannel,
+            ));
+        }
+        // Safety: caller upholds the same requirements.
+        Ok(unsafe { Self::steal(controller, multiplexer, index) })
+    }
+
     /// Enable the DMA channel for transfers
     ///
     /// # Safety
@@ -68,18 +476,75 @@ impl Channel {
     ///   else
     /// - if the transfer uses a circular buffer, you must ensure that the circular
     ///   buffer is correctly sized and aligned.
+    ///
+    /// # Panics
+    ///
+    /// With debug assertions, panics if the channel's TCD is inconsistent. See
+    /// [`start`](Self::start) for the checks.
     pub unsafe fn enable(&self) {
-        // Immutable write OK. No other methods directly modify ERQ.
-        self.registers.SERQ.write(self.index as u8);
+        self.claim_clock();
+        #[cfg(debug_assertions)]
+        self.validate_tcd();
+        match self.registers() {
+            // Immutable write OK. No other methods directly modify ERQ.
+            Registers::EDma { controller, .. } => controller.SERQ.write(self.index as u8),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                // Don't clear DONE by writing it back.
+                ral::modify_reg!(crate::ral::edma34, channel, CH_CSR, ERQ: 1, DONE: 0)
+            }
+        }
     }
 
     /// Returns the DMA channel number
     ///
     /// Channels are unique and numbered within the half-open range `[0, 32)`.
+    /// On chips with more than one DMA controller, channel numbers are only
+    /// unique within a controller.
     pub fn channel(&self) -> usize {
         self.index
     }
 
+    /// Create another handle to this channel
+    ///
+    /// # Safety
+    ///
+    /// Only use the handle to query the channel's state. Don't use it to
+    /// modify registers, or to run transfers.
+    pub(crate) unsafe fn alias(&self) -> Channel {
+        Channel {
+            index: self.index,
+            registers: self.registers,
+            mask: self.mask,
+            tcd: self.tcd,
+            waker: self.waker,
+            latched_error: self.latched_error,
+            transfer_id: self.transfer_id,
+            #[cfg(feature = "telemetry")]
+            telemetry: self.telemetry,
+            memory_map: self.memory_map,
+            #[cfg(feature = "clock-gating")]
+            clock: self.clock,
+        }
+    }
+
+    /// Returns `true` if `other` is managed by the same DMA controller as this channel
+    ///
+    /// Channel numbers are only unique within a controller. On chips with more
+    /// than one DMA controller, use this to make sure that two channels can
+    /// coordinate with each other. The driver checks this wherever it combines
+    /// channels, like [`set_minor_loop_link`](Self::set_minor_loop_link),
+    /// [`full_duplex`](crate::peripheral::full_duplex), and
+    /// [`ChannelPool::acquire_partner`](crate::pool::ChannelPool::acquire_partner).
+    pub fn is_same_controller(&self, other: &Channel) -> bool {
+        self.registers.controller_address() == other.registers.controller_address()
+    }
+
+    /// Returns `true` if this channel is managed by `controller`
+    pub(crate) fn is_managed_by(&self, controller: Controller) -> bool {
+        self.registers.controller_address() == Registers::new(controller, 0).controller_address()
+    }
+
     /// Set the channel's bandwidth control
     ///
     /// - `None` disables bandwidth control (default setting)
@@ -99,8 +564,43 @@ impl Channel {
     }
 
     /// Returns a handle to this channel's transfer control descriptor
-    fn tcd(&self) -> &crate::ral::tcd::RegisterBlock {
-        &self.registers.TCD[self.index]
+    #[inline(always)]
+    pub(crate) fn tcd(&self) -> &crate::ral::tcd::RegisterBlock {
+        &self.tcd
+    }
+
+    /// Returns this channel's registers
+    #[inline(always)]
+    fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    /// Note that this channel is using the controller, and enable the
+    /// controller's clock, if the driver gates the clock when it's idle
+    ///
+    /// Call this before programming the channel for a transfer. Queries of
+    /// the channel's status don't claim the clock. [`disable`](Self::disable)
+    /// ends the claim.
+    #[inline(always)]
+    pub(crate) fn claim_clock(&self) {
+        #[cfg(feature = "clock-gating")]
+        if let Some(clock) = self.clock {
+            clock.claim(self.index);
+        }
+    }
+
+    /// Stop using the controller, and gate its clock if no other channel is
+    /// using it
+    ///
+    /// Only gates the clock if the driver gates the clock when it's idle.
+    /// Don't access the channel's registers after this call, unless you're
+    /// about to use the channel again.
+    #[cfg(feature = "clock-gating")]
+    pub(crate) fn release_clock(&self) {
+        if let Some(clock) = self.clock {
+            clock.release(self.index);
+            clock.gate_if_idle();
+        }
     }
 
     /// Set the source address for a DMA transfer
@@ -120,6 +620,15 @@ impl Channel {
         ral::write_reg!(crate::ral::tcd, tcd, SADDR, saddr as u32);
     }
 
+    /// Returns the current source address
+    ///
+    /// The DMA engine updates the source address as it performs a
+    /// transfer. The value may change as soon as it's read.
+    pub fn source_address(&self) -> *const () {
+        let tcd = self.tcd();
+        ral::read_reg!(crate::ral::tcd, tcd, SADDR) as *const ()
+    }
+
     /// Set the source offset *in bytes*
     ///
     /// `offset` could be negative, which would decrement the address.
@@ -151,6 +660,15 @@ impl Channel {
         ral::write_reg!(crate::ral::tcd, tcd, DADDR, daddr as u32);
     }
 
+    /// Returns the current destination address
+    ///
+    /// The DMA engine updates the destination address as it performs a
+    /// transfer. The value may change as soon as it's read.
+    pub fn destination_address(&self) -> *const () {
+        let tcd = self.tcd();
+        ral::read_reg!(crate::ral::tcd, tcd, DADDR) as *const ()
+    }
+
     /// Set the destination offset *in bytes*
     ///
     /// `offset` could be negative, which would decrement the address.
@@ -168,12 +686,16 @@ impl Channel {
 
     /// Set the transfer attributes for the source
     ///
+    /// The source's element size may differ from the destination's element size.
+    /// See [`try_set_minor_loop_bytes`](Self::try_set_minor_loop_bytes).
+    ///
     /// # Safety
     ///
     /// An incorrect `modulo` value may allow the DMA engine to loop back
     /// to an incorrect address. You must ensure that `modulo` is valid
     /// for your source.
     pub unsafe fn set_source_attributes<E: Element>(&self, modulo: u8) {
+        let () = E::LAYOUT;
         let tcd = self.tcd();
         ral::write_reg!(
             crate::ral::tcd,
@@ -212,12 +734,16 @@ impl Channel {
 
     /// Set the transfer attributes for the destination
     ///
+    /// The destination's element size may differ from the source's element size.
+    /// See [`try_set_minor_loop_bytes`](Self::try_set_minor_loop_bytes).
+    ///
     /// # Safety
     ///
     /// An incorrect `modulo` value may allow the DMA engine to loop back
     /// to an incorrect address. You must ensure that `modulo` is valid
     /// for your destination.
     pub unsafe fn set_destination_attributes<E: Element>(&self, modulo: u8) {
+        let () = E::LAYOUT;
         let tcd = self.tcd();
         ral::write_reg!(
             crate::ral::tcd,
@@ -233,15 +759,264 @@ impl Channel {
     /// Describes how many bytes we should transfer for each DMA service request.
     /// Note that `nbytes` of `0` is interpreted as a 4GB transfer.
     ///
+    /// When minor loop mapping is enabled, the byte count shares its register with
+    /// the minor loop offset. The driver encodes the byte count for the controller's
+    /// current setting, and keeps any minor loop offset. See
+    /// [`set_minor_loop_offset`](Self::set_minor_loop_offset).
+    ///
     /// # Safety
     ///
     /// This might allow the DMA engine to read beyond the source, or write beyond
     /// the destination. Caller must ensure that the number of bytes per minor loop
     /// is valid for the given transfer.
+    ///
+    /// # Panics
+    ///
+    /// When minor loop mapping is enabled, panics if `nbytes` doesn't fit in the
+    /// byte count. The byte count is limited to 30 bits, or 10 bits if the channel
+    /// has a minor loop offset.
     pub unsafe fn set_minor_loop_bytes(&self, nbytes: u32) {
-        // Immutable write OK. 32-bit store on NBYTES.
+        use crate::ral::tcd::NBYTES;
+        // Immutable write OK. NBYTES is only modified by this method, and by
+        // methods that take &mut self.
+        let tcd = self.tcd();
+        if !self.is_minor_loop_mapping() {
+            ral::write_reg!(crate::ral::tcd, tcd, NBYTES, nbytes);
+            return;
+        }
+
+        let offsets = tcd.NBYTES.read() & (NBYTES::SMLOE::mask | NBYTES::DMLOE::mask);
+        assert!(
+            nbytes <= self.minor_loop_bytes_limit(),
+            "Minor loop byte count is too large"
+        );
+        if offsets != 0 {
+            ral::modify_reg!(crate::ral::tcd, tcd, NBYTES, NBYTES: nbytes);
+        } else {
+            ral::write_reg!(crate::ral::tcd, tcd, NBYTES, nbytes);
+        }
+    }
+
+    /// Set the number of *bytes* to transfer per minor loop, checking it against
+    /// the source and destination element sizes
+    ///
+    /// The source and destination may have different element sizes. For example,
+    /// a transfer could pack `u8`s from a buffer into a `u32` peripheral register.
+    /// Each minor loop must move a whole number of source elements, and a whole
+    /// number of destination elements. Otherwise, the transfer fails with a loop
+    /// configuration error after it's enabled.
+    ///
+    /// Returns [`InvalidLength`](crate::ConfigurationError::InvalidLength), and
+    /// doesn't touch the hardware, if `nbytes` is zero, if it isn't a multiple of
+    /// both element sizes, or if it's too large for
+    /// [`set_minor_loop_bytes`](Self::set_minor_loop_bytes).
+    ///
+    /// ```no_run
+    /// use imxrt_dma::channel::{self, Channel};
+    /// # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// # fn f() -> imxrt_dma::Result<()> {
+    /// let mut channel: Channel = // A DMA channel
+    ///     # unsafe { DMA.channel(7) };
+    /// let source = [1u8, 2, 3, 4, 5, 6, 7, 8];
+    /// let mut destination = [0u32; 2];
+    ///
+    /// // Safety: the buffers outlive the transfer, and two minor loops
+    /// // move every byte.
+    /// unsafe {
+    ///     channel::set_source_linear_buffer(&mut channel, &source);
+    ///     channel::set_destination_linear_buffer(&mut channel, &mut destination);
+    ///     channel.try_set_minor_loop_bytes::<u8, u32>(4)?;
+    ///     channel.set_transfer_iterations(2);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// See [`set_minor_loop_bytes`](Self::set_minor_loop_bytes).
+    pub unsafe fn try_set_minor_loop_bytes<S: Element, D: Element>(
+        &self,
+        nbytes: u32,
+    ) -> Result<(), Error> {
+        let size = core::mem::size_of::<S>().max(core::mem::size_of::<D>()) as u32;
+        if nbytes == 0 || !nbytes.is_multiple_of(size) || nbytes > self.minor_loop_bytes_limit() {
+            return Err(Error::configuration(
+                crate::ConfigurationError::InvalidLength,
+            ));
+        }
+        // Safety: caller upholds the same requirements.
+        unsafe { self.set_minor_loop_bytes(nbytes) };
+        Ok(())
+    }
+
+    /// Set an offset that's applied to the source and / or destination address
+    /// after each minor loop
+    ///
+    /// Use a minor loop offset to move through a buffer with a stride, or to step
+    /// back to the start of a block. A minor loop offset requires the controller's
+    /// minor loop mapping. If it's disabled, this method enables it. Minor loop mapping
+    /// changes how every channel encodes its byte count; the driver accounts for that
+    /// in [`set_minor_loop_bytes`](Self::set_minor_loop_bytes).
+    ///
+    /// [`MinorLoopOffset::None`] removes the offset.
+    ///
+    /// # Safety
+    ///
+    /// An offset moves the addresses that the DMA engine accesses. Caller must ensure
+    /// that the offset keeps the transfer within valid memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset doesn't fit in 20 signed bits, or if the channel's current
+    /// byte count doesn't fit in 10 bits. See [`try_set_minor_loop_offset`](Self::try_set_minor_loop_offset)
+    /// for a method that doesn't panic.
+    pub unsafe fn set_minor_loop_offset(&mut self, offset: MinorLoopOffset) {
+        use crate::ral::tcd::NBYTES;
+        let (source, destination, offset) = match offset {
+            MinorLoopOffset::None => (false, false, 0),
+            MinorLoopOffset::Source(offset) => (true, false, offset),
+            MinorLoopOffset::Destination(offset) => (false, true, offset),
+            MinorLoopOffset::Both(offset) => (true, true, offset),
+        };
+        assert!(
+            (-(1 << 19)..(1 << 19)).contains(&offset),
+            "Minor loop offset must fit in 20 signed bits"
+        );
+
         let tcd = self.tcd();
-        ral::write_reg!(crate::ral::tcd, tcd, NBYTES, nbytes);
+        if !(source || destination) {
+            if self.is_minor_loop_mapping() {
+                ral::modify_reg!(crate::ral::tcd, tcd, NBYTES, SMLOE: 0, DMLOE: 0, MLOFF: 0);
+            }
+            return;
+        }
+
+        self.enable_minor_loop_mapping();
+        let nbytes = tcd.NBYTES.read();
+        let offsets = nbytes & (NBYTES::SMLOE::mask | NBYTES::DMLOE::mask);
+        let nbytes = if offsets != 0 {
+            nbytes & NBYTES::NBYTES::mask
+        } else {
+            nbytes
+        };
+        assert!(
+            nbytes <= NBYTES::NBYTES::mask,
+            "Minor loop byte count is too large for a minor loop offset"
+        );
+        ral::write_reg!(crate::ral::tcd, tcd, NBYTES,
+            SMLOE: source as u32,
+            DMLOE: destination as u32,
+            MLOFF: offset as u32,
+            NBYTES: nbytes
+        );
+    }
+
+    /// Set an offset that's applied after each minor loop, if the offset is valid
+    ///
+    /// This is like [`set_minor_loop_offset`](Self::set_minor_loop_offset), but it
+    /// returns an error instead of panicking:
+    ///
+    /// - [`InvalidValue`](crate::ConfigurationError::InvalidValue) if the offset
+    ///   doesn't fit in 20 signed bits.
+    /// - [`InvalidLength`](crate::ConfigurationError::InvalidLength) if the
+    ///   channel's current byte count doesn't fit in 10 bits.
+    ///
+    /// The channel and the controller aren't changed if there's an error.
+    ///
+    /// # Safety
+    ///
+    /// See [`set_minor_loop_offset`](Self::set_minor_loop_offset).
+    pub unsafe fn try_set_minor_loop_offset(
+        &mut self,
+        offset: MinorLoopOffset,
+    ) -> Result<(), Error> {
+        use crate::ral::tcd::NBYTES;
+        let offset_value = match offset {
+            MinorLoopOffset::None => 0,
+            MinorLoopOffset::Source(offset)
+            | MinorLoopOffset::Destination(offset)
+            | MinorLoopOffset::Both(offset) => offset,
+        };
+        if !(-(1 << 19)..(1 << 19)).contains(&offset_value) {
+            return Err(Error::configuration(
+                crate::ConfigurationError::InvalidValue,
+            ));
+        }
+        // Removing an offset never fails.
+        let nbytes = if offset == MinorLoopOffset::None {
+            0
+        } else if self.has_minor_loop_offset() {
+            self.tcd().NBYTES.read() & NBYTES::NBYTES::mask
+        } else {
+            self.tcd().NBYTES.read()
+        };
+        if nbytes > NBYTES::NBYTES::mask {
+            return Err(Error::configuration(
+                crate::ConfigurationError::InvalidLength,
+            ));
+        }
+        // Safety: caller upholds the same requirements.
+        unsafe { self.set_minor_loop_offset(offset) };
+        Ok(())
+    }
+
+    /// Returns `true` if the controller's minor loop mapping is enabled
+    fn is_minor_loop_mapping(&self) -> bool {
+        match self.registers() {
+            Registers::EDma { controller, .. } => {
+                ral::read_reg!(crate::ral::dma, controller, CR, EMLM == 1)
+            }
+            // Always enabled.
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { .. } => true,
+        }
+    }
+
+    /// Enable the controller's minor loop mapping, if it's disabled
+    fn enable_minor_loop_mapping(&self) {
+        match self.registers() {
+            Registers::EDma { controller, .. } => {
+                // Prevent a racing read-modify-write from another channel.
+                crate::critical::free(
+                    |_| ral::modify_reg!(crate::ral::dma, controller, CR, EMLM: 1, CX: 0, ECX: 0),
+                )
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { .. } => {}
+        }
+    }
+
+    /// Returns `true` if this channel has a minor loop offset
+    pub(crate) fn has_minor_loop_offset(&self) -> bool {
+        use crate::ral::tcd::NBYTES;
+        self.is_minor_loop_mapping()
+            && self.tcd().NBYTES.read() & (NBYTES::SMLOE::mask | NBYTES::DMLOE::mask) != 0
+    }
+
+    /// Returns the number of bytes moved by each minor loop
+    ///
+    /// Removes any minor loop offset from the count.
+    #[cfg(any(debug_assertions, feature = "telemetry"))]
+    pub(crate) fn minor_loop_bytes(&self) -> u32 {
+        let nbytes = self.tcd().NBYTES.read();
+        if !self.is_minor_loop_mapping() {
+            nbytes
+        } else {
+            nbytes & self.minor_loop_bytes_limit()
+        }
+    }
+
+    /// The largest byte count accepted by [`set_minor_loop_bytes`](Self::set_minor_loop_bytes)
+    pub(crate) fn minor_loop_bytes_limit(&self) -> u32 {
+        use crate::ral::tcd::NBYTES;
+        if !self.is_minor_loop_mapping() {
+            u32::MAX
+        } else if self.has_minor_loop_offset() {
+            NBYTES::NBYTES::mask
+        } else {
+            NBYTES::DMLOE::mask - 1
+        }
     }
 
     /// Tells the DMA channel how many transfer iterations to perform
@@ -249,7 +1024,12 @@ impl Channel {
     /// A 'transfer iteration' is a read from a source, and a write to a destination, with
     /// read and write sizes described by a minor loop. Each iteration requires a DMA
     /// service request, either from hardware or from software. The maximum number of iterations
-    /// is 2^15.
+    /// is 2^15 - 1. Larger counts are truncated; see [`try_set_transfer_iterations`](Self::try_set_transfer_iterations)
+    /// for a method that checks the count.
+    ///
+    /// This removes any [minor loop link](Self::set_minor_loop_link), so that a
+    /// link from the channel's last use doesn't affect its next transfer. Set
+    /// the link after the iterations.
     ///
     /// # Safety
     ///
@@ -258,33 +1038,247 @@ impl Channel {
     /// for the transfer.
     pub unsafe fn set_transfer_iterations(&mut self, iterations: u16) {
         let tcd = self.tcd();
-        // Note that this is clearing the ELINK bit. We don't have support
-        // for channel-to-channel linking right now. Clearing ELINK is intentional
-        // to use the whole 15 bits for iterations.
-        ral::modify_reg!(crate::ral::tcd, tcd, CITER, CITER: iterations);
-        ral::modify_reg!(crate::ral::tcd, tcd, BITER, BITER: iterations);
+        // Note that this is clearing the ELINK bit. Clearing ELINK is intentional
+        // to use the whole 15 bits for iterations. Users opt into the link with
+        // set_minor_loop_link.
+        tcd.CITER.write(iterations & BITER::BITER::mask);
+        tcd.BITER.write(iterations & BITER::BITER::mask);
+    }
+
+    /// Tells the DMA channel how many transfer iterations to perform, if the count is valid
+    ///
+    /// Returns [`InvalidLength`](crate::ConfigurationError::InvalidLength), and
+    /// doesn't touch the hardware, if `iterations` is zero, or if it's larger than
+    /// 2^15 - 1.
+    ///
+    /// # Safety
+    ///
+    /// See [`set_transfer_iterations`](Self::set_transfer_iterations).
+    pub unsafe fn try_set_transfer_iterations(&mut self, iterations: u16) -> Result<(), Error> {
+        if !(1..=BITER::BITER::mask).contains(&iterations) {
+            return Err(Error::configuration(
+                crate::ConfigurationError::InvalidLength,
+            ));
+        }
+        // Safety: caller upholds the same requirements.
+        unsafe { self.set_transfer_iterations(iterations) };
+        Ok(())
     }
 
     /// Returns the beginning transfer iterations setting for the channel.
     ///
     /// This reflects the last call to `set_transfer_iterations`.
     pub fn beginning_transfer_iterations(&self) -> u16 {
+        self.tcd().BITER.read() & self.transfer_iterations_mask()
+    }
+
+    /// Returns the current transfer iterations for the channel
+    ///
+    /// The DMA controller decrements this count after each transfer iteration.
+    /// It's reloaded from the beginning transfer iterations when the major loop
+    /// completes.
+    pub fn current_transfer_iterations(&self) -> u16 {
+        self.tcd().CITER.read() & self.transfer_iterations_mask()
+    }
+
+    /// The mask of the iteration count, which depends on the minor loop link
+    fn transfer_iterations_mask(&self) -> u16 {
+        if self.minor_loop_link().is_some() {
+            BITER::BITER_ELINK::mask
+        } else {
+            BITER::BITER::mask
+        }
+    }
+
+    /// Link the channel's minor loops to another channel
+    ///
+    /// When `Some(channel)`, each minor loop completion, except for the last,
+    /// sends a service request to `channel`. `channel` must belong to the same
+    /// DMA controller; see [`is_same_controller`](Self::is_same_controller). The linked channel performs one
+    /// of its own minor loops, as if software had [started](Self::start) it.
+    /// Use this for tightly-coupled transfers, like interleaving LPSPI commands
+    /// with LPSPI data. The last minor loop doesn't send the request; it
+    /// completes the major loop instead. `None` removes the link.
+    ///
+    /// The link shares a register with the [transfer iterations](Self::set_transfer_iterations).
+    /// With a link, the channel performs at most 2^9 - 1 iterations, and this
+    /// truncates a larger count. See [`try_set_minor_loop_link`](Self::try_set_minor_loop_link)
+    /// for a method that checks the count. Setting the transfer iterations
+    /// removes the link, so set the link after the iterations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` belongs to a different DMA controller, or if its
+    /// number is greater than or equal to 32.
+    pub fn set_minor_loop_link(&mut self, channel: Option<&Channel>) {
+        let link = match channel {
+            Some(channel) => {
+                assert!(
+                    self.is_same_controller(channel),
+                    "DMA channel {} is on a different controller",
+                    channel.index
+                );
+                let channel = channel.index;
+                assert!(channel < 32, "DMA channel {channel} can't be linked");
+                BITER::ELINK::mask | (channel as u16) << BITER::LINKCH::offset
+            }
+            None => 0,
+        };
         let tcd = self.tcd();
-        ral::read_reg!(crate::ral::tcd, tcd, BITER, BITER)
+        let iterations = self.beginning_transfer_iterations()
+            & if channel.is_some() {
+                BITER::BITER_ELINK::mask
+            } else {
+                BITER::BITER::mask
+            };
+        tcd.CITER.write(link | iterations);
+        tcd.BITER.write(link | iterations);
+    }
+
+    /// Link the channel's minor loops to another channel, if the link is valid
+    ///
+    /// Returns
+    ///
+    /// - [`DifferentControllers`](crate::ConfigurationError::DifferentControllers)
+    ///   if `channel` belongs to a different DMA controller.
+    /// - [`InvalidChannel`](crate::ConfigurationError::InvalidChannel) if
+    ///   `channel`'s number is greater than or equal to 32.
+    /// - [`InvalidLength`](crate::ConfigurationError::InvalidLength) if the
+    ///   channel performs more than 2^9 - 1 transfer iterations.
+    ///
+    /// If there's an error, the channel is unchanged.
+    ///
+    /// See [`set_minor_loop_link`](Self::set_minor_loop_link) for more information.
+    pub fn try_set_minor_loop_link(&mut self, channel: Option<&Channel>) -> Result<(), Error> {
+        if let Some(channel) = channel {
+            if !self.is_same_controller(channel) {
+                return Err(Error::configuration(
+                    crate::ConfigurationError::DifferentControllers,
+                ));
+            }
+            if channel.index >= 32 {
+                return Err(Error::configuration(
+                    crate::ConfigurationError::InvalidChannel,
+                ));
+            }
+            if self.beginning_transfer_iterations() > BITER::BITER_ELINK::mask {
+                return Err(Error::configuration(
+                    crate::ConfigurationError::InvalidLength,
+                ));
+            }
+        }
+        self.set_minor_loop_link(channel);
+        Ok(())
+    }
+
+    /// Returns the channel that receives a service request when one of this
+    /// channel's minor loops completes
+    ///
+    /// Returns `None` if the channel doesn't [link its minor loops](Self::set_minor_loop_link).
+    pub fn minor_loop_link(&self) -> Option<usize> {
+        let biter = self.tcd().BITER.read();
+        (biter & BITER::ELINK::mask != 0)
+            .then(|| ((biter & BITER::LINKCH::mask) >> BITER::LINKCH::offset).into())
+    }
+
+    /// Set the DMAMUX channel configuration, if the channel is idle
+    ///
+    /// Changing a channel's request source while the channel is transferring
+    /// data, or while a peripheral is requesting service, results in undefined
+    /// transfers. This method checks that the channel is disabled, inactive, and
+    /// not receiving a hardware request before it changes the configuration.
+    /// Otherwise, it returns [`ConfigurationError::ChannelBusy`](crate::ConfigurationError::ChannelBusy)
+    /// without changing the configuration.
+    ///
+    /// If the channel doesn't support the configuration, this returns
+    /// [`ConfigurationError::Unsupported`](crate::ConfigurationError::Unsupported)
+    /// instead of panicking. See [`set_channel_configuration`](Self::set_channel_configuration)
+    /// for the unsupported configurations.
+    ///
+    /// # Panics
+    ///
+    /// A custom [`Multiplexer`] may panic.
+    pub fn try_set_channel_configuration(
+        &mut self,
+        configuration: Configuration,
+    ) -> Result<(), Error> {
+        if self.is_enabled() || self.is_active() || self.is_hardware_signaling() {
+            return Err(Error::configuration(crate::ConfigurationError::ChannelBusy));
+        }
+        self.check_configuration(configuration)?;
+        self.set_channel_configuration(configuration);
+        Ok(())
+    }
+
+    /// Check that the channel supports `configuration`
+    ///
+    /// A custom multiplexer accepts any configuration.
+    fn check_configuration(&self, configuration: Configuration) -> Result<(), Error> {
+        let supported = match (self.registers(), configuration) {
+            (
+                Registers::EDma {
+                    multiplexer: Mux::Dmamux(_),
+                    ..
+                },
+                Configuration::Enable { periodic: true, .. },
+            ) => self.channel() < 4,
+            #[cfg(feature = "edma34")]
+            (
+                Registers::EDma34 { .. },
+                Configuration::Enable { periodic: true, .. } | Configuration::AlwaysOn,
+            ) => false,
+            _ => true,
+        };
+        if supported {
+            Ok(())
+        } else {
+            Err(Error::configuration(crate::ConfigurationError::Unsupported))
+        }
     }
 
     /// Set the DMAMUX channel configuration
     ///
-    /// See the [`Configuration`] documentation for more information.
+    /// See the [`Configuration`] documentation for more information. If the driver
+    /// uses a custom [`Multiplexer`], this calls the multiplexer, and the panics
+    /// described below are up to the multiplexer.
     ///
     /// # Panics
     ///
     /// Only the first four DMA channels support periodic triggering from PIT timers. This method
     /// panics if `triggering` is set for the [`Enable`](crate::channel::Configuration)
     /// variant, but the channel does not support triggering.
+    ///
+    /// eDMA3 and eDMA4 channels don't support periodic triggering, or the `AlwaysOn`
+    /// configuration. This method panics if you request either on those channels.
     pub fn set_channel_configuration(&mut self, configuration: Configuration) {
+        let multiplexer = match self.registers() {
+            Registers::EDma {
+                multiplexer: Mux::Dmamux(multiplexer),
+                ..
+            } => multiplexer,
+            Registers::EDma {
+                multiplexer: Mux::Custom(multiplexer),
+                ..
+            } => {
+                multiplexer.set_channel_configuration(self.index, configuration);
+                return;
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                let source = match configuration {
+                    Configuration::Off => 0,
+                    Configuration::Enable {
+                        source,
+                        periodic: false,
+                    } => source,
+                    _ => panic!("Requested an unsupported eDMA3 / eDMA4 channel configuration."),
+                };
+                ral::write_reg!(crate::ral::edma34, channel, CH_MUX, SRC: source);
+                return;
+            }
+        };
         // Immutable write OK. 32-bit store on configuration register.
-        let chcfg = &self.multiplexer.chcfg[self.index];
+        let chcfg = &multiplexer.chcfg[self.index];
         match configuration {
             Configuration::Off => chcfg.write(0),
             Configuration::Enable { source, periodic } => {
@@ -305,26 +1299,140 @@ impl Channel {
         }
     }
 
+    /// Check that `source` can be used in an [`Enable`](Configuration::Enable) configuration
+    ///
+    /// A custom multiplexer accepts any source.
+    pub(crate) fn check_source(&self, source: u32) -> Result<(), Error> {
+        let limit = match self.registers() {
+            Registers::EDma {
+                multiplexer: Mux::Dmamux(_),
+                ..
+            } => dmamux::RegisterBlock::SOURCE_MASK,
+            Registers::EDma {
+                multiplexer: Mux::Custom(_),
+                ..
+            } => u32::MAX,
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { .. } => ral::edma34::CH_MUX::SRC::mask,
+        };
+        if source <= limit {
+            Ok(())
+        } else {
+            Err(Error::configuration(
+                crate::ConfigurationError::InvalidSignal,
+            ))
+        }
+    }
+
     /// Returns `true` if the DMA channel is receiving a service signal from hardware
     pub fn is_hardware_signaling(&self) -> bool {
-        self.registers.HRS.read() & (1 << self.index) != 0
+        match self.registers() {
+            Registers::EDma { controller, .. } => controller.HRS.read() & self.mask != 0,
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { hrs, .. } => hrs.read() & self.mask != 0,
+        }
     }
 
     /// Disable the DMA channel, preventing any DMA transfers
     pub fn disable(&self) {
-        // Immutable write OK. No other methods directly modify ERQ.
-        self.registers.CERQ.write(self.index as u8);
+        match self.registers() {
+            // Immutable write OK. No other methods directly modify ERQ.
+            Registers::EDma { controller, .. } => controller.CERQ.write(self.index as u8),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                // Don't clear DONE by writing it back.
+                ral::modify_reg!(crate::ral::edma34, channel, CH_CSR, ERQ: 0, DONE: 0)
+            }
+        }
+        #[cfg(feature = "clock-gating")]
+        if let Some(clock) = self.clock {
+            clock.release(self.index);
+        }
+    }
+
+    /// Cancel this channel's transfer
+    ///
+    /// `cancel` disables the channel. If the channel is active, `cancel` also
+    /// stops the active minor loop, and waits for the cancel to take effect.
+    /// A [`Transfer`](crate::Transfer) that's waiting on this channel resolves
+    /// with an error that [is cancelled](Error::is_cancelled), so that you can
+    /// tell it apart from a hardware fault.
+    ///
+    /// The controller stops starting new channels until the cancel completes.
+    /// If the controller was halted, it stays halted. If the controller halts
+    /// while `cancel` runs, because of another channel's error and the
+    /// controller's halt on error setting, it stays halted.
+    pub fn cancel(&self) {
+        self.disable();
+        // One critical section, so that a concurrent halt isn't undone.
+        crate::critical::free(|_| match self.registers() {
+            Registers::EDma { controller, .. } => {
+                let halted = ral::read_reg!(crate::ral::dma, controller, CR, HALT == 1);
+                let errors = controller.ERR.read();
+                ral::modify_reg!(crate::ral::dma, controller, CR, HALT: 1, CX: 0, ECX: 0);
+                // Once halted, no other channel can start. If this channel
+                // is active, it's the channel that ECX cancels.
+                if self.is_active() {
+                    ral::modify_reg!(crate::ral::dma, controller, CR, ECX: 1, CX: 0);
+                    while ral::read_reg!(crate::ral::dma, controller, CR, ECX == 1) {}
+                }
+                // The cancel may report an error for this channel. An error
+                // from another channel halts the controller if it halts on errors.
+                let other_errors = controller.ERR.read() & !errors & !self.mask;
+                let halted_on_error =
+                    other_errors != 0 && ral::read_reg!(crate::ral::dma, controller, CR, HOE == 1);
+                if !halted && !halted_on_error {
+                    ral::modify_reg!(crate::ral::dma, controller, CR, HALT: 0, CX: 0, ECX: 0);
+                }
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { management, .. } => {
+                let halted = ral::read_reg!(crate::ral::edma34, management, CSR, HALT == 1);
+                let errors = management.ES.read();
+                ral::modify_reg!(crate::ral::edma34, management, CSR, HALT: 1, CX: 0, ECX: 0);
+                if self.is_active() {
+                    ral::modify_reg!(crate::ral::edma34, management, CSR, ECX: 1, CX: 0);
+                    while ral::read_reg!(crate::ral::edma34, management, CSR, ECX == 1) {}
+                }
+                // There's no error bitmask. A new error status that doesn't
+                // name this channel is another channel's error.
+                let status = management.ES.read();
+                let errchn = ral::read_reg!(crate::ral::edma34, management, ES, ERRCHN);
+                let other_error = status != errors && errchn as usize != self.index;
+                let halted_on_error =
+                    other_error && ral::read_reg!(crate::ral::edma34, management, CSR, HAE == 1);
+                if !halted && !halted_on_error {
+                    ral::modify_reg!(crate::ral::edma34, management, CSR, HALT: 0, CX: 0, ECX: 0);
+                }
+            }
+        })
     }
 
     /// Returns `true` if this DMA channel generated an interrupt
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.is_interrupt"
+    )]
     pub fn is_interrupt(&self) -> bool {
-        self.registers.INT.read() & (1 << self.index) != 0
+        match self.registers() {
+            Registers::EDma { controller, .. } => controller.INT.read() & self.mask != 0,
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => channel.CH_INT.read() & 1 != 0,
+        }
     }
 
     /// Clear the interrupt flag from this DMA channel
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.clear_interrupt"
+    )]
     pub fn clear_interrupt(&self) {
-        // Immutable write OK. No other methods modify INT.
-        self.registers.CINT.write(self.index as u8);
+        match self.registers() {
+            // Immutable write OK. No other methods modify INT.
+            Registers::EDma { controller, .. } => controller.CINT.write(self.index as u8),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => channel.CH_INT.write(1),
+        }
     }
 
     /// Enable or disable 'disable on completion'
@@ -344,49 +1452,463 @@ impl Channel {
         ral::modify_reg!(crate::ral::tcd, tcd, CSR, INTMAJOR: intr as u16);
     }
 
+    /// Returns `true` if the channel interrupts when the transfer completes
+    ///
+    /// See [`set_interrupt_on_completion`](Self::set_interrupt_on_completion).
+    pub fn is_interrupt_on_completion(&self) -> bool {
+        let tcd = self.tcd();
+        ral::read_reg!(crate::ral::tcd, tcd, CSR, INTMAJOR == 1)
+    }
+
+    /// Enable or disable interrupt generation when the transfer is half complete
+    ///
+    /// The interrupt fires when the current major iteration count reaches half
+    /// of the beginning major iteration count. You're responsible for registering
+    /// your interrupt handler.
+    pub fn set_interrupt_on_half(&mut self, intr: bool) {
+        let tcd = self.tcd();
+        ral::modify_reg!(crate::ral::tcd, tcd, CSR, INTHALF: intr as u16);
+    }
+
+    /// Enable or disable asynchronous DMA requests while the core is in stop mode
+    ///
+    /// When enabled, the channel can service hardware requests while the core is
+    /// in a low-power stop mode. The channel's peripheral must also be able to run
+    /// in stop mode. Disabled after reset.
+    pub fn set_asynchronous_request_in_stop(&mut self, enable: bool) {
+        match self.registers() {
+            Registers::EDma { controller, .. } => {
+                // EARS is shared by all channels. Prevent a racing
+                // read-modify-write from another channel.
+                crate::critical::free(|_| {
+                    let mask = self.mask;
+                    let ears = controller.EARS.read();
+                    controller
+                        .EARS
+                        .write(if enable { ears | mask } else { ears & !mask });
+                })
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                // Don't clear DONE by writing it back.
+                ral::modify_reg!(crate::ral::edma34, channel, CH_CSR, EARQ: enable as u32, DONE: 0)
+            }
+        }
+    }
+
+    /// Returns `true` if the channel services requests while the core is in stop mode
+    pub fn is_asynchronous_request_in_stop(&self) -> bool {
+        match self.registers() {
+            Registers::EDma { controller, .. } => controller.EARS.read() & self.mask != 0,
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                ral::read_reg!(crate::ral::edma34, channel, CH_CSR, EARQ == 1)
+            }
+        }
+    }
+
+    /// Let a higher priority channel preempt this channel
+    ///
+    /// When `true`, a channel with a higher priority, and that [can preempt](Self::set_can_preempt),
+    /// suspends this channel's minor loop to perform its own. This channel
+    /// resumes when the other channel's minor loop completes. When `false`
+    /// (default setting), this channel's minor loops always run to completion.
+    ///
+    /// Preemption only happens between channels with different
+    /// [priorities](crate::Dma::set_priorities). Use this with the priorities to
+    /// make sure that a real-time channel is serviced promptly, even while a
+    /// long minor loop is in progress.
+    pub fn set_preemptible(&mut self, preemptible: bool) {
+        match self.registers() {
+            Registers::EDma { controller, .. } => {
+                use crate::ral::dma::DCHPRI::ECP;
+                let dchpri = &controller.DCHPRI[self.index];
+                // The driver also changes the priority fields. Prevent a
+                // racing read-modify-write from another context.
+                crate::critical::free(|_| {
+                    let value = dchpri.read() & !ECP::mask;
+                    dchpri.write(if preemptible {
+                        value | ECP::mask
+                    } else {
+                        value
+                    });
+                })
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => crate::critical::free(
+                |_| ral::modify_reg!(crate::ral::edma34, channel, CH_PRI, ECP: preemptible as u32),
+            ),
+        }
+    }
+
+    /// Returns `true` if a higher priority channel can preempt this channel
+    ///
+    /// See [`set_preemptible`](Self::set_preemptible) for more information.
+    pub fn is_preemptible(&self) -> bool {
+        match self.registers() {
+            Registers::EDma { controller, .. } => {
+                use crate::ral::dma::DCHPRI::ECP;
+                controller.DCHPRI[self.index].read() & ECP::mask != 0
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                ral::read_reg!(crate::ral::edma34, channel, CH_PRI, ECP == 1)
+            }
+        }
+    }
+
+    /// Let this channel preempt a lower priority channel
+    ///
+    /// When `true` (default setting), this channel suspends a lower priority,
+    /// [preemptible](Self::set_preemptible) channel to perform its own minor
+    /// loop. When `false`, this channel waits for the other channel's minor
+    /// loop to complete.
+    pub fn set_can_preempt(&mut self, can_preempt: bool) {
+        match self.registers() {
+            Registers::EDma { controller, .. } => {
+                use crate::ral::dma::DCHPRI::DPA;
+                let dchpri = &controller.DCHPRI[self.index];
+                // See set_preemptible.
+                crate::critical::free(|_| {
+                    let value = dchpri.read() & !DPA::mask;
+                    dchpri.write(if can_preempt {
+                        value
+                    } else {
+                        value | DPA::mask
+                    });
+                })
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => crate::critical::free(
+                |_| ral::modify_reg!(crate::ral::edma34, channel, CH_PRI, DPA: !can_preempt as u32),
+            ),
+        }
+    }
+
+    /// Returns `true` if this channel can preempt a lower priority channel
+    ///
+    /// See [`set_can_preempt`](Self::set_can_preempt) for more information.
+    pub fn can_preempt(&self) -> bool {
+        match self.registers() {
+            Registers::EDma { controller, .. } => {
+                use crate::ral::dma::DCHPRI::DPA;
+                controller.DCHPRI[self.index].read() & DPA::mask == 0
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                ral::read_reg!(crate::ral::edma34, channel, CH_PRI, DPA == 0)
+            }
+        }
+    }
+
     /// Indicates if the DMA transfer has completed
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.is_complete"
+    )]
     pub fn is_complete(&self) -> bool {
-        let tcd = self.tcd();
-        ral::read_reg!(crate::ral::tcd, tcd, CSR, DONE == 1)
+        match self.registers() {
+            Registers::EDma { .. } => {
+                let tcd = self.tcd();
+                ral::read_reg!(crate::ral::tcd, tcd, CSR, DONE == 1)
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                ral::read_reg!(crate::ral::edma34, channel, CH_CSR, DONE == 1)
+            }
+        }
     }
 
     /// Clears completion indication
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.clear_complete"
+    )]
     pub fn clear_complete(&self) {
-        // Immutable write OK. CDNE affects a bit in TCD. But, other writes to
-        // TCD require &mut reference. Existence of &mut reference blocks
-        // clear_complete calls.
-        self.registers.CDNE.write(self.index as u8);
+        match self.registers() {
+            // Immutable write OK. CDNE affects a bit in TCD. But, other writes to
+            // TCD require &mut reference. Existence of &mut reference blocks
+            // clear_complete calls.
+            Registers::EDma { controller, .. } => controller.CDNE.write(self.index as u8),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                ral::modify_reg!(crate::ral::edma34, channel, CH_CSR, DONE: 1)
+            }
+        }
     }
 
     /// Indicates if the DMA channel is in an error state
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.is_error"
+    )]
     pub fn is_error(&self) -> bool {
-        self.registers.ERR.read() & (1 << self.index) != 0
+        match self.registers() {
+            Registers::EDma { controller, .. } => controller.ERR.read() & self.mask != 0,
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                ral::read_reg!(crate::ral::edma34, channel, CH_ES, ERR == 1)
+            }
+        }
     }
 
     /// Clears the error flag
     pub fn clear_error(&self) {
-        // Immutable write OK. CERR affects a bit in ERR, which is
-        // not written to elsewhere.
-        self.registers.CERR.write(self.index as u8);
+        if let Some(latched) = self.latched_error {
+            latched.store(0, Ordering::Relaxed);
+        }
+        match self.registers() {
+            // Immutable write OK. CERR affects a bit in ERR, which is
+            // not written to elsewhere.
+            Registers::EDma { controller, .. } => controller.CERR.write(self.index as u8),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                ral::write_reg!(crate::ral::edma34, channel, CH_ES, ERR: 1)
+            }
+        }
     }
 
     /// Indicates if this DMA channel is actively transferring data
     pub fn is_active(&self) -> bool {
-        let tcd = self.tcd();
-        ral::read_reg!(crate::ral::tcd, tcd, CSR, ACTIVE == 1)
+        match self.registers() {
+            Registers::EDma { .. } => {
+                let tcd = self.tcd();
+                ral::read_reg!(crate::ral::tcd, tcd, CSR, ACTIVE == 1)
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                ral::read_reg!(crate::ral::edma34, channel, CH_CSR, ACTIVE == 1)
+            }
+        }
     }
 
     /// Indicates if this DMA channel is enabled
     pub fn is_enabled(&self) -> bool {
-        self.registers.ERQ.read() & (1 << self.index) != 0
+        match self.registers() {
+            Registers::EDma { controller, .. } => controller.ERQ.read() & self.mask != 0,
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                ral::read_reg!(crate::ral::edma34, channel, CH_CSR, ERQ == 1)
+            }
+        }
+    }
+
+    /// Returns the channel's transfer status, using as few register reads as possible
+    ///
+    /// The checks run in priority order: error, then complete, then enabled.
+    /// Each check only runs if the earlier checks were false. An error that's
+    /// latched by [`on_interrupt`](crate::Dma::on_interrupt) is found without
+    /// reading a register. eDMA3 and eDMA4 channels report completion and
+    /// enable in the same register, so they need at most two reads.
+    #[inline]
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.transfer_status"
+    )]
+    pub(crate) fn transfer_status(&self) -> TransferStatus {
+        let latched = self
+            .latched_error
+            .is_some_and(|latched| latched.load(Ordering::Relaxed) != 0);
+        match self.registers() {
+            Registers::EDma { controller, .. } => {
+                if latched || controller.ERR.read() & self.mask != 0 {
+                    TransferStatus::Error
+                } else if self.tcd().CSR.read() & crate::ral::tcd::CSR::DONE::mask != 0 {
+                    TransferStatus::Complete
+                } else if controller.ERQ.read() & self.mask != 0 {
+                    TransferStatus::Enabled
+                } else {
+                    TransferStatus::Disabled
+                }
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                if latched || ral::read_reg!(crate::ral::edma34, channel, CH_ES, ERR == 1) {
+                    return TransferStatus::Error;
+                }
+                let (done, erq) = ral::read_reg!(crate::ral::edma34, channel, CH_CSR, DONE, ERQ);
+                if done != 0 {
+                    TransferStatus::Complete
+                } else if erq != 0 {
+                    TransferStatus::Enabled
+                } else {
+                    TransferStatus::Disabled
+                }
+            }
+        }
+    }
+
+    /// Copy the channel's configuration
+    pub(crate) fn save_state(&self) -> ChannelState {
+        let (multiplexer, priority) = match self.registers() {
+            Registers::EDma {
+                controller,
+                multiplexer,
+            } => (
+                match multiplexer {
+                    Mux::Dmamux(multiplexer) => Some(multiplexer.chcfg[self.index].read()),
+                    Mux::Custom(_) => None,
+                },
+                controller.DCHPRI[self.index].read() as u32,
+            ),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                (Some(channel.CH_MUX.read()), channel.CH_PRI.read())
+            }
+        };
+        ChannelState {
+            tcd: self.tcd().snapshot(),
+            multiplexer,
+            priority,
+            enabled: self.is_enabled(),
+            asynchronous_request_in_stop: self.is_asynchronous_request_in_stop(),
+        }
+    }
+
+    /// Restore the channel's configuration, except for its enable bit
+    ///
+    /// # Safety
+    ///
+    /// The state may describe a transfer. The caller must make sure that the
+    /// transfer's memory is still valid before enabling the channel.
+    pub(crate) unsafe fn restore_state(&mut self, state: &ChannelState) {
+        self.disable();
+        self.tcd().restore(&state.tcd);
+        match self.registers() {
+            Registers::EDma {
+                controller,
+                multiplexer,
+            } => {
+                if let (Mux::Dmamux(multiplexer), Some(chcfg)) = (multiplexer, state.multiplexer) {
+                    multiplexer.chcfg[self.index].write(chcfg);
+                }
+                controller.DCHPRI[self.index].write(state.priority as u8);
+            }
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { channel, .. } => {
+                if let Some(source) = state.multiplexer {
+                    channel.CH_MUX.write(source);
+                }
+                channel.CH_PRI.write(state.priority);
+            }
+        }
+        self.set_asynchronous_request_in_stop(state.asynchronous_request_in_stop);
     }
 
     /// Returns the value from the **global** error status register
     ///
     /// It may reflect the last channel that produced an error, and that
     /// may not be related to this channel.
+    ///
+    /// eDMA3 and eDMA4 channels have their own error status register. For
+    /// these channels, the error reflects this channel's status, formatted
+    /// like the eDMA error status register.
+    ///
+    /// The error includes a snapshot of this channel's TCD.
     pub fn error_status(&self) -> Error {
-        Error::new(self.registers.ES.read())
+        let error = match self.registers() {
+            Registers::EDma { controller, .. } => Error::new(controller.ES.read()),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 {
+                management,
+                channel,
+                ..
+            } => {
+                let es = channel.CH_ES.read();
+                // Error bits [7:0] match. Move the ERR flag into VLD, and
+                // add the channel number.
+                let vld = es & ral::edma34::CH_ES::ERR::mask;
+                let mut es = vld | ((self.index as u32 & 0x1F) << 8) | (es & 0xFF);
+                // The channel doesn't know that it was cancelled. Check if
+                // the management page says that this channel was cancelled.
+                let (ecx, errchn) = ral::read_reg!(crate::ral::edma34, management, ES, ECX, ERRCHN);
+                if ecx != 0 && errchn as usize == self.index {
+                    es |= 1 << 16;
+                }
+                Error::new(es).with_channel(self.index)
+            }
+        };
+        error
+            .with_tcd(self.tcd().snapshot())
+            .with_transfer_id(self.transfer_id())
+    }
+
+    /// Returns this channel's error
+    ///
+    /// An eDMA controller's error status describes the last channel that
+    /// errored. If another channel errored after this channel, this channel's
+    /// error details are lost. The error is still valid, and it names this
+    /// channel, but it doesn't indicate a cause.
+    fn channel_error(&self) -> Error {
+        let error = self.error_status();
+        if error.error_channel() == Some(self.index) {
+            error
+        } else {
+            Error::lost(self.index)
+                .with_tcd(self.tcd().snapshot())
+                .with_transfer_id(self.transfer_id())
+        }
+    }
+
+    /// Latch this channel's error, and return the latched error
+    ///
+    /// Call this when the channel's ERR flag is set. The first error is kept
+    /// until [`clear_error`](Self::clear_error). Also returns `true` if this is
+    /// the first time that the error was observed. Without a latch, every error
+    /// is new.
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.latch_error"
+    )]
+    pub(crate) fn latch_error(&self) -> (Error, bool) {
+        let error = self.channel_error();
+        let Some(latched) = self.latched_error else {
+            return (error, true);
+        };
+        let new = latched
+            .compare_exchange(0, error.raw(), Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok();
+        (self.latched_error_status(), new)
+    }
+
+    /// Returns the latched error, or this channel's error if nothing is
+    /// latched
+    ///
+    /// Never returns another channel's error status.
+    pub(crate) fn latched_error_status(&self) -> Error {
+        match self
+            .latched_error
+            .map(|latched| latched.load(Ordering::Relaxed))
+        {
+            Some(es) if es != 0 => Error::new(es)
+                .with_channel(self.index)
+                .with_tcd(self.tcd().snapshot())
+                .with_transfer_id(self.transfer_id()),
+            _ => self.channel_error(),
+        }
+    }
+
+    /// Describe a transfer that was cancelled by disabling this channel
+    pub(crate) fn cancelled_error(&self) -> Error {
+        Error::cancelled(self.index)
+            .with_tcd(self.tcd().snapshot())
+            .with_transfer_id(self.transfer_id())
+    }
+
+    /// Returns the ID of the most recent [`Transfer`](crate::Transfer) on this channel
+    ///
+    /// Returns `None` if no transfer has started on the channel, or if the
+    /// channel was [stolen](Self::steal). See [`Transfer::id`](crate::Transfer::id).
+    pub fn transfer_id(&self) -> Option<u32> {
+        let id = self.transfer_id?.load(Ordering::Relaxed);
+        (id != 0).then_some(id)
+    }
+
+    /// Note that the transfer `id` is starting on this channel
+    pub(crate) fn set_transfer_id(&self, id: u32) {
+        if let Some(transfer_id) = self.transfer_id {
+            transfer_id.store(id, Ordering::Relaxed);
+        }
     }
 
     /// Start a DMA transfer
@@ -397,11 +1919,82 @@ impl Channel {
     /// to request DMA service.
     ///
     /// Flag is automatically cleared by hardware after it's asserted.
+    ///
+    /// # Panics
+    ///
+    /// With debug assertions, `enable()` and `start()` check the channel's TCD
+    /// before the hardware sees it. They panic with a description of the
+    /// problem, instead of letting the hardware report an error, if
+    ///
+    /// - the minor loop byte count isn't a multiple of the source and destination
+    ///   transfer sizes.
+    /// - the source or destination address, or its offset, isn't aligned to its
+    ///   transfer size.
+    /// - a source or destination modulo is smaller than its transfer size.
     pub fn start(&self) {
-        // Immutable write OK. SSRT affects a bit in TCD. But, other writes to
-        // TCD require &mut reference. Existence of &mut reference blocks
-        // start calls.
-        self.registers.SSRT.write(self.index as u8);
+        self.claim_clock();
+        #[cfg(debug_assertions)]
+        self.validate_tcd();
+        match self.registers() {
+            // Immutable write OK. SSRT affects a bit in TCD. But, other writes to
+            // TCD require &mut reference. Existence of &mut reference blocks
+            // start calls.
+            Registers::EDma { controller, .. } => controller.SSRT.write(self.index as u8),
+            #[cfg(feature = "edma34")]
+            Registers::EDma34 { .. } => {
+                let tcd = self.tcd();
+                ral::modify_reg!(crate::ral::tcd, tcd, CSR, START: 1)
+            }
+        }
+    }
+}
+
+impl Channel {
+    /// Panic if the TCD would cause a configuration error
+    ///
+    /// See [`start`](Self::start) for the checks.
+    #[cfg(debug_assertions)]
+    fn validate_tcd(&self) {
+        use crate::ral::tcd::SATTR;
+
+        let tcd = self.tcd().snapshot();
+        let index = self.index;
+        let attributes = |attr: u8| {
+            (
+                1u32 << ((attr & SATTR::SIZE::mask) >> SATTR::SIZE::offset),
+                (attr & SATTR::MOD::mask) >> SATTR::MOD::offset,
+            )
+        };
+        let (ssize, smod) = attributes(tcd.SATTR);
+        let (dsize, dmod) = attributes(tcd.DATTR);
+
+        let nbytes = self.minor_loop_bytes();
+        assert!(
+            nbytes.is_multiple_of(ssize) && nbytes.is_multiple_of(dsize),
+            "DMA channel {index}: minor loop byte count {nbytes} isn't a multiple of the \
+             {ssize}-byte source size and the {dsize}-byte destination size"
+        );
+        for (name, address, offset, size, modulo) in [
+            ("source", tcd.SADDR, tcd.SOFF, ssize, smod),
+            ("destination", tcd.DADDR, tcd.DOFF, dsize, dmod),
+        ] {
+            assert!(
+                address.is_multiple_of(size),
+                "DMA channel {index}: {name} address {address:#010X} isn't aligned to \
+                 its {size}-byte transfer size"
+            );
+            assert!(
+                offset.unsigned_abs().is_multiple_of(size as u16),
+                "DMA channel {index}: {name} offset {offset} isn't a multiple of \
+                 its {size}-byte transfer size"
+            );
+            assert!(
+                modulo == 0 || 1u32 << modulo >= size,
+                "DMA channel {index}: {name} modulo of {} bytes is smaller than \
+                 its {size}-byte transfer size",
+                1u32 << modulo
+            );
+        }
     }
 }
 
@@ -410,8 +2003,69 @@ impl Channel {
 // them being (mutably) shared.
 unsafe impl Send for Channel {}
 
+/// A DMA request multiplexer
+///
+/// A multiplexer routes peripheral DMA requests to DMA channels. The i.MX RT
+/// DMAMUX is supported by [`Dma::new`](crate::Dma::new). If your DMA controller
+/// has a different multiplexer, or no multiplexer, implement this trait, and use
+/// [`Dma::with_multiplexer`](crate::Dma::with_multiplexer).
+///
+/// ```
+/// use imxrt_dma::{channel::{Configuration, Multiplexer}, Dma};
+///
+/// /// This controller's requests are hard-wired to its channels.
+/// struct NoMultiplexer;
+///
+/// impl Multiplexer for NoMultiplexer {
+///     fn set_channel_configuration(&self, _: usize, configuration: Configuration) {
+///         assert_eq!(configuration, Configuration::Off, "No multiplexer to configure");
+///     }
+/// }
+///
+/// static MUX: NoMultiplexer = NoMultiplexer;
+/// # const DMA_PTR: *const () = core::ptr::null() as _;
+/// static DMA: Dma<32> = unsafe { Dma::with_multiplexer(DMA_PTR, &MUX) };
+/// ```
+pub trait Multiplexer: Sync {
+    /// Apply `configuration` to the multiplexer's `channel`
+    ///
+    /// This is called by [`Channel::set_channel_configuration`]. `channel`
+    /// is always less than the driver's channel count.
+    fn set_channel_configuration(&self, channel: usize, configuration: Configuration);
+}
+
+/// A copy of a channel's configuration
+///
+/// See [`Dma::save_state`](crate::Dma::save_state).
+#[derive(Clone, Copy)]
+pub(crate) struct ChannelState {
+    tcd: ral::tcd::Snapshot,
+    /// `None` if the channel uses a custom multiplexer.
+    multiplexer: Option<u32>,
+    priority: u32,
+    pub(crate) enabled: bool,
+    asynchronous_request_in_stop: bool,
+}
+
+/// A minor loop offset
+///
+/// See [`Channel::set_minor_loop_offset`] for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MinorLoopOffset {
+    /// No offset
+    None,
+    /// Apply the offset to the source address
+    Source(i32),
+    /// Apply the offset to the destination address
+    Destination(i32),
+    /// Apply the offset to both the source and destination addresses
+    Both(i32),
+}
+
 /// DMAMUX channel configuration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive]
 pub enum Configuration {
     /// The DMAMUX channel is disabled
@@ -525,24 +2179,35 @@ pub unsafe fn set_destination_linear_buffer<E: Element>(chan: &mut Channel, dest
     );
 }
 
-/// Assert properties about the circular buffer
-fn circular_buffer_asserts<E>(buffer: &[E]) {
-    let len = buffer.len();
+/// Compute the modulo value of a circular buffer of `len` elements at `address`
+///
+/// Asserts that `len` is a power of two, and that `address` is aligned to the
+/// buffer's size in bytes. Channels and armed images share this computation.
+pub(crate) const fn circular_buffer_modulo<E>(address: usize, len: usize) -> u8 {
     assert!(
         len.is_power_of_two(),
         "DMA circular buffer size is not power of two"
     );
-    let start = buffer.as_ptr();
-    let size = core::mem::size_of_val(buffer);
+    let size = core::mem::size_of::<E>() * len;
     assert!(
-        (start as usize) % size == 0,
+        address & (size - 1) == 0,
         "DMA circular buffer is not properly aligned"
     );
+    size.trailing_zeros() as u8
 }
 
-/// Compute the circular buffer modulo value
-fn circular_buffer_modulo<E>(buffer: &[E]) -> u32 {
-    31 - core::mem::size_of_val(buffer).leading_zeros()
+/// Check the properties that `circular_buffer_modulo` asserts
+fn check_circular_buffer<E>(buffer: &[E]) -> Result<(), Error> {
+    if !buffer.len().is_power_of_two() {
+        return Err(Error::configuration(
+            crate::ConfigurationError::InvalidLength,
+        ));
+    }
+    let size = core::mem::size_of_val(buffer);
+    if (buffer.as_ptr() as usize) & (size.max(1) - 1) != 0 {
+        return Err(Error::configuration(crate::ConfigurationError::Alignment));
+    }
+    Ok(())
 }
 
 /// Set a circular buffer as the source for a DMA transfer
@@ -562,15 +2227,37 @@ fn circular_buffer_modulo<E>(buffer: &[E]) -> u32 {
 /// - the capacity is not a power of two
 /// - the alignment is not a multiple of the buffer's size in bytes
 pub unsafe fn set_source_circular_buffer<E: Element>(chan: &mut Channel, source: &[E]) {
-    circular_buffer_asserts(source);
-    let modulo = circular_buffer_modulo(source);
+    let modulo = circular_buffer_modulo::<E>(source.as_ptr() as usize, source.len());
 
     chan.set_source_address(source.as_ptr());
     chan.set_source_offset(core::mem::size_of::<E>() as i16);
-    chan.set_source_attributes::<E>(modulo as u8);
+    chan.set_source_attributes::<E>(modulo);
     chan.set_source_last_address_adjustment(0);
 }
 
+/// Set a circular buffer as the source for a DMA transfer, if the buffer is valid
+///
+/// This is like [`set_source_circular_buffer`], but it returns an error instead
+/// of panicking:
+///
+/// - [`InvalidLength`](crate::ConfigurationError::InvalidLength) if the capacity
+///   is not a power of two.
+/// - [`Alignment`](crate::ConfigurationError::Alignment) if the alignment is not
+///   a multiple of the buffer's size in bytes.
+///
+/// # Safety
+///
+/// See [`set_source_circular_buffer`].
+pub unsafe fn try_set_source_circular_buffer<E: Element>(
+    chan: &mut Channel,
+    source: &[E],
+) -> Result<(), Error> {
+    check_circular_buffer(source)?;
+    // Safety: caller upholds the same requirements.
+    unsafe { set_source_circular_buffer(chan, source) };
+    Ok(())
+}
+
 /// Set a circular buffer as the destination for a DMA transfer
 ///
 /// When the transfer completes, the DMA channel remain at the
@@ -591,11 +2278,28 @@ pub unsafe fn set_destination_circular_buffer<E: Element>(
     chan: &mut Channel,
     destination: &mut [E],
 ) {
-    circular_buffer_asserts(destination);
-    let modulo = circular_buffer_modulo(destination);
+    let modulo = circular_buffer_modulo::<E>(destination.as_ptr() as usize, destination.len());
 
     chan.set_destination_address(destination.as_ptr());
     chan.set_destination_offset(core::mem::size_of::<E>() as i16);
-    chan.set_destination_attributes::<E>(modulo as u8);
+    chan.set_destination_attributes::<E>(modulo);
     chan.set_destination_last_address_adjustment(0);
 }
+
+/// Set a circular buffer as the destination for a DMA transfer, if the buffer is valid
+///
+/// This is like [`set_destination_circular_buffer`], but it returns an error
+/// instead of panicking. See [`try_set_source_circular_buffer`] for the errors.
+///
+/// # Safety
+///
+/// See [`set_destination_circular_buffer`].
+pub unsafe fn try_set_destination_circular_buffer<E: Element>(
+    chan: &mut Channel,
+    destination: &mut [E],
+) -> Result<(), Error> {
+    check_circular_buffer(destination)?;
+    // Safety: caller upholds the same requirements.
+    unsafe { set_destination_circular_buffer(chan, destination) };
+    Ok(())
+}
@@ -0,0 +1,505 @@
+//! Preconfigured DMA drivers for i.MX RT chips
+//!
+//! Each chip family is available behind a feature of the same name. The module
+//! for a chip family provides the DMA controller and DMAMUX addresses, the number
+//! of DMA channels, and a `DMA` driver that uses those values. Using the `DMA`
+//! driver doesn't require any `unsafe` code.
+//!
+//! ```
+//! # #[cfg(feature = "imxrt1060")] {
+//! use imxrt_dma::chips::imxrt1060;
+//!
+//! let channel = imxrt1060::DMA.try_channel(7).unwrap();
+//! # }
+//! ```
+//!
+//! | Feature       | Module        | Chips                          |
+//! | ------------- | ------------- | ------------------------------ |
+//! | `"imxrt1010"` | `imxrt1010`   | i.MX RT 1011                   |
+//! | `"imxrt1020"` | `imxrt1020`   | i.MX RT 1021                   |
+//! | `"imxrt1060"` | `imxrt1060`   | i.MX RT 1051, 1052, 1061, 1062, 1064 |
+//! | `"imxrt1170"` | `imxrt1170`   | i.MX RT 1171, 1172, 1173, 1175, 1176 |
+//!
+//! To define your own driver for a chip, like a [`PolledDma`](crate::PolledDma),
+//! use the chip module's `dma` function instead of [`Dma::new`](crate::Dma::new).
+//! `dma` checks that the driver's `CHANNELS` parameter matches the chip's channel
+//! count. A mismatch fails to compile.
+//!
+//! ```
+//! # #[cfg(feature = "imxrt1010")] {
+//! use imxrt_dma::{chips::imxrt1010, PolledDma};
+//!
+//! // Safety: this is the only driver for the DMA controller.
+//! static MY_DMA: PolledDma<16> = unsafe { imxrt1010::dma() };
+//! // static MY_DMA: PolledDma<32> = unsafe { imxrt1010::dma() }; // Fails to compile!
+//! # }
+//! ```
+//!
+//! Some chips have more than one DMA controller. Each controller has its own
+//! `Dma` driver. Channels, wakers, and interrupt handling are independent for
+//! each driver. For example, channel 3 from `imxrt1170::DMA` and channel 3
+//! from `imxrt1170::DMA_LPSR` are different channels.
+//!
+//! Don't create a second `Dma` for the same controller with [`Dma::new`](crate::Dma::new).
+//! Both drivers would allocate the same channels, and they wouldn't share wakers.
+//!
+//! # Interrupts
+//!
+//! Each chip module maps a channel to its NVIC [`Interrupt`]. On chips with 32 channels,
+//! channels `n` and `n + 16` share an interrupt. Use `unmask_interrupt` to unmask a
+//! channel's interrupt, instead of naming the interrupt by hand. Call
+//! [`on_interrupt`](crate::Dma::on_interrupt) for both channels that share the interrupt.
+//!
+//! ```no_run
+//! # #[cfg(feature = "imxrt1060")] {
+//! use imxrt_dma::chips::imxrt1060;
+//!
+//! let channel = imxrt1060::DMA.try_channel(7).unwrap();
+//! assert_eq!(imxrt1060::INTERRUPTS[7], imxrt1060::INTERRUPTS[23]);
+//! // Safety: the DMA7_DMA23 handler calls on_interrupt
+//! // for channels 7 and 23.
+//! unsafe { imxrt1060::unmask_interrupt(7) };
+//! # }
+//! ```
+//!
+//! `bind` does the same for a channel, and returns a
+//! [`BoundChannel`](crate::bound::BoundChannel) that interrupts on completion.
+//!
+//! ```no_run
+//! # #[cfg(feature = "imxrt1060")] {
+//! use imxrt_dma::chips::imxrt1060;
+//!
+//! let channel = imxrt1060::DMA.try_channel(7).unwrap();
+//! // Safety: the DMA7_DMA23 handler calls on_interrupt
+//! // for channels 7 and 23.
+//! let channel = unsafe { imxrt1060::bind(channel) };
+//! # }
+//! ```
+//!
+//! # Embassy
+//!
+//! With the `"embassy"` feature, each chip module has an `InterruptHandler` type
+//! for embassy's `bind_interrupts!` macro. `InterruptHandler<N>` handles DMA
+//! channel interrupt `N`, and every channel that shares the interrupt.
+//! `ErrorInterruptHandler` handles the `ERROR_INTERRUPT` for every channel that's
+//! in an error state. The `imxrt1170` module also has `LpsrInterruptHandler<N>` and
+//! `LpsrErrorInterruptHandler`, which do the same for `DMA_LPSR`.
+//!
+//! Embassy HALs generate their own `interrupt::typelevel::Handler` trait, so this
+//! crate can't implement it. Instead, the HAL uses `imxrt_dma::impl_embassy_handlers!`
+//! to implement its `Handler` trait for each DMA interrupt. Then, users bind the
+//! DMA interrupts like any other interrupt.
+//!
+//! ```
+//! # #[cfg(all(feature = "embassy", feature = "imxrt1060"))] {
+//! # #[allow(non_camel_case_types)]
+//! # mod typelevel {
+//! #     pub trait Interrupt {}
+//! #     pub trait Handler<I: Interrupt> { unsafe fn on_interrupt(); }
+//! #     pub enum DMA0_DMA16 {}
+//! #     impl Interrupt for DMA0_DMA16 {}
+//! #     pub enum DMA7_DMA23 {}
+//! #     impl Interrupt for DMA7_DMA23 {}
+//! #     pub enum DMA_ERROR {}
+//! #     impl Interrupt for DMA_ERROR {}
+//! # }
+//! // In the HAL, next to the HAL's `interrupt_mod!`:
+//! imxrt_dma::impl_embassy_handlers!(
+//!     imxrt_dma::chips::imxrt1060, typelevel,
+//!     DMA0_DMA16 => 0,
+//!     DMA7_DMA23 => 7,
+//!     DMA_ERROR => ErrorInterruptHandler,
+//! );
+//!
+//! // In the application:
+//! // bind_interrupts!(struct Irqs {
+//! //     DMA7_DMA23 => imxrt_dma::chips::imxrt1060::InterruptHandler<7>;
+//! //     DMA_ERROR => imxrt_dma::chips::imxrt1060::ErrorInterruptHandler;
+//! // });
+//! # }
+//! ```
+
+use cortex_m::interrupt::InterruptNumber;
+
+/// A DMA interrupt number
+///
+/// Use this with the `cortex_m` NVIC API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Interrupt(u16);
+
+impl Interrupt {
+    /// Returns the interrupt number
+    pub const fn number(self) -> u16 {
+        self.0
+    }
+}
+
+// Safety: chip modules only create valid DMA interrupt numbers.
+unsafe impl InterruptNumber for Interrupt {
+    fn number(self) -> u16 {
+        self.0
+    }
+}
+
+/// Map each channel to one of `vectors` interrupts
+#[allow(dead_code)] // Unused without a chip feature.
+const fn interrupts<const CHANNELS: usize>(vectors: u16) -> [Interrupt; CHANNELS] {
+    let mut interrupts = [Interrupt(0); CHANNELS];
+    let mut channel = 0;
+    while channel < CHANNELS {
+        interrupts[channel] = Interrupt(channel as u16 % vectors);
+        channel += 1;
+    }
+    interrupts
+}
+
+/// Checks a driver's channel count against a chip's channel count
+#[allow(dead_code)] // Unused without a chip feature.
+struct ChannelCount<const DRIVER: usize, const CHIP: usize>;
+
+#[allow(dead_code)] // Unused without a chip feature.
+impl<const DRIVER: usize, const CHIP: usize> ChannelCount<DRIVER, CHIP> {
+    const MATCH: () = assert!(
+        DRIVER == CHIP,
+        "CHANNELS does not match the chip's DMA channel count"
+    );
+}
+
+/// Calls `dma.on_interrupt` for every channel that shares interrupt `vector`
+///
+/// # Safety
+///
+/// See [`Dma::on_interrupt`](crate::Dma::on_interrupt).
+#[cfg(feature = "embassy")]
+#[allow(dead_code)] // Unused without a chip feature.
+#[inline(always)]
+unsafe fn on_channel_interrupt<const CHANNELS: usize>(
+    dma: &'static crate::Dma<CHANNELS>,
+    vector: usize,
+    vectors: usize,
+) {
+    let mut channel = vector;
+    while channel < CHANNELS {
+        // Safety: caller upholds the requirements.
+        unsafe { dma.on_interrupt(channel) };
+        channel += vectors;
+    }
+}
+
+/// Calls `dma.on_interrupt` for every channel that's in an error state
+///
+/// # Safety
+///
+/// See [`Dma::on_interrupt`](crate::Dma::on_interrupt).
+#[cfg(feature = "embassy")]
+#[allow(dead_code)] // Unused without a chip feature.
+#[inline(always)]
+unsafe fn on_error_interrupt<const CHANNELS: usize>(dma: &'static crate::Dma<CHANNELS>) {
+    for channel in 0..CHANNELS {
+        // Safety: the handle only reads the channel's status. on_interrupt
+        // requirements are upheld by the caller.
+        unsafe {
+            if dma.channel(channel).is_error() {
+                dma.on_interrupt(channel);
+            }
+        }
+    }
+}
+
+macro_rules! chip {
+    (
+        $(#[$attr:meta])*
+        $feature:literal, $chip:ident,
+        dma: $dma:literal,
+        dmamux: $dmamux:literal,
+        channels: $channels:literal,
+        vectors: $vectors:literal,
+        error: $error:literal,
+        $($extra:item)*
+    ) => {
+        $(#[$attr])*
+        #[cfg(feature = $feature)]
+        pub mod $chip {
+            use crate::Dma;
+
+            /// Address of the DMA controller registers
+            pub const DMA_ADDRESS: *const () = $dma as *const ();
+            /// Address of the DMA multiplexer registers
+            pub const DMAMUX_ADDRESS: *const () = $dmamux as *const ();
+            /// Number of DMA channels
+            pub const CHANNELS: usize = $channels;
+            /// Number of DMA channel interrupts
+            #[allow(dead_code)] // Only used by some chips' extra items.
+            const VECTORS: usize = $vectors;
+
+            /// The DMA driver
+            // Safety: addresses and channel count are valid for this chip.
+            pub static DMA: Dma<CHANNELS> = unsafe { Dma::new(DMA_ADDRESS, DMAMUX_ADDRESS) };
+
+            /// Create a DMA driver for this chip's DMA controller
+            ///
+            /// Use this to define your own driver, instead of [`DMA`]. If `N` isn't
+            /// [`CHANNELS`], this fails to compile.
+            ///
+            /// # Safety
+            ///
+            /// Don't use [`DMA`], or another driver for the same controller. Both
+            /// drivers would allocate the same channels, and they wouldn't share
+            /// wakers.
+            pub const unsafe fn dma<const N: usize, const WAKERS: usize, const WATCHERS: usize>(
+            ) -> Dma<N, WAKERS, WATCHERS> {
+                let () = super::ChannelCount::<N, CHANNELS>::MATCH;
+                // Safety: addresses are valid for this chip, and the channel
+                // count matches. Caller upholds the uniqueness requirement.
+                unsafe { Dma::new(DMA_ADDRESS, DMAMUX_ADDRESS) }
+            }
+
+            /// Each channel's interrupt, indexed by channel number
+            pub const INTERRUPTS: [super::Interrupt; CHANNELS] = super::interrupts($vectors);
+            /// The DMA error interrupt, shared by all channels
+            pub const ERROR_INTERRUPT: super::Interrupt = super::Interrupt($error);
+
+            /// Unmask the NVIC interrupt for `channel`
+            ///
+            /// # Safety
+            ///
+            /// Unmasking an interrupt may break mask-based critical sections. Make sure
+            /// that the interrupt handler calls `DMA.on_interrupt` for every channel
+            /// that shares the interrupt.
+            ///
+            /// On a hosted target, there's no NVIC, and this does nothing.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `channel` is greater than or equal to the number of channels.
+            pub unsafe fn unmask_interrupt(channel: usize) {
+                let interrupt = INTERRUPTS[channel];
+                // Safety: caller upholds the requirements.
+                #[cfg(target_os = "none")]
+                unsafe {
+                    cortex_m::peripheral::NVIC::unmask(interrupt)
+                };
+                #[cfg(not(target_os = "none"))]
+                let _ = interrupt;
+            }
+
+            /// Bind a channel from [`DMA`], and unmask its NVIC interrupt
+            ///
+            /// See [`BoundChannel::new`](crate::bound::BoundChannel::new) for more
+            /// information.
+            ///
+            /// # Safety
+            ///
+            /// See [`unmask_interrupt`].
+            ///
+            /// # Panics
+            ///
+            /// Panics if the channel doesn't have a waker.
+            pub unsafe fn bind(channel: crate::channel::Channel) -> crate::bound::BoundChannel {
+                let index = channel.channel();
+                let channel = crate::bound::BoundChannel::new(channel);
+                // Safety: caller upholds the requirements.
+                unsafe { unmask_interrupt(index) };
+                channel
+            }
+
+            /// Calls `DMA.on_interrupt` for every channel that shares interrupt `VECTOR`
+            ///
+            /// See the [embassy section](super#embassy) for more information. Requires
+            /// the `"embassy"` feature.
+            ///
+            /// `VECTOR` is the index of the DMA channel interrupt; it's the smallest
+            /// channel that shares the interrupt. A `VECTOR` that isn't a DMA
+            /// channel interrupt fails to compile.
+            #[cfg(feature = "embassy")]
+            pub struct InterruptHandler<const VECTOR: usize> {
+                _private: (),
+            }
+
+            #[cfg(feature = "embassy")]
+            impl<const VECTOR: usize> InterruptHandler<VECTOR> {
+                const VALID: () = assert!(VECTOR < VECTORS, "Not a DMA channel interrupt");
+
+                /// Handle the interrupt
+                ///
+                /// # Safety
+                ///
+                /// Only call this from the DMA interrupt handler for `VECTOR`. See
+                /// [`Dma::on_interrupt`](crate::Dma::on_interrupt) for more requirements.
+                #[inline(always)]
+                pub unsafe fn on_interrupt() {
+                    let () = Self::VALID;
+                    // Safety: caller upholds the requirements.
+                    unsafe { super::on_channel_interrupt(&DMA, VECTOR, VECTORS) }
+                }
+            }
+
+            /// Calls `DMA.on_interrupt` for every channel that's in an error state
+            ///
+            /// Bind this to the [`ERROR_INTERRUPT`]. See the [embassy section](super#embassy)
+            /// for more information. Requires the `"embassy"` feature.
+            #[cfg(feature = "embassy")]
+            pub struct ErrorInterruptHandler {
+                _private: (),
+            }
+
+            #[cfg(feature = "embassy")]
+            impl ErrorInterruptHandler {
+                /// Handle the interrupt
+                ///
+                /// # Safety
+                ///
+                /// Only call this from the DMA error interrupt handler. See
+                /// [`Dma::on_interrupt`](crate::Dma::on_interrupt) for more requirements.
+                #[inline(always)]
+                pub unsafe fn on_interrupt() {
+                    // Safety: caller upholds the requirements.
+                    unsafe { super::on_error_interrupt(&DMA) }
+                }
+            }
+
+            $($extra)*
+        }
+    };
+}
+
+/// Implement an embassy HAL's `Handler` trait for a chip's DMA interrupts
+///
+/// The first argument is a chip module, like `imxrt_dma::chips::imxrt1060`. The
+/// second is the path to the HAL's `interrupt::typelevel` module. Then, list each
+/// typelevel interrupt and the DMA interrupt index that it handles. For the error
+/// interrupt, or for another controller's interrupts, name the chip module's handler
+/// type instead of an index, like `ErrorInterruptHandler` or `LpsrInterruptHandler<7>`.
+///
+/// Embassy HALs generate their own `Handler` trait. Because of Rust's coherence rules,
+/// only the crate that owns that trait can use this macro. See the
+/// [embassy section](crate::chips#embassy) for an example.
+#[cfg(feature = "embassy")]
+#[macro_export]
+macro_rules! impl_embassy_handlers {
+    (@impl [$($chip:tt)*] [$($typelevel:tt)*] $(,)?) => {};
+    (@impl [$($chip:tt)*] [$($typelevel:tt)*] $irq:ident => $vector:literal $(, $($rest:tt)*)?) => {
+        $crate::impl_embassy_handlers!(@impl [$($chip)*] [$($typelevel)*] $irq => InterruptHandler<$vector> $(, $($rest)*)?);
+    };
+    (@impl [$($chip:tt)*] [$($typelevel:tt)*] $irq:ident => $handler:ident $(<$vector:literal>)? $(, $($rest:tt)*)?) => {
+        impl $($typelevel)*::Handler<$($typelevel)*::$irq> for $($chip)*::$handler $(<$vector>)? {
+            #[inline(always)]
+            unsafe fn on_interrupt() {
+                // Safety: the HAL only calls this from the `$irq` interrupt handler.
+                unsafe { <$($chip)*::$handler $(<$vector>)?>::on_interrupt() }
+            }
+        }
+        $crate::impl_embassy_handlers!(@impl [$($chip)*] [$($typelevel)*] $($($rest)*)?);
+    };
+    ($($chip:ident)::+, $($typelevel:ident)::+, $($handlers:tt)+) => {
+        $crate::impl_embassy_handlers!(@impl [$($chip)::+] [$($typelevel)::+] $($handlers)+);
+    };
+}
+
+chip! {
+    /// i.MX RT 1010 chips
+    "imxrt1010", imxrt1010,
+    dma: 0x400E_8000,
+    dmamux: 0x400E_C000,
+    channels: 16,
+    vectors: 16,
+    error: 16,
+}
+
+chip! {
+    /// i.MX RT 1020 chips
+    "imxrt1020", imxrt1020,
+    dma: 0x400E_8000,
+    dmamux: 0x400E_C000,
+    channels: 32,
+    vectors: 16,
+    error: 16,
+}
+
+chip! {
+    /// i.MX RT 1050, 1060, and 1064 chips
+    "imxrt1060", imxrt1060,
+    dma: 0x400E_8000,
+    dmamux: 0x400E_C000,
+    channels: 32,
+    vectors: 16,
+    error: 16,
+}
+
+chip! {
+    /// i.MX RT 1170 chips
+    "imxrt1170", imxrt1170,
+    dma: 0x4007_0000,
+    dmamux: 0x4007_4000,
+    channels: 32,
+    vectors: 16,
+    error: 16,
+
+    /// Address of the low-power DMA controller (eDMA_LPSR) registers
+    pub const DMA_LPSR_ADDRESS: *const () = 0x40C1_4000 as *const ();
+    /// Address of the low-power DMA multiplexer (DMAMUX1) registers
+    pub const DMAMUX_LPSR_ADDRESS: *const () = 0x40C1_8000 as *const ();
+
+    /// The low-power DMA driver
+    ///
+    /// This controls the eDMA_LPSR controller. It's independent of [`DMA`].
+    /// Call `DMA_LPSR.on_interrupt` from the eDMA_LPSR interrupt handlers.
+    /// Those interrupts are routed to the Cortex-M4, so they're not described
+    /// by [`INTERRUPTS`].
+    // Safety: addresses and channel count are valid for this chip.
+    pub static DMA_LPSR: Dma<CHANNELS> =
+        unsafe { Dma::new(DMA_LPSR_ADDRESS, DMAMUX_LPSR_ADDRESS) };
+
+    /// Calls `DMA_LPSR.on_interrupt` for every channel that shares eDMA_LPSR
+    /// interrupt `VECTOR`
+    ///
+    /// Like [`InterruptHandler`], but for [`DMA_LPSR`]. Channels `n` and `n + 16`
+    /// share an eDMA_LPSR interrupt. Requires the `"embassy"` feature.
+    #[cfg(feature = "embassy")]
+    pub struct LpsrInterruptHandler<const VECTOR: usize> {
+        _private: (),
+    }
+
+    #[cfg(feature = "embassy")]
+    impl<const VECTOR: usize> LpsrInterruptHandler<VECTOR> {
+        const VALID: () = assert!(VECTOR < VECTORS, "Not a DMA channel interrupt");
+
+        /// Handle the interrupt
+        ///
+        /// # Safety
+        ///
+        /// Only call this from the eDMA_LPSR interrupt handler for `VECTOR`. See
+        /// [`Dma::on_interrupt`](crate::Dma::on_interrupt) for more requirements.
+        #[inline(always)]
+        pub unsafe fn on_interrupt() {
+            let () = Self::VALID;
+            // Safety: caller upholds the requirements.
+            unsafe { super::on_channel_interrupt(&DMA_LPSR, VECTOR, VECTORS) }
+        }
+    }
+
+    /// Calls `DMA_LPSR.on_interrupt` for every channel that's in an error state
+    ///
+    /// Like [`ErrorInterruptHandler`], but for [`DMA_LPSR`]. Bind this to the
+    /// eDMA_LPSR error interrupt. Requires the `"embassy"` feature.
+    #[cfg(feature = "embassy")]
+    pub struct LpsrErrorInterruptHandler {
+        _private: (),
+    }
+
+    #[cfg(feature = "embassy")]
+    impl LpsrErrorInterruptHandler {
+        /// Handle the interrupt
+        ///
+        /// # Safety
+        ///
+        /// Only call this from the eDMA_LPSR error interrupt handler. See
+        /// [`Dma::on_interrupt`](crate::Dma::on_interrupt) for more requirements.
+        #[inline(always)]
+        pub unsafe fn on_interrupt() {
+            // Safety: caller upholds the requirements.
+            unsafe { super::on_error_interrupt(&DMA_LPSR) }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! DMA clock gate support
+
+use crate::critical;
+use core::{
+    cell::Cell,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+use cortex_m::interrupt::{CriticalSection, Mutex};
+
+#[cfg(feature = "clock-gating")]
+use core::sync::atomic::{AtomicBool, AtomicU32};
+
+/// Tracks the user's clock gate hook, and whether it enabled the clock
+pub(crate) struct Clock {
+    /// A `fn(bool)`, or null if there's no hook.
+    ///
+    /// Atomic, so that drivers without a hook don't need a critical section.
+    gate: AtomicPtr<()>,
+    enabled: Mutex<Cell<bool>>,
+    /// Set if the driver gates the clock when it's idle.
+    #[cfg(feature = "clock-gating")]
+    automatic: AtomicBool,
+    /// Set while the user holds the clock enabled with `enable_clock`.
+    #[cfg(feature = "clock-gating")]
+    held: AtomicBool,
+    /// Channels that are using the controller, 32 channels per word.
+    ///
+    /// A channel's bit is only set while the clock is enabled.
+    #[cfg(feature = "clock-gating")]
+    users: [AtomicU32; 2],
+}
+
+impl Clock {
+    pub(crate) const fn new() -> Self {
+        Self {
+            gate: AtomicPtr::new(ptr::null_mut()),
+            enabled: Mutex::new(Cell::new(false)),
+            #[cfg(feature = "clock-gating")]
+            automatic: AtomicBool::new(false),
+            #[cfg(feature = "clock-gating")]
+            held: AtomicBool::new(false),
+            #[cfg(feature = "clock-gating")]
+            users: [AtomicU32::new(0), AtomicU32::new(0)],
+        }
+    }
+
+    pub(crate) fn set_gate(&self, gate: fn(bool)) {
+        self.gate.store(gate as *mut (), Ordering::Release);
+    }
+
+    fn gate(&self) -> Option<fn(bool)> {
+        let gate = self.gate.load(Ordering::Acquire);
+        // Safety: the only non-null values come from set_gate.
+        (!gate.is_null()).then(|| unsafe { core::mem::transmute::<*mut (), fn(bool)>(gate) })
+    }
+
+    /// Enable the clock, if it's not already enabled
+    ///
+    /// Does nothing if there's no clock gate hook.
+    pub(crate) fn enable(&self) {
+        if let Some(gate) = self.gate() {
+            critical::free(|cs| self.ungate(gate, cs));
+        }
+    }
+
+    /// Enable the clock for a new channel handle
+    ///
+    /// With automatic gating, a channel claims the clock when it's programmed,
+    /// so creating a handle, like one that only queries the channel's status,
+    /// leaves the clock alone.
+    pub(crate) fn enable_for_channel(&self) {
+        #[cfg(feature = "clock-gating")]
+        if self.is_automatic() {
+            return;
+        }
+        self.enable();
+    }
+
+    fn ungate(&self, gate: fn(bool), cs: &CriticalSection) {
+        let enabled = self.enabled.borrow(cs);
+        if !enabled.get() {
+            gate(true);
+            enabled.set(true);
+        }
+    }
+}
+
+#[cfg(feature = "clock-gating")]
+impl Clock {
+    pub(crate) fn set_automatic(&self, automatic: bool) {
+        self.automatic.store(automatic, Ordering::Relaxed);
+        if !automatic {
+            // Channels no longer claim the clock before they use it.
+            self.enable();
+        }
+    }
+
+    pub(crate) fn is_automatic(&self) -> bool {
+        self.automatic.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if automatic gating disabled the clock
+    ///
+    /// While the clock is gated, every channel is disabled.
+    pub(crate) fn is_gated(&self) -> bool {
+        self.is_automatic()
+            && self.gate().is_some()
+            && critical::free(|cs| !self.enabled.borrow(cs).get())
+    }
+
+    /// Enable the clock, and keep it enabled until [`release_hold`](Self::release_hold)
+    pub(crate) fn hold(&self) {
+        self.held.store(true, Ordering::Relaxed);
+        self.enable();
+    }
+
+    /// Stop holding the clock, and gate it if no channel is using the controller
+    pub(crate) fn release_hold(&self) {
+        self.held.store(false, Ordering::Relaxed);
+        self.gate_if_idle();
+    }
+
+    /// Note that `channel` is using the controller, and enable the clock
+    #[inline(always)]
+    pub(crate) fn claim(&self, channel: usize) {
+        if !self.is_automatic() {
+            return;
+        }
+        let users = &self.users[channel / 32];
+        let mask = 1 << (channel % 32);
+        if users.load(Ordering::Relaxed) & mask != 0 {
+            return;
+        }
+        if let Some(gate) = self.gate() {
+            critical::free(|cs| {
+                self.ungate(gate, cs);
+                users.fetch_or(mask, Ordering::Relaxed);
+            });
+        }
+    }
+
+    /// Note that `channel` stopped using the controller
+    #[inline(always)]
+    pub(crate) fn release(&self, channel: usize) {
+        self.users[channel / 32].fetch_and(!(1 << (channel % 32)), Ordering::Relaxed);
+    }
+
+    /// Disable the clock if no channel is using the controller, and if the
+    /// user isn't holding it
+    pub(crate) fn gate_if_idle(&self) {
+        if !self.is_automatic() || self.held.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(gate) = self.gate() {
+            critical::free(|cs| {
+                let enabled = self.enabled.borrow(cs);
+                let idle = self
+                    .users
+                    .iter()
+                    .all(|users| users.load(Ordering::Relaxed) == 0);
+                if idle && enabled.get() {
+                    gate(false);
+                    enabled.set(false);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! A log of the driver's register writes
+//!
+//! When you [`start`] recording, the driver logs every register write that
+//! it performs: TCD programming, multiplexer configuration, and controller
+//! commands. The log holds the last [`LOG_LEN`] writes; older writes are
+//! overwritten. Use the log to answer "what exactly did the driver program?"
+//! after the fact.
+//!
+//! ```no_run
+//! use imxrt_dma::command_log::{self, Register};
+//! # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+//!
+//! command_log::start();
+//! // Use the driver...
+//! command_log::stop();
+//!
+//! for command in command_log::log().iter() {
+//!     if let Some(Register::Tcd { channel, name }) = DMA.decode(command) {
+//!         // Channel `channel` had `command.value()` written to `name`...
+//!     }
+//! }
+//! ```
+//!
+//! The log is a single static, shared by all drivers. A debugger can read it
+//! from a crash dump; its symbol is `imxrt_dma::command_log::LOG`. Each
+//! [`Command`] holds the register's address, so you can decode it with your
+//! chip's memory map.
+//!
+//! Logging happens in a critical section, and it only happens while you're
+//! recording. This module requires the `"command-log"` feature.
+
+use crate::{critical, ral, Dma};
+use core::{
+    cell::RefCell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use cortex_m::interrupt::Mutex;
+
+/// The number of register writes kept by the log
+pub const LOG_LEN: usize = 64;
+
+/// A register write performed by the driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Command {
+    address: u32,
+    value: u32,
+    size: u8,
+}
+
+impl Command {
+    const EMPTY: Self = Command {
+        address: 0,
+        value: 0,
+        size: 0,
+    };
+
+    /// Returns the register's address
+    pub const fn address(&self) -> u32 {
+        self.address
+    }
+    /// Returns the written value, zero-extended
+    pub const fn value(&self) -> u32 {
+        self.value
+    }
+    /// Returns the register's size, in bytes
+    pub const fn size(&self) -> usize {
+        self.size as usize
+    }
+}
+
+/// A copy of the most recent register writes
+///
+/// Use [`log()`] to get a copy.
+#[derive(Debug, Clone, Copy)]
+pub struct CommandLog {
+    commands: [Command; LOG_LEN],
+    /// The total number of writes ever logged. The next write
+    /// goes at `count % LOG_LEN`.
+    count: u32,
+}
+
+impl CommandLog {
+    const fn new() -> Self {
+        CommandLog {
+            commands: [Command::EMPTY; LOG_LEN],
+            count: 0,
+        }
+    }
+
+    fn push(&mut self, command: Command) {
+        self.commands[self.count as usize % LOG_LEN] = command;
+        self.count = self.count.wrapping_add(1);
+    }
+
+    /// Returns the number of writes in the log
+    pub fn len(&self) -> usize {
+        (self.count as usize).min(LOG_LEN)
+    }
+
+    /// Returns `true` if there are no writes in the log
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the number of writes logged since the log was cleared
+    ///
+    /// If this is larger than [`len`](Self::len), some writes were overwritten.
+    pub fn total(&self) -> u32 {
+        self.count
+    }
+
+    /// Iterate over the writes, from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = &Command> + '_ {
+        let start = self.count as usize - self.len();
+        (start..self.count as usize).map(|index| &self.commands[index % LOG_LEN])
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CommandLog {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "CommandLog {{ total: {}, commands: [", self.count);
+        for (index, command) in self.iter().enumerate() {
+            if index > 0 {
+                defmt::write!(f, ", ");
+            }
+            defmt::write!(f, "{}", command);
+        }
+        defmt::write!(f, "] }}");
+    }
+}
+
+/// The log of all drivers' register writes
+static LOG: Mutex<RefCell<CommandLog>> = Mutex::new(RefCell::new(CommandLog::new()));
+
+static RECORDING: AtomicBool = AtomicBool::new(false);
+
+/// Start logging register writes
+///
+/// This keeps any writes that are already in the log.
+pub fn start() {
+    RECORDING.store(true, Ordering::Relaxed);
+}
+
+/// Stop logging register writes
+pub fn stop() {
+    RECORDING.store(false, Ordering::Relaxed);
+}
+
+/// Returns `true` if the driver is logging register writes
+pub fn is_recording() -> bool {
+    RECORDING.load(Ordering::Relaxed)
+}
+
+/// Returns a copy of the log
+pub fn log() -> CommandLog {
+    critical::free(|cs| *LOG.borrow(cs).borrow())
+}
+
+/// Remove all writes from the log
+pub fn clear() {
+    critical::free(|cs| *LOG.borrow(cs).borrow_mut() = CommandLog::new());
+}
+
+/// Log the driver's write of `size` bytes at `address`, if recording
+#[inline(always)]
+pub(crate) fn record(address: usize, size: usize, value: u32) {
+    if is_recording() {
+        let command = Command {
+            address: address as u32,
+            value,
+            size: size as u8,
+        };
+        critical::free(|cs| LOG.borrow(cs).borrow_mut().push(command));
+    }
+}
+
+/// The register written by a [`Command`]
+///
+/// Use [`Dma::decode`] to find the register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Register {
+    /// A channel's TCD register
+    ///
+    /// Register names match [`TcdSnapshot`](crate::TcdSnapshot)'s fields.
+    Tcd {
+        /// The DMA channel
+        channel: usize,
+        /// The register name
+        name: &'static str,
+    },
+    /// A channel's multiplexer configuration
+    Multiplexer {
+        /// The DMA channel
+        channel: usize,
+    },
+    /// Any other controller register
+    Controller {
+        /// The register's offset from the start of the controller's registers
+        offset: usize,
+    },
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    Dma<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Find the register written by `command`
+    ///
+    /// Returns `None` if `command` didn't write one of this driver's registers.
+    /// A custom multiplexer's registers aren't known to the driver, so their
+    /// writes aren't logged.
+    pub fn decode(&self, command: &Command) -> Option<Register> {
+        // The log holds 32-bit addresses, so compare the low bits of each base.
+        let offset = |base: usize| command.address().wrapping_sub(base as u32) as usize;
+        match self.controller {
+            crate::Controller::EDma {
+                controller,
+                multiplexer,
+            } => {
+                const TCDS: usize = core::mem::offset_of!(ral::dma::RegisterBlock, TCD);
+                const SIZE: usize = core::mem::size_of::<ral::tcd::RegisterBlock>();
+                if let crate::Multiplexer::Dmamux(multiplexer) = multiplexer {
+                    let offset = offset(multiplexer.0 as usize);
+                    if offset < CHANNELS * 4 {
+                        return Some(Register::Multiplexer {
+                            channel: offset / 4,
+                        });
+                    }
+                }
+                let offset = offset(controller.0 as usize);
+                if (TCDS..TCDS + CHANNELS * SIZE).contains(&offset) {
+                    Some(Register::Tcd {
+                        channel: (offset - TCDS) / SIZE,
+                        name: ral::tcd::register_name((offset - TCDS) % SIZE)?,
+                    })
+                } else if offset < TCDS {
+                    Some(Register::Controller { offset })
+                } else {
+                    None
+                }
+            }
+            #[cfg(feature = "edma34")]
+            crate::Controller::EDma3(management) | crate::Controller::EDma4(management) => {
+                use ral::edma34::{ChannelRegisterBlock, CHANNEL_OFFSET};
+                const MUX: usize = core::mem::offset_of!(ChannelRegisterBlock, CH_MUX);
+                const TCD: usize = core::mem::offset_of!(ChannelRegisterBlock, TCD);
+                let stride = if matches!(self.controller, crate::Controller::EDma3(_)) {
+                    ral::edma34::EDMA3_CHANNEL_STRIDE
+                } else {
+                    ral::edma34::EDMA4_CHANNEL_STRIDE
+                };
+                let offset = offset(management.0 as usize);
+                if offset < CHANNEL_OFFSET {
+                    return Some(Register::Controller { offset });
+                }
+                let channel = (offset - CHANNEL_OFFSET) / stride;
+                if channel >= CHANNELS {
+                    return None;
+                }
+                match (offset - CHANNEL_OFFSET) % stride {
+                    MUX => Some(Register::Multiplexer { channel }),
+                    page if page >= TCD => Some(Register::Tcd {
+                        channel,
+                        name: ral::tcd::register_name(page - TCD)?,
+                    }),
+                    page => Some(Register::Controller {
+                        offset: CHANNEL_OFFSET + channel * stride + page,
+                    }),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,1055 @@
+//! DMA controller configuration
+
+use crate::{
+    channel::{ChannelState, Configuration},
+    ral, BandwidthControl, ConfigurationError, Controller, Dma, Error,
+};
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    Dma<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Apply all controller-wide settings in `config`
+    ///
+    /// `configure` validates the whole configuration, then applies it with a
+    /// single write. Prefer this over the individual setters when you're changing
+    /// more than one setting; there's no point where the controller runs with
+    /// only some of the settings applied.
+    ///
+    /// `configure` doesn't change whether the controller is halted. See
+    /// [`halt`](Self::halt).
+    ///
+    /// eDMA3 and eDMA4 controllers don't have channel groups, and always use minor
+    /// loop mapping. `configure` ignores those settings for these controllers.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::{Arbitration, Dma, DmaConfig};
+    /// # const DMA_PTR: *const () = core::ptr::null() as _;
+    /// # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+    ///
+    /// static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+    ///
+    /// DMA.configure(
+    ///     DmaConfig::new()
+    ///         .channel_arbitration(Arbitration::RoundRobin)
+    ///         .halt_on_error(true)
+    ///         .halt_in_debug(true),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the configuration is invalid. See [`set_group_priorities`](Self::set_group_priorities)
+    /// for group priority requirements. Also panics if the configuration disables
+    /// minor loop mapping while a channel has a minor loop offset. See
+    /// [`try_configure`](Self::try_configure) for a method that doesn't panic.
+    pub fn configure(&'static self, config: DmaConfig) {
+        let (group0, group1) = config.group_priorities;
+        assert!(
+            group0 <= 1 && group1 <= 1,
+            "Group priorities must be 0 or 1"
+        );
+        assert!(group0 != group1, "Group priorities must be different");
+        if !config.minor_loop_mapping {
+            assert!(
+                (0..CHANNELS).all(|index| {
+                    // Safety: we're only reading the channel's configuration.
+                    !unsafe { self.channel(index) }.has_minor_loop_offset()
+                }),
+                "Can't disable minor loop mapping while a channel has a minor loop offset"
+            );
+        }
+        self.write_config(&config);
+    }
+
+    /// Apply all controller-wide settings in `config`, if they're valid
+    ///
+    /// This is like [`configure`](Self::configure), but it returns an error
+    /// instead of panicking:
+    ///
+    /// - [`InvalidValue`](crate::ConfigurationError::InvalidValue) if the group
+    ///   priorities are invalid, or if the configuration disables minor loop
+    ///   mapping while a channel has a minor loop offset.
+    ///
+    /// The controller isn't changed if there's an error.
+    pub fn try_configure(&'static self, config: DmaConfig) -> Result<(), Error> {
+        let (group0, group1) = config.group_priorities;
+        if !valid_group_priorities(group0, group1) {
+            return Err(Error::configuration(ConfigurationError::InvalidValue));
+        }
+        if !config.minor_loop_mapping
+            && (0..CHANNELS).any(|index| {
+                // Safety: we're only reading the channel's configuration.
+                unsafe { self.channel(index) }.has_minor_loop_offset()
+            })
+        {
+            return Err(Error::configuration(ConfigurationError::InvalidValue));
+        }
+        self.write_config(&config);
+        Ok(())
+    }
+
+    /// Write `config` to the control register
+    ///
+    /// Like every change to the control register, this is a critical section,
+    /// so it doesn't race another context's read-modify-write. It writes zero to
+    /// CX and ECX, so that reading a pending cancel doesn't cancel again.
+    fn write_config(&self, config: &DmaConfig) {
+        let (group0, group1) = config.group_priorities;
+        match self.controller {
+            Controller::EDma { controller, .. } => crate::critical::free(|_| {
+                ral::modify_reg!(crate::ral::dma, controller, CR,
+                    EDBG: config.halt_in_debug as u32,
+                    ERCA: config.channel_arbitration.is_round_robin() as u32,
+                    ERGA: config.group_arbitration.is_round_robin() as u32,
+                    HOE: config.halt_on_error as u32,
+                    EMLM: config.minor_loop_mapping as u32,
+                    GRP0PRI: group0,
+                    GRP1PRI: group1,
+                    CX: 0,
+                    ECX: 0
+                )
+            }),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                crate::critical::free(|_| {
+                    ral::modify_reg!(crate::ral::edma34, management, CSR,
+                        EDBG: config.halt_in_debug as u32,
+                        ERCA: config.channel_arbitration.is_round_robin() as u32,
+                        HAE: config.halt_on_error as u32,
+                        CX: 0,
+                        ECX: 0
+                    )
+                })
+            }
+        }
+    }
+
+    /// Enable or disable halt on error
+    ///
+    /// When enabled, any channel error halts the DMA controller. No channel
+    /// makes progress until the controller is reset. Use this if it's safer to
+    /// stop all DMA activity than to keep moving possibly-corrupt data.
+    ///
+    /// Halt on error is disabled after reset.
+    pub fn set_halt_on_error(&self, halt: bool) {
+        match self.controller {
+            Controller::EDma { controller, .. } => crate::critical::free(
+                |_| ral::modify_reg!(crate::ral::dma, controller, CR, HOE: halt as u32, CX: 0, ECX: 0),
+            ),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                crate::critical::free(|_| {
+                    ral::modify_reg!(crate::ral::edma34, management, CSR,
+                        HAE: halt as u32,
+                        CX: 0,
+                        ECX: 0
+                    )
+                })
+            }
+        }
+    }
+
+    /// Returns `true` if any channel error halts the DMA controller
+    pub fn is_halt_on_error(&self) -> bool {
+        match self.controller {
+            Controller::EDma { controller, .. } => {
+                ral::read_reg!(crate::ral::dma, controller, CR, HOE == 1)
+            }
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                ral::read_reg!(crate::ral::edma34, management, CSR, HAE == 1)
+            }
+        }
+    }
+
+    /// Enable or disable halting in debug mode
+    ///
+    /// When enabled, the DMA controller stalls new channel activations while the
+    /// core is halted by a debugger. A channel that's active completes its current
+    /// minor loop. Use this when you're single-stepping code that shares buffers
+    /// with in-flight DMA transfers.
+    ///
+    /// Halting in debug mode is disabled after reset.
+    pub fn set_halt_in_debug(&self, halt: bool) {
+        match self.controller {
+            Controller::EDma { controller, .. } => crate::critical::free(
+                |_| ral::modify_reg!(crate::ral::dma, controller, CR, EDBG: halt as u32, CX: 0, ECX: 0),
+            ),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                crate::critical::free(|_| {
+                    ral::modify_reg!(crate::ral::edma34, management, CSR,
+                        EDBG: halt as u32,
+                        CX: 0,
+                        ECX: 0
+                    )
+                })
+            }
+        }
+    }
+
+    /// Returns `true` if the DMA controller halts while the core is in debug mode
+    pub fn is_halt_in_debug(&self) -> bool {
+        match self.controller {
+            Controller::EDma { controller, .. } => {
+                ral::read_reg!(crate::ral::dma, controller, CR, EDBG == 1)
+            }
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                ral::read_reg!(crate::ral::edma34, management, CSR, EDBG == 1)
+            }
+        }
+    }
+
+    /// Set the priorities of the two channel groups
+    ///
+    /// Group 0 is channels 0 through 15, and group 1 is channels 16 through 31.
+    /// When both groups have an active request, the controller services the group
+    /// with the higher priority. Each priority is either 0 or 1, and the two
+    /// priorities must be different.
+    ///
+    /// After reset, group 1 has priority 1, and group 0 has priority 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a priority is greater than 1, or if the priorities are equal.
+    /// Equal group priorities are a configuration error that the controller reports
+    /// when a channel activates.
+    ///
+    /// eDMA3 and eDMA4 controllers don't have channel groups. This method panics
+    /// if it's called on those controllers. See [`try_set_group_priorities`](Self::try_set_group_priorities)
+    /// for a method that doesn't panic.
+    pub fn set_group_priorities(&self, group0: u32, group1: u32) {
+        assert!(
+            group0 <= 1 && group1 <= 1,
+            "Group priorities must be 0 or 1"
+        );
+        assert!(group0 != group1, "Group priorities must be different");
+        match self.controller {
+            Controller::EDma { controller, .. } => crate::critical::free(|_| {
+                ral::modify_reg!(crate::ral::dma, controller, CR,
+                    GRP0PRI: group0,
+                    GRP1PRI: group1,
+                    CX: 0,
+                    ECX: 0
+                )
+            }),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(_) | Controller::EDma4(_) => {
+                panic!("eDMA3 / eDMA4 controllers don't have channel groups.")
+            }
+        }
+    }
+
+    /// Set the priorities of the two channel groups, if they're valid
+    ///
+    /// This is like [`set_group_priorities`](Self::set_group_priorities), but it
+    /// returns an error instead of panicking:
+    ///
+    /// - [`InvalidValue`](crate::ConfigurationError::InvalidValue) if a priority
+    ///   is greater than 1, or if the priorities are equal.
+    /// - [`Unsupported`](crate::ConfigurationError::Unsupported) for eDMA3 and
+    ///   eDMA4 controllers.
+    pub fn try_set_group_priorities(&self, group0: u32, group1: u32) -> Result<(), Error> {
+        if !valid_group_priorities(group0, group1) {
+            return Err(Error::configuration(ConfigurationError::InvalidValue));
+        }
+        match self.controller {
+            Controller::EDma { controller, .. } => {
+                crate::critical::free(|_| {
+                    ral::modify_reg!(crate::ral::dma, controller, CR,
+                        GRP0PRI: group0,
+                        GRP1PRI: group1,
+                        CX: 0,
+                        ECX: 0
+                    )
+                });
+                Ok(())
+            }
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(_) | Controller::EDma4(_) => {
+                Err(Error::configuration(ConfigurationError::Unsupported))
+            }
+        }
+    }
+
+    /// Returns the priorities of channel group 0 and group 1, in that order
+    ///
+    /// # Panics
+    ///
+    /// eDMA3 and eDMA4 controllers don't have channel groups. This method panics
+    /// if it's called on those controllers. See [`try_group_priorities`](Self::try_group_priorities)
+    /// for a method that doesn't panic.
+    pub fn group_priorities(&self) -> (u32, u32) {
+        match self.try_group_priorities() {
+            Ok(priorities) => priorities,
+            Err(_) => panic!("eDMA3 / eDMA4 controllers don't have channel groups."),
+        }
+    }
+
+    /// Returns the priorities of channel group 0 and group 1, in that order
+    ///
+    /// Returns [`Unsupported`](crate::ConfigurationError::Unsupported) for
+    /// eDMA3 and eDMA4 controllers, which don't have channel groups.
+    pub fn try_group_priorities(&self) -> Result<(u32, u32), Error> {
+        match self.controller {
+            Controller::EDma { controller, .. } => Ok(ral::read_reg!(
+                crate::ral::dma,
+                controller,
+                CR,
+                GRP0PRI,
+                GRP1PRI
+            )),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(_) | Controller::EDma4(_) => {
+                Err(Error::configuration(ConfigurationError::Unsupported))
+            }
+        }
+    }
+
+    /// Halt all DMA activity
+    ///
+    /// The controller stalls the start of any new channel. Channels that are
+    /// executing are allowed to complete their current minor loop. Use this to
+    /// freeze DMA activity around operations like flash programming, then call
+    /// [`resume`](Self::resume).
+    pub fn halt(&self) {
+        self.set_halt(true);
+    }
+
+    /// Resume DMA activity after a [`halt`](Self::halt)
+    ///
+    /// This also resumes a controller that halted on an error. See
+    /// [`set_halt_on_error`](Self::set_halt_on_error).
+    pub fn resume(&self) {
+        self.set_halt(false);
+    }
+
+    /// Returns `true` if the controller is halted
+    pub fn is_halted(&self) -> bool {
+        match self.controller {
+            Controller::EDma { controller, .. } => {
+                ral::read_reg!(crate::ral::dma, controller, CR, HALT == 1)
+            }
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                ral::read_reg!(crate::ral::edma34, management, CSR, HALT == 1)
+            }
+        }
+    }
+
+    fn set_halt(&self, halt: bool) {
+        match self.controller {
+            Controller::EDma { controller, .. } => crate::critical::free(
+                |_| ral::modify_reg!(crate::ral::dma, controller, CR, HALT: halt as u32, CX: 0, ECX: 0),
+            ),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                crate::critical::free(|_| {
+                    ral::modify_reg!(crate::ral::edma34, management, CSR,
+                        HALT: halt as u32,
+                        CX: 0,
+                        ECX: 0
+                    )
+                })
+            }
+        }
+    }
+
+    /// Reset the DMA controller and all of its channels to a known state
+    ///
+    /// `reset` disables every channel's hardware requests, cancels the active
+    /// transfer, and clears every channel's interrupt, error, and completion flags.
+    /// It resets every channel's transfer control descriptor, and turns off every
+    /// channel's multiplexer configuration.
+    ///
+    /// Use this during initialization, when the controller might be in an unknown
+    /// state. For example, a soft reset or a bootloader may leave channels enabled.
+    /// `reset` doesn't change the controller's configuration, like its group
+    /// priorities or halt on error. It also doesn't release channels allocated by
+    /// [`try_channel`](Self::try_channel).
+    ///
+    /// # Safety
+    ///
+    /// `reset` modifies all channels. Make sure that no other code is using a
+    /// channel. A transfer that's cancelled by `reset` never completes.
+    pub unsafe fn reset(&'static self) {
+        for index in 0..CHANNELS {
+            // Safety: caller ensures that no one else is using any channel.
+            let channel = unsafe { self.channel(index) };
+            channel.disable();
+        }
+
+        self.cancel();
+
+        for index in 0..CHANNELS {
+            // Safety: caller ensures that no one else is using any channel.
+            let mut channel = unsafe { self.channel(index) };
+            channel.reset();
+            channel.set_channel_configuration(Configuration::Off);
+            channel.set_asynchronous_request_in_stop(false);
+            channel.clear_complete();
+            channel.clear_error();
+            channel.clear_interrupt();
+        }
+    }
+
+    /// Cancel the active transfer, and wait for the cancel to complete
+    fn cancel(&self) {
+        match self.controller {
+            Controller::EDma { controller, .. } => {
+                crate::critical::free(
+                    |_| ral::modify_reg!(crate::ral::dma, controller, CR, CX: 1, ECX: 0),
+                );
+                while ral::read_reg!(crate::ral::dma, controller, CR, CX == 1) {}
+            }
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                crate::critical::free(
+                    |_| ral::modify_reg!(crate::ral::edma34, management, CSR, CX: 1, ECX: 0),
+                );
+                while ral::read_reg!(crate::ral::edma34, management, CSR, CX == 1) {}
+            }
+        }
+    }
+
+    /// Set the arbitration priority of every channel
+    ///
+    /// `priorities[n]` is the priority of channel `n`. A channel with a higher
+    /// value has a higher priority.
+    ///
+    /// An eDMA controller uses fixed priority arbitration within each group of 16
+    /// channels. Priorities are in the range `[0, 16)`, and each channel in a group
+    /// must have a unique priority. Changing one channel's priority at a time would
+    /// temporarily assign the same priority to two channels, so `set_priorities`
+    /// validates the whole table, then halts the controller while it programs
+    /// the table. The controller resumes afterwards, unless it was already halted.
+    /// After reset, each channel's priority is its channel number within its group.
+    ///
+    /// eDMA3 and eDMA4 priorities are in the range `[0, 8)`, and don't need to be
+    /// unique.
+    ///
+    /// Changing the priorities doesn't change a channel's preemption settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a priority is out of range, or if two channels in the same
+    /// eDMA group have the same priority. See [`try_set_priorities`](Self::try_set_priorities)
+    /// for a method that doesn't panic.
+    pub fn set_priorities(&self, priorities: &[u8; CHANNELS]) {
+        match self.controller {
+            Controller::EDma { .. } => {
+                for group in priorities.chunks(16) {
+                    let mut used: u16 = 0;
+                    for &priority in group {
+                        assert!(
+                            priority < 16,
+                            "eDMA channel priorities must be less than 16"
+                        );
+                        assert!(
+                            used & (1 << priority) == 0,
+                            "eDMA channel priorities must be unique within a group"
+                        );
+                        used |= 1 << priority;
+                    }
+                }
+            }
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(_) | Controller::EDma4(_) => {
+                assert!(
+                    priorities.iter().all(|&priority| priority < 8),
+                    "eDMA3 / eDMA4 channel priorities must be less than 8"
+                );
+            }
+        }
+        self.write_priorities(priorities);
+    }
+
+    /// Set the arbitration priority of every channel, if the priorities are valid
+    ///
+    /// This is like [`set_priorities`](Self::set_priorities), but it returns
+    /// [`InvalidValue`](crate::ConfigurationError::InvalidValue) instead of
+    /// panicking. The priorities aren't changed if there's an error.
+    ///
+    /// ```
+    /// # #[cfg(feature = "sim")] {
+    /// use imxrt_dma::{sim::Simulator, ConfigurationError};
+    ///
+    /// let sim = Simulator::<32>::new();
+    /// let dma = sim.dma();
+    ///
+    /// // Reverse the priorities within each group of 16 channels. Channels 0
+    /// // and 16 are in different groups, so they can share a priority.
+    /// let priorities: [u8; 32] = core::array::from_fn(|index| 15 - (index % 16) as u8);
+    /// dma.try_set_priorities(&priorities).unwrap();
+    /// assert_eq!(dma.priorities(), priorities);
+    ///
+    /// // Channels 0 and 1 are in the same group, so they can't.
+    /// let mut duplicate = priorities;
+    /// duplicate[1] = duplicate[0];
+    /// let error = dma.try_set_priorities(&duplicate).unwrap_err();
+    /// assert_eq!(error.configuration_error(), Some(ConfigurationError::InvalidValue));
+    ///
+    /// // Priorities are less than 16.
+    /// let mut large = priorities;
+    /// large[0] = 16;
+    /// assert!(dma.try_set_priorities(&large).is_err());
+    ///
+    /// // Neither error changed the priorities.
+    /// assert_eq!(dma.priorities(), priorities);
+    /// # }
+    /// ```
+    pub fn try_set_priorities(&self, priorities: &[u8; CHANNELS]) -> Result<(), Error> {
+        let valid = match self.controller {
+            Controller::EDma { .. } => priorities.chunks(16).all(|group| {
+                let mut used: u16 = 0;
+                group.iter().all(|&priority| {
+                    let unique = priority < 16 && used & (1 << priority) == 0;
+                    used |= 1 << (priority % 16);
+                    unique
+                })
+            }),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(_) | Controller::EDma4(_) => {
+                priorities.iter().all(|&priority| priority < 8)
+            }
+        };
+        if !valid {
+            return Err(Error::configuration(ConfigurationError::InvalidValue));
+        }
+        self.write_priorities(priorities);
+        Ok(())
+    }
+
+    fn write_priorities(&self, priorities: &[u8; CHANNELS]) {
+        match self.controller {
+            Controller::EDma { controller, .. } => {
+                use crate::ral::dma::DCHPRI::CHPRI;
+                let halted = self.is_halted();
+                self.halt();
+                for (index, &priority) in priorities.iter().enumerate() {
+                    let dchpri = &controller.DCHPRI[index];
+                    // A channel also changes its preemption fields.
+                    crate::critical::free(|_| {
+                        dchpri.write((dchpri.read() & !CHPRI::mask) | (priority << CHPRI::offset))
+                    });
+                }
+                if !halted {
+                    self.resume();
+                }
+            }
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(_) | Controller::EDma4(_) => {
+                for (index, &priority) in priorities.iter().enumerate() {
+                    if let Some(channel) = self.edma34_channel(index) {
+                        crate::critical::free(
+                            |_| ral::modify_reg!(crate::ral::edma34, channel, CH_PRI, APL: priority as u32),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the arbitration priority of every channel
+    ///
+    /// See [`set_priorities`](Self::set_priorities) for more information.
+    pub fn priorities(&self) -> [u8; CHANNELS] {
+        let mut priorities = [0; CHANNELS];
+        for (index, priority) in priorities.iter_mut().enumerate() {
+            *priority = match self.controller {
+                Controller::EDma { controller, .. } => {
+                    use crate::ral::dma::DCHPRI::CHPRI;
+                    (controller.DCHPRI[index].read() & CHPRI::mask) >> CHPRI::offset
+                }
+                #[cfg(feature = "edma34")]
+                Controller::EDma3(_) | Controller::EDma4(_) => {
+                    let channel = self.edma34_channel(index).unwrap();
+                    ral::read_reg!(crate::ral::edma34, channel, CH_PRI, APL) as u8
+                }
+            };
+        }
+        priorities
+    }
+
+    /// Configure every channel's arbitration from a preset
+    ///
+    /// `profiles[n]` is the profile of channel `n`. A [`Profile`] sets a channel's
+    /// priority, its preemption, and its bandwidth control together. See the
+    /// profiles for their settings.
+    ///
+    /// On an eDMA controller, `set_profiles` computes a unique priority for each
+    /// channel in a group: channels with a higher priority profile get higher
+    /// priorities, and channels with the same profile keep their channel number
+    /// order. The priorities are programmed like [`set_priorities`](Self::set_priorities).
+    ///
+    /// The bandwidth control is part of each channel's transfer control descriptor,
+    /// so a [`reset`](crate::channel::Channel::reset) of the channel clears it. Set
+    /// the profiles after you configure your channels, and before you start
+    /// transfers. Use [`Profile::bandwidth_control`] to restore a channel's bandwidth
+    /// control after a reset.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::{Dma, Profile};
+    /// static DMA: Dma<32> = // Handle to DMA driver.
+    /// # unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// let mut profiles = [Profile::Bulk; 32];
+    /// profiles[7] = Profile::AudioRealtime; // SAI
+    /// profiles[31] = Profile::Background; // Logging
+    /// DMA.set_profiles(&profiles);
+    /// ```
+    pub fn set_profiles(&'static self, profiles: &[Profile; CHANNELS]) {
+        let priorities = match self.controller {
+            Controller::EDma { .. } => core::array::from_fn(|index| {
+                let group = index / 16 * 16..(index / 16 * 16 + 16).min(CHANNELS);
+                let rank = (profiles[index].rank(), index);
+                group
+                    .filter(|&other| (profiles[other].rank(), other) < rank)
+                    .count() as u8
+            }),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(_) | Controller::EDma4(_) => {
+                core::array::from_fn(|index| profiles[index].edma34_priority())
+            }
+        };
+        self.write_priorities(&priorities);
+
+        for (index, &profile) in profiles.iter().enumerate() {
+            match self.controller {
+                Controller::EDma { controller, .. } => {
+                    use crate::ral::dma::DCHPRI::{DPA, ECP};
+                    let dchpri = &controller.DCHPRI[index];
+                    crate::critical::free(|_| {
+                        let mut value = dchpri.read() & !(DPA::mask | ECP::mask);
+                        if !profile.can_preempt() {
+                            value |= DPA::mask;
+                        }
+                        if profile.is_preemptible() {
+                            value |= ECP::mask;
+                        }
+                        dchpri.write(value);
+                    });
+                }
+                #[cfg(feature = "edma34")]
+                Controller::EDma3(_) | Controller::EDma4(_) => {
+                    let channel = self.edma34_channel(index).unwrap();
+                    crate::critical::free(|_| {
+                        ral::modify_reg!(crate::ral::edma34, channel, CH_PRI,
+                            DPA: !profile.can_preempt() as u32,
+                            ECP: profile.is_preemptible() as u32)
+                    });
+                }
+            }
+            // Safety: only changing the channel's bandwidth control.
+            let mut channel = unsafe { self.channel(index) };
+            channel.set_bandwidth_control(profile.bandwidth_control());
+        }
+    }
+
+    /// Save the configuration of the controller and all of its channels
+    ///
+    /// The state includes the controller's configuration, and each channel's
+    /// transfer control descriptor, multiplexer configuration, priority, and
+    /// enable bit. Use this before entering a low-power mode that loses the
+    /// controller's state, then call [`restore_state`](Self::restore_state)
+    /// after wakeup.
+    ///
+    /// The state doesn't include a custom [`Multiplexer`](crate::channel::Multiplexer)'s
+    /// configuration. It also doesn't include status flags, like a channel's
+    /// interrupt, error, and completion flags.
+    ///
+    /// Save the state while channels are idle. A channel that's transferring data
+    /// during `save_state` might be saved in the middle of a minor loop.
+    pub fn save_state(&'static self) -> DmaState<CHANNELS> {
+        let control = match self.controller {
+            Controller::EDma { controller, .. } => controller.CR.read(),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => management.CSR.read(),
+        };
+        DmaState {
+            control,
+            channels: core::array::from_fn(|index| {
+                // Safety: we're only reading the channel's configuration.
+                let channel = unsafe { self.channel(index) };
+                channel.save_state()
+            }),
+        }
+    }
+
+    /// Restore the configuration of the controller and all of its channels
+    ///
+    /// `restore_state` halts the controller while it restores each channel. It
+    /// restores the controller's configuration, including its halt state, and
+    /// then enables the channels that were enabled when the state was saved.
+    ///
+    /// # Safety
+    ///
+    /// `restore_state` modifies all channels. Make sure that no other code is using
+    /// a channel. The state may describe transfers. Make sure that all memory used
+    /// by those transfers is still valid, and that it's still owned by the transfers.
+    pub unsafe fn restore_state(&'static self, state: &DmaState<CHANNELS>) {
+        self.halt();
+
+        for (index, state) in state.channels.iter().enumerate() {
+            // Safety: caller ensures that no one else is using any channel.
+            let mut channel = unsafe { self.channel(index) };
+            // Safety: caller ensures that memory is still valid.
+            unsafe { channel.restore_state(state) };
+        }
+
+        match self.controller {
+            Controller::EDma { controller, .. } => {
+                use crate::ral::dma::CR;
+                controller
+                    .CR
+                    .write(state.control & !(CR::CX::mask | CR::ECX::mask));
+            }
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                use crate::ral::edma34::CSR;
+                management
+                    .CSR
+                    .write(state.control & !(CSR::CX::mask | CSR::ECX::mask));
+            }
+        }
+
+        for (index, state) in state.channels.iter().enumerate() {
+            if state.enabled {
+                // Safety: caller ensures that no one else is using any channel,
+                // and that the restored transfer is valid.
+                unsafe { self.channel(index).enable() };
+            }
+        }
+    }
+
+    /// Returns `true` if any channel is active, or if any channel is
+    /// receiving a hardware request
+    ///
+    /// See [`wait_idle`](Self::wait_idle) to wait until this is `false`.
+    pub fn is_any_active(&'static self) -> bool {
+        // Automatic gating only gates the clock when every channel is disabled.
+        #[cfg(feature = "clock-gating")]
+        if self.clock.is_gated() {
+            return false;
+        }
+        (0..CHANNELS).any(|index| {
+            // Safety: we're only reading the channel's status.
+            let channel = unsafe { self.channel(index) };
+            channel.is_active() || channel.is_hardware_signaling()
+        })
+    }
+
+    /// Describe the features of the DMA controller
+    ///
+    /// Use this in generic code that needs to adapt to the controller it's
+    /// running on. The capabilities are derived from the controller type and
+    /// the channel count; they don't access the hardware.
+    pub const fn capabilities(&self) -> Capabilities {
+        match self.controller {
+            Controller::EDma { .. } => Capabilities {
+                channels: CHANNELS,
+                groups: CHANNELS.div_ceil(16),
+                scatter_gather: true,
+                minor_loop_mapping: true,
+                periodic_triggers: if CHANNELS < 4 { CHANNELS } else { 4 },
+                integrated_multiplexer: false,
+            },
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(_) | Controller::EDma4(_) => Capabilities {
+                channels: CHANNELS,
+                groups: 1,
+                scatter_gather: true,
+                minor_loop_mapping: true,
+                periodic_triggers: 0,
+                integrated_multiplexer: true,
+            },
+        }
+    }
+
+    /// Capture a snapshot of the controller's registers
+    ///
+    /// Format the snapshot with `Debug`, or with `defmt` when the `"defmt"`
+    /// feature is enabled, and include it in bug reports and field diagnostics.
+    /// Capturing a snapshot only reads registers.
+    pub fn debug_dump(&'static self) -> DebugDump<CHANNELS> {
+        let (control, error_status) = match self.controller {
+            Controller::EDma { controller, .. } => (controller.CR.read(), controller.ES.read()),
+            #[cfg(feature = "edma34")]
+            Controller::EDma3(management) | Controller::EDma4(management) => {
+                (management.CSR.read(), management.ES.read())
+            }
+        };
+
+        let mut dump = DebugDump {
+            control,
+            error_status,
+            enabled: 0,
+            interrupt: 0,
+            error: 0,
+            hardware_request: 0,
+            priorities: self.priorities(),
+        };
+
+        for index in 0..CHANNELS {
+            // Safety: we're only reading the channel's status.
+            let channel = unsafe { self.channel(index) };
+            let bit = 1 << index;
+            for (flag, mask) in [
+                (channel.is_enabled(), &mut dump.enabled),
+                (channel.is_interrupt(), &mut dump.interrupt),
+                (channel.is_error(), &mut dump.error),
+                (channel.is_hardware_signaling(), &mut dump.hardware_request),
+            ] {
+                if flag {
+                    *mask |= bit;
+                }
+            }
+        }
+
+        dump
+    }
+}
+
+/// The saved configuration of a DMA controller and its channels
+///
+/// Use [`Dma::save_state`] to create a `DmaState`, and [`Dma::restore_state`]
+/// to restore it.
+#[derive(Clone, Copy)]
+pub struct DmaState<const CHANNELS: usize> {
+    control: u32,
+    channels: [ChannelState; CHANNELS],
+}
+
+/// The features of a DMA controller
+///
+/// See [`Dma::capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The number of channels
+    pub channels: usize,
+    /// The number of channel groups
+    ///
+    /// See [`Dma::set_group_priorities`].
+    pub groups: usize,
+    /// `true` if channels support scatter / gather transfers
+    pub scatter_gather: bool,
+    /// `true` if the controller supports minor loop mapping
+    ///
+    /// See [`Channel::set_minor_loop_offset`](crate::channel::Channel::set_minor_loop_offset).
+    pub minor_loop_mapping: bool,
+    /// The number of channels that support periodic triggering
+    ///
+    /// These are the lowest-numbered channels.
+    pub periodic_triggers: usize,
+    /// `true` if the request multiplexer is part of the controller
+    pub integrated_multiplexer: bool,
+}
+
+/// A snapshot of the DMA controller's registers
+///
+/// Use [`Dma::debug_dump`] to capture a snapshot. In each channel bitmask,
+/// bit `n` describes channel `n`.
+///
+/// For eDMA3 and eDMA4 controllers, `control` and `error_status` are the
+/// management page's CSR and ES registers. The channel bitmasks are collected
+/// from each channel's registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DebugDump<const CHANNELS: usize> {
+    /// Control register (CR)
+    pub control: u32,
+    /// Error status register (ES)
+    pub error_status: u32,
+    /// Channels with enabled hardware requests (ERQ)
+    pub enabled: u64,
+    /// Channels with a pending interrupt (INT)
+    pub interrupt: u64,
+    /// Channels in an error state (ERR)
+    pub error: u64,
+    /// Channels receiving a hardware request (HRS)
+    pub hardware_request: u64,
+    /// Each channel's arbitration priority (DCHPRI)
+    pub priorities: [u8; CHANNELS],
+}
+
+/// How the controller selects the next channel, or channel group, to service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Arbitration {
+    /// Service the request with the highest priority
+    #[default]
+    Fixed,
+    /// Service requests in order, ignoring priorities
+    RoundRobin,
+}
+
+impl Arbitration {
+    const fn is_round_robin(self) -> bool {
+        matches!(self, Arbitration::RoundRobin)
+    }
+}
+
+/// A preset for a channel's priority, preemption, and bandwidth control
+///
+/// Use [`set_profiles`](Dma::set_profiles) to apply profiles to channels.
+/// The presets assume fixed priority arbitration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Profile {
+    /// A latency-sensitive stream, like audio
+    ///
+    /// The highest priority. The channel can preempt other channels, and
+    /// other channels can't preempt it. There's no bandwidth control.
+    AudioRealtime,
+    /// A throughput-oriented transfer, like a buffer copy or a display update
+    ///
+    /// A middle priority. The channel doesn't preempt other channels, and
+    /// an [`AudioRealtime`](Self::AudioRealtime) channel can preempt it.
+    /// There's no bandwidth control.
+    Bulk,
+    /// A transfer that should stay out of the way, like logging
+    ///
+    /// The lowest priority. The channel doesn't preempt other channels, and
+    /// an [`AudioRealtime`](Self::AudioRealtime) channel can preempt it. The
+    /// channel stalls for 8 cycles after each read and write, leaving bus
+    /// bandwidth to other channels and to the CPU.
+    Background,
+}
+
+impl Profile {
+    /// Returns `true` if the channel can preempt a lower priority channel
+    pub const fn can_preempt(self) -> bool {
+        matches!(self, Profile::AudioRealtime)
+    }
+
+    /// Returns `true` if a higher priority channel can preempt the channel
+    pub const fn is_preemptible(self) -> bool {
+        !matches!(self, Profile::AudioRealtime)
+    }
+
+    /// Returns the channel's bandwidth control
+    ///
+    /// See [`set_bandwidth_control`](crate::channel::Channel::set_bandwidth_control).
+    pub const fn bandwidth_control(self) -> Option<BandwidthControl> {
+        match self {
+            Profile::Background => Some(BandwidthControl::Stall8Cycles),
+            Profile::AudioRealtime | Profile::Bulk => None,
+        }
+    }
+
+    /// Orders profiles from lowest to highest priority
+    const fn rank(self) -> u8 {
+        match self {
+            Profile::Background => 0,
+            Profile::Bulk => 1,
+            Profile::AudioRealtime => 2,
+        }
+    }
+
+    /// The eDMA3 / eDMA4 priority level, which doesn't need to be unique
+    #[cfg(feature = "edma34")]
+    const fn edma34_priority(self) -> u8 {
+        match self {
+            Profile::Background => 0,
+            Profile::Bulk => 3,
+            Profile::AudioRealtime => 7,
+        }
+    }
+}
+
+/// Controller-wide DMA settings
+///
+/// Use [`Dma::configure`] to apply the settings. The default configuration
+/// matches the controller's reset state, except that minor loop mapping is
+/// enabled. That way, configuring the controller doesn't break a channel with a
+/// minor loop offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DmaConfig {
+    channel_arbitration: Arbitration,
+    group_arbitration: Arbitration,
+    group_priorities: (u32, u32),
+    minor_loop_mapping: bool,
+    halt_on_error: bool,
+    halt_in_debug: bool,
+}
+
+impl DmaConfig {
+    /// Create the default configuration
+    ///
+    /// See [`DmaConfig`] for the defaults.
+    pub const fn new() -> Self {
+        Self {
+            channel_arbitration: Arbitration::Fixed,
+            group_arbitration: Arbitration::Fixed,
+            group_priorities: (0, 1),
+            minor_loop_mapping: true,
+            halt_on_error: false,
+            halt_in_debug: false,
+        }
+    }
+
+    /// Set the arbitration between channels in a group
+    pub const fn channel_arbitration(mut self, arbitration: Arbitration) -> Self {
+        self.channel_arbitration = arbitration;
+        self
+    }
+
+    /// Set the arbitration between the two channel groups
+    pub const fn group_arbitration(mut self, arbitration: Arbitration) -> Self {
+        self.group_arbitration = arbitration;
+        self
+    }
+
+    /// Set the priorities of channel group 0 and group 1
+    ///
+    /// See [`Dma::set_group_priorities`] for more information.
+    pub const fn group_priorities(mut self, group0: u32, group1: u32) -> Self {
+        self.group_priorities = (group0, group1);
+        self
+    }
+
+    /// Enable or disable minor loop mapping
+    ///
+    /// Minor loop mapping changes how every channel's minor loop byte count is
+    /// encoded. The driver enables minor loop mapping when a channel needs a minor
+    /// loop offset; see [`Channel::set_minor_loop_offset`](crate::channel::Channel::set_minor_loop_offset).
+    ///
+    /// Minor loop mapping is enabled by default. Disabling it fails if a channel
+    /// has a minor loop offset.
+    pub const fn minor_loop_mapping(mut self, enable: bool) -> Self {
+        self.minor_loop_mapping = enable;
+        self
+    }
+
+    /// Enable or disable halt on error
+    ///
+    /// See [`Dma::set_halt_on_error`] for more information.
+    pub const fn halt_on_error(mut self, halt: bool) -> Self {
+        self.halt_on_error = halt;
+        self
+    }
+
+    /// Enable or disable halting in debug mode
+    ///
+    /// See [`Dma::set_halt_in_debug`] for more information.
+    pub const fn halt_in_debug(mut self, halt: bool) -> Self {
+        self.halt_in_debug = halt;
+        self
+    }
+}
+
+impl Default for DmaConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns `true` if the channel group priorities are 0 and 1, in any order
+const fn valid_group_priorities(group0: u32, group1: u32) -> bool {
+    group0 <= 1 && group1 <= 1 && group0 != group1
+}
@@ -0,0 +1,61 @@
+//! Critical sections
+//!
+//! On a bare-metal target, a critical section masks interrupts. On a hosted
+//! target, like `x86_64-unknown-linux-gnu`, a critical section is a global,
+//! reentrant lock. That lets tests, and the `sim` module, run the driver on
+//! a host.
+
+use cortex_m::interrupt::CriticalSection;
+
+/// Execute `f` in a critical section
+#[cfg(target_os = "none")]
+#[inline(always)]
+pub(crate) fn free<F, R>(f: F) -> R
+where
+    F: FnOnce(&CriticalSection) -> R,
+{
+    cortex_m::interrupt::free(f)
+}
+
+/// Execute `f` in a critical section
+#[cfg(not(target_os = "none"))]
+pub(crate) fn free<F, R>(f: F) -> R
+where
+    F: FnOnce(&CriticalSection) -> R,
+{
+    use std::{
+        cell::RefCell,
+        sync::{Mutex, MutexGuard, PoisonError},
+    };
+
+    static LOCK: Mutex<()> = Mutex::new(());
+    std::thread_local! {
+        /// The nesting depth, and the lock guard held by the outermost section.
+        static HELD: RefCell<(usize, Option<MutexGuard<'static, ()>>)> =
+            const { RefCell::new((0, None)) };
+    }
+
+    /// Leaves the section, even if `f` panics.
+    struct Exit;
+    impl Drop for Exit {
+        fn drop(&mut self) {
+            HELD.with_borrow_mut(|(depth, guard)| {
+                *depth -= 1;
+                if *depth == 0 {
+                    *guard = None;
+                }
+            });
+        }
+    }
+
+    HELD.with_borrow_mut(|(depth, guard)| {
+        if *depth == 0 {
+            *guard = Some(LOCK.lock().unwrap_or_else(PoisonError::into_inner));
+        }
+        *depth += 1;
+    });
+    let _exit = Exit;
+    // Safety: this thread holds the global lock until the outermost
+    // section exits.
+    f(&unsafe { CriticalSection::new() })
+}
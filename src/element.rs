@@ -1,13 +1,47 @@
 //! Trait to generalize acceptable DMA transfer elements
 
-/// Describes a transferrable DMA element; basically, an unsigned
-/// integer of any size.
+/// Describes a transferrable DMA element; basically, an integer
+/// of any size.
+///
+/// Signed integers use the same transfer size as the unsigned integer
+/// of the same width. An `f32` is moved as a 32-bit transfer.
+///
+/// `u64`, `i64`, and `f64` use 64-bit transfers. A 64-bit transfer moves
+/// the element in a single bus access, which may improve memory copy
+/// throughput. The buffer and any peripheral register must be aligned to
+/// 8 bytes.
+///
+/// `[u8; 32]` uses 32-byte burst transfers. With the `"edma34"` feature,
+/// `[u8; 16]` uses 16-byte burst transfers. Only the eDMA3 and eDMA4 support
+/// 16-byte bursts; the eDMA in the i.MX RT 10xx and 1170 reserves that transfer
+/// size, so don't use `[u8; 16]` with those controllers. Each element is a full
+/// burst. Like the other transfer sizes, the address must be aligned to the
+/// burst size, but the array types don't guarantee that alignment. Make sure
+/// that your buffers are aligned; otherwise, the transfer fails with an
+/// address error.
+///
+/// With the `"embedded-graphics"` feature, `Rgb565` and `RawU16` pixels use
+/// 16-bit transfers.
+///
+/// To use your own newtype as an element, see [`impl_element_newtype!`](crate::impl_element_newtype).
 pub trait Element: Copy + private::Sealed {
     /// An identifier describing the data transfer size
     ///
     /// Part of the TCD API; see documentation on TCD\[SSIZE\]
     /// and TCD\[DSIZE\] for more information.
     const DATA_TRANSFER_ID: u8;
+
+    /// Fails to compile if the element's layout doesn't match its transfer size
+    ///
+    /// The size must match the transfer size. Elements up to 64 bits must
+    /// also be aligned to their size. Burst elements may have any alignment.
+    #[doc(hidden)]
+    const LAYOUT: () = assert!(
+        core::mem::size_of::<Self>() == 1 << Self::DATA_TRANSFER_ID
+            && (Self::DATA_TRANSFER_ID > 3
+                || core::mem::align_of::<Self>() == core::mem::size_of::<Self>()),
+        "Element layout does not match its DATA_TRANSFER_ID"
+    );
 }
 
 impl Element for u8 {
@@ -26,11 +60,151 @@ impl Element for u64 {
     const DATA_TRANSFER_ID: u8 = 3;
 }
 
-mod private {
+impl Element for i8 {
+    const DATA_TRANSFER_ID: u8 = 0;
+}
+
+impl Element for i16 {
+    const DATA_TRANSFER_ID: u8 = 1;
+}
+
+impl Element for i32 {
+    const DATA_TRANSFER_ID: u8 = 2;
+}
+
+impl Element for f32 {
+    const DATA_TRANSFER_ID: u8 = 2;
+}
+
+impl Element for i64 {
+    const DATA_TRANSFER_ID: u8 = 3;
+}
+
+impl Element for f64 {
+    const DATA_TRANSFER_ID: u8 = 3;
+}
+
+/// Only the eDMA3 and eDMA4 support 16-byte bursts.
+#[cfg(feature = "edma34")]
+impl Element for [u8; 16] {
+    const DATA_TRANSFER_ID: u8 = 4;
+}
+
+impl Element for [u8; 32] {
+    const DATA_TRANSFER_ID: u8 = 5;
+}
+
+/// Implement [`Element`] for newtypes that wrap an element
+///
+/// Each newtype must be a `Copy` tuple struct with a single field, and that
+/// field must be the element. The newtype uses the element's transfer size.
+/// The newtype must be named by an identifier that's in scope.
+///
+/// ```
+/// use imxrt_dma::{impl_element_newtype, Element};
+///
+/// #[derive(Clone, Copy)]
+/// #[repr(transparent)]
+/// pub struct Sample(pub i16);
+///
+/// #[derive(Clone, Copy)]
+/// #[repr(transparent)]
+/// pub struct Pixel(u16);
+///
+/// impl_element_newtype!(Sample => i16, Pixel => u16);
+/// assert_eq!(Sample::DATA_TRANSFER_ID, i16::DATA_TRANSFER_ID);
+/// ```
+///
+/// A type that holds anything other than the element fails to compile, since
+/// the DMA controller could write a value that's invalid for the other data.
+///
+/// ```compile_fail
+/// use imxrt_dma::impl_element_newtype;
+///
+/// #[derive(Clone, Copy)]
+/// pub struct Tagged(u16, bool);
+///
+/// impl_element_newtype!(Tagged => u16);
+/// ```
+#[macro_export]
+macro_rules! impl_element_newtype {
+    ($($newtype:ident => $elem:ty),+ $(,)?) => {
+        $(
+            const _: () = {
+                // Only a tuple struct with a single `$elem` field matches this pattern.
+                #[allow(dead_code)]
+                const fn into_element(newtype: $newtype) -> $elem {
+                    let $newtype(elem) = newtype;
+                    elem
+                }
+                assert!(
+                    ::core::mem::size_of::<$newtype>() == ::core::mem::size_of::<$elem>()
+                        && ::core::mem::align_of::<$newtype>() == ::core::mem::align_of::<$elem>(),
+                    "Element newtype layout does not match its element"
+                );
+            };
+            impl $crate::__private::Sealed for $newtype {}
+            impl $crate::Element for $newtype {
+                const DATA_TRANSFER_ID: u8 = <$elem as $crate::Element>::DATA_TRANSFER_ID;
+            }
+            const _: () = <$newtype as $crate::Element>::LAYOUT;
+        )+
+    };
+}
+
+const _: () = {
+    let () = u8::LAYOUT;
+    let () = u16::LAYOUT;
+    let () = u32::LAYOUT;
+    let () = u64::LAYOUT;
+    let () = i8::LAYOUT;
+    let () = i16::LAYOUT;
+    let () = i32::LAYOUT;
+    let () = i64::LAYOUT;
+    let () = f32::LAYOUT;
+    let () = f64::LAYOUT;
+    #[cfg(feature = "edma34")]
+    let () = <[u8; 16]>::LAYOUT;
+    let () = <[u8; 32]>::LAYOUT;
+};
+
+/// Pixel types, so display drivers can transfer framebuffers
+#[cfg(feature = "embedded-graphics")]
+mod embedded_graphics {
+    use super::{private::Sealed, Element};
+    use embedded_graphics_core::pixelcolor::{raw::RawU16, Rgb565};
+
+    // Safety: every 16-bit value is a valid color.
+    impl Sealed for Rgb565 {}
+    impl Element for Rgb565 {
+        const DATA_TRANSFER_ID: u8 = 1;
+    }
+
+    impl Sealed for RawU16 {}
+    impl Element for RawU16 {
+        const DATA_TRANSFER_ID: u8 = 1;
+    }
+
+    const _: () = {
+        let () = Rgb565::LAYOUT;
+        let () = RawU16::LAYOUT;
+    };
+}
+
+pub(crate) mod private {
     pub trait Sealed {}
 
     impl Sealed for u8 {}
     impl Sealed for u16 {}
     impl Sealed for u32 {}
     impl Sealed for u64 {}
+    impl Sealed for i8 {}
+    impl Sealed for i16 {}
+    impl Sealed for i32 {}
+    impl Sealed for f32 {}
+    impl Sealed for i64 {}
+    impl Sealed for f64 {}
+    #[cfg(feature = "edma34")]
+    impl Sealed for [u8; 16] {}
+    impl Sealed for [u8; 32] {}
 }
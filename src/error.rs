@@ -1,5 +1,6 @@
 //! DMA error status
 
+use crate::ral::tcd::Snapshot as TcdSnapshot;
 use core::fmt::{self, Debug, Display};
 
 /// A wrapper around a DMA error status value
@@ -8,16 +9,283 @@ use core::fmt::{self, Debug, Display};
 /// error status register at the point of an error. The
 /// wrapper implements both `Debug` and `Display`. Format
 /// the error to see a summary of the error bits.
+///
+/// Some errors are detected by the driver before the hardware is involved.
+/// These errors have a [`ConfigurationError`], and their error status is zero.
+///
+/// Errors from the hardware also carry a copy of the channel's transfer control
+/// descriptor (TCD), taken when the error was observed. Use [`tcd`](Self::tcd)
+/// to see the configuration that the hardware rejected.
 #[derive(Clone, Copy)]
 pub struct Error {
     /// The raw error status
     es: u32,
+    /// Set if the driver detected the error
+    configuration: Option<ConfigurationError>,
+    /// The channel's TCD at the point of the error
+    tcd: Option<TcdSnapshot>,
+    /// The transfer that failed
+    transfer_id: Option<u32>,
+    /// The channel that produced the error, if it's wider than ERRCHN
+    channel: Option<usize>,
+}
+
+/// An error detected by the driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ConfigurationError {
+    /// The channel is enabled, active, or receiving a hardware request
+    ChannelBusy,
+    /// The channel index is greater than or equal to the number of channels
+    InvalidChannel,
+    /// A peripheral's data register isn't aligned to the element size
+    Alignment,
+    /// The buffer is empty, or it's too large for a single transfer
+    InvalidLength,
+    /// The peripheral's request signal doesn't fit in the multiplexer
+    InvalidSignal,
+    /// The hardware doesn't support the request
+    Unsupported,
+    /// A setting is out of range, or it conflicts with another setting
+    InvalidValue,
+    /// A self-test transfer didn't reproduce its data
+    VerificationFailed,
+    /// A buffer is outside of the driver's memory map
+    UnreachableMemory,
+    /// Software didn't keep up with a continuous transfer
+    Overrun,
+    /// Channels that work together belong to different DMA controllers
+    DifferentControllers,
+}
+
+/// The category of a DMA error
+///
+/// Use [`Error::kind`] to get the category of an error. An error status may
+/// have more than one error bit set; `kind` reports the most specific one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The driver detected the error before the hardware was involved
+    Configuration(ConfigurationError),
+    /// The transfer was cancelled
+    Cancelled,
+    /// A bus error while reading the source
+    SourceBus,
+    /// A bus error while writing the destination
+    DestinationBus,
+    /// The source address isn't aligned to the source transfer size
+    SourceAddress,
+    /// The destination address isn't aligned to the destination transfer size
+    DestinationAddress,
+    /// The source offset isn't aligned to the source transfer size
+    SourceOffset,
+    /// The destination offset isn't aligned to the destination transfer size
+    DestinationOffset,
+    /// The minor loop byte count or the major loop count is invalid
+    NbytesCiter,
+    /// The scatter / gather TCD address is invalid
+    ScatterGather,
+    /// Two channels share a priority
+    ChannelPriority,
+    /// Two channel groups share a priority
+    GroupPriority,
+    /// The error status doesn't describe a known error
+    Unknown,
 }
 
 impl Error {
     #[inline(always)]
     pub(crate) const fn new(es: u32) -> Self {
-        Error { es }
+        Error {
+            es,
+            configuration: None,
+            tcd: None,
+            transfer_id: None,
+            channel: None,
+        }
+    }
+    /// An error that describes a cancelled transfer on `channel`
+    #[inline(always)]
+    pub(crate) const fn cancelled(channel: usize) -> Self {
+        Error::new(1 << 31 | 1 << 16 | (channel as u32 & 0x1F) << 8).with_channel(channel)
+    }
+    /// An error on `channel` whose details were replaced by another
+    /// channel's error
+    ///
+    /// The error is valid, but it doesn't indicate a cause.
+    #[inline(always)]
+    pub(crate) const fn lost(channel: usize) -> Self {
+        Error::new(1 << 31 | (channel as u32 & 0x1F) << 8).with_channel(channel)
+    }
+    /// Note that `channel` produced this error
+    ///
+    /// The error status only has room for channels 0 through 31.
+    #[inline(always)]
+    pub(crate) const fn with_channel(mut self, channel: usize) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+    #[inline(always)]
+    pub(crate) const fn with_tcd(mut self, tcd: TcdSnapshot) -> Self {
+        self.tcd = Some(tcd);
+        self
+    }
+    #[inline(always)]
+    pub(crate) const fn with_transfer_id(mut self, transfer_id: Option<u32>) -> Self {
+        if transfer_id.is_some() {
+            self.transfer_id = transfer_id;
+        }
+        self
+    }
+    #[inline(always)]
+    pub(crate) const fn configuration(error: ConfigurationError) -> Self {
+        Error {
+            es: 0,
+            configuration: Some(error),
+            tcd: None,
+            transfer_id: None,
+            channel: None,
+        }
+    }
+    /// Returns the configuration error, if the driver detected this error
+    #[inline(always)]
+    pub const fn configuration_error(self) -> Option<ConfigurationError> {
+        self.configuration
+    }
+    /// Returns the category of this error
+    ///
+    /// ```
+    /// use imxrt_dma::{Error, ErrorKind};
+    ///
+    /// fn should_retry(error: Error) -> bool {
+    ///     matches!(error.kind(), ErrorKind::SourceBus | ErrorKind::DestinationBus)
+    /// }
+    /// ```
+    ///
+    /// Bus errors are [retryable](Self::is_retryable), configuration errors are
+    /// [fatal](Self::is_fatal), and cancelled transfers are neither.
+    ///
+    /// ```
+    /// # #[cfg(feature = "sim")] {
+    /// use imxrt_dma::{memcpy, sim::{Fault, Simulator}, ConfigurationError, ErrorKind};
+    ///
+    /// let sim = Simulator::<32>::new();
+    /// let mut channel = sim.dma().try_channel(0).unwrap();
+    /// let source = [1u32; 4];
+    /// let mut destination = [0u32; 4];
+    /// // Safety: buffers outlive the transfers.
+    /// unsafe {
+    ///     sim.map(&source);
+    ///     sim.map(&destination);
+    /// }
+    ///
+    /// sim.inject(0, Fault::DestinationBusError { beat: 2 });
+    /// let error = sim
+    ///     .block_on(memcpy::memcpy(&source, &mut destination, &mut channel))
+    ///     .unwrap_err();
+    /// assert_eq!(error.kind(), ErrorKind::DestinationBus);
+    /// assert!(error.is_retryable() && !error.is_fatal());
+    ///
+    /// sim.inject(0, Fault::Cancel { beat: 1 });
+    /// let error = sim
+    ///     .block_on(memcpy::memcpy(&source, &mut destination, &mut channel))
+    ///     .unwrap_err();
+    /// assert_eq!(error.kind(), ErrorKind::Cancelled);
+    /// assert!(!error.is_retryable() && !error.is_fatal());
+    ///
+    /// let Err(error) = memcpy::try_memcpy(&source[..0], &mut destination, &mut channel) else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(
+    ///     error.kind(),
+    ///     ErrorKind::Configuration(ConfigurationError::InvalidLength)
+    /// );
+    /// assert!(!error.is_retryable() && error.is_fatal());
+    /// # }
+    /// ```
+    pub const fn kind(self) -> ErrorKind {
+        if let Some(configuration) = self.configuration {
+            ErrorKind::Configuration(configuration)
+        } else if self.is_cancelled() {
+            ErrorKind::Cancelled
+        } else if self.is_source_bus() {
+            ErrorKind::SourceBus
+        } else if self.is_destination_bus() {
+            ErrorKind::DestinationBus
+        } else if self.is_source_address() {
+            ErrorKind::SourceAddress
+        } else if self.is_destination_address() {
+            ErrorKind::DestinationAddress
+        } else if self.is_source_offset() {
+            ErrorKind::SourceOffset
+        } else if self.is_destination_offset() {
+            ErrorKind::DestinationOffset
+        } else if self.is_loop_configuration() {
+            ErrorKind::NbytesCiter
+        } else if self.is_scatter_gather() {
+            ErrorKind::ScatterGather
+        } else if self.is_channel_priority() {
+            ErrorKind::ChannelPriority
+        } else if self.is_group_priority() {
+            ErrorKind::GroupPriority
+        } else {
+            ErrorKind::Unknown
+        }
+    }
+    /// Returns the channel's TCD, captured when the error was observed
+    ///
+    /// Returns `None` for a [`configuration_error`](Self::configuration_error).
+    /// The snapshot is taken after the error, so the iteration count and
+    /// addresses show how far the transfer progressed.
+    #[inline(always)]
+    pub const fn tcd(&self) -> Option<&TcdSnapshot> {
+        self.tcd.as_ref()
+    }
+    /// Returns the ID of the transfer that failed
+    ///
+    /// Returns `None` for a [`configuration_error`](Self::configuration_error),
+    /// and for errors that aren't associated with a [`Transfer`](crate::Transfer).
+    /// See [`Transfer::id`](crate::Transfer::id).
+    #[inline(always)]
+    pub const fn transfer_id(&self) -> Option<u32> {
+        self.transfer_id
+    }
+    /// Indicates that the error may be transient, and the transfer may
+    /// succeed if it's tried again
+    ///
+    /// Bus errors are retryable. For example, external memory may not be
+    /// ready the first time the DMA controller accesses it. A cancelled
+    /// transfer isn't retryable, since software asked to stop it.
+    #[inline(always)]
+    pub const fn is_retryable(self) -> bool {
+        matches!(
+            self.kind(),
+            ErrorKind::SourceBus | ErrorKind::DestinationBus
+        )
+    }
+    /// Indicates that the transfer is misconfigured, and it will fail again
+    /// if it's tried again
+    ///
+    /// Address, offset, loop, scatter / gather, and priority errors are
+    /// fatal, as are all [configuration errors](Self::configuration_error).
+    /// An error can be neither retryable nor fatal; for example, a cancelled
+    /// transfer.
+    #[inline(always)]
+    pub const fn is_fatal(self) -> bool {
+        matches!(
+            self.kind(),
+            ErrorKind::Configuration(_)
+                | ErrorKind::SourceAddress
+                | ErrorKind::DestinationAddress
+                | ErrorKind::SourceOffset
+                | ErrorKind::DestinationOffset
+                | ErrorKind::NbytesCiter
+                | ErrorKind::ScatterGather
+                | ErrorKind::ChannelPriority
+                | ErrorKind::GroupPriority
+        )
     }
     /// Returns the raw error status value
     #[inline(always)]
@@ -30,12 +298,17 @@ impl Error {
     }
     /// Logical OR of all DMA channel error status bits
     ///
-    /// If you have an `Error` this should always be true.
+    /// If you have an `Error` from the hardware this should always be true.
+    /// It's false for a [`configuration_error`](Self::configuration_error).
     #[inline(always)]
     pub const fn is_valid(self) -> bool {
         self.is_bit(31)
     }
     /// Indicates if the transfer was cancelled
+    ///
+    /// A cancelled transfer isn't a hardware fault. This is set when
+    /// software cancels the transfer, for example with
+    /// [`Channel::cancel`](crate::channel::Channel::cancel).
     #[inline(always)]
     pub const fn is_cancelled(self) -> bool {
         self.is_bit(16)
@@ -51,6 +324,10 @@ impl Error {
         self.is_bit(14)
     }
     /// Indicates the channel number
+    ///
+    /// This is the error status' 5-bit ERRCHN field. It can't describe eDMA4
+    /// channels 32 and above; use [`error_channel`](Self::error_channel) for
+    /// the full channel number.
     #[inline(always)]
     pub const fn channel_number(self) -> u32 {
         (self.es >> 8) & 0x1F
@@ -95,16 +372,82 @@ impl Error {
     pub const fn is_destination_bus(self) -> bool {
         self.is_bit(0)
     }
+    /// Returns the channel that produced the error
+    ///
+    /// Returns `None` if the error status isn't [valid](Self::is_valid). That
+    /// includes every [`configuration_error`](Self::configuration_error).
+    #[inline(always)]
+    pub const fn error_channel(self) -> Option<usize> {
+        if !self.is_valid() {
+            None
+        } else if let Some(channel) = self.channel {
+            Some(channel)
+        } else {
+            Some(self.channel_number() as usize)
+        }
+    }
+    /// Indicates a bus error while reading the source
+    ///
+    /// Same as [`is_source_bus`](Self::is_source_bus).
+    #[inline(always)]
+    pub const fn is_source_bus_error(self) -> bool {
+        self.is_source_bus()
+    }
+    /// Indicates a bus error while writing the destination
+    ///
+    /// Same as [`is_destination_bus`](Self::is_destination_bus).
+    #[inline(always)]
+    pub const fn is_destination_bus_error(self) -> bool {
+        self.is_destination_bus()
+    }
+    /// Indicates that the minor loop byte count (NBYTES) or the major loop
+    /// count (CITER) is invalid
+    ///
+    /// Same as [`is_loop_configuration`](Self::is_loop_configuration).
+    #[inline(always)]
+    pub const fn is_nbytes_error(self) -> bool {
+        self.is_loop_configuration()
+    }
+    /// Indicates an invalid scatter / gather TCD address
+    ///
+    /// Same as [`is_scatter_gather`](Self::is_scatter_gather).
+    #[inline(always)]
+    pub const fn is_scatter_gather_error(self) -> bool {
+        self.is_scatter_gather()
+    }
+    /// Indicates an invalid source address or source offset
+    #[inline(always)]
+    pub const fn is_source_error(self) -> bool {
+        self.is_source_address() || self.is_source_offset()
+    }
+    /// Indicates an invalid destination address or destination offset
+    #[inline(always)]
+    pub const fn is_destination_error(self) -> bool {
+        self.is_destination_address() || self.is_destination_offset()
+    }
 }
 
 impl Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "DMA_ES({:#010X})", self.es)
+        if let Some(configuration) = self.configuration {
+            return write!(f, "DMA_CONFIGURATION({configuration:?})");
+        }
+        write!(f, "DMA_ES({:#010X})", self.es)?;
+        if let Some(transfer_id) = self.transfer_id {
+            write!(f, " transfer {transfer_id}")?;
+        }
+        if let Some(tcd) = &self.tcd {
+            write!(f, " {tcd:?}")?;
+        }
+        Ok(())
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(configuration) = self.configuration {
+            return write!(f, "DMA configuration error: {configuration:?}");
+        }
         write!(f,
              "DMA_ES: VLD {vld} ECX {ecx} GPE {gpe} CPE {cpe} ERRCHN {errchn} SAE {sae} SOE {soe} DAE {dae} DOE {doe} NCE {nce} SGE {sge} SBE {sbe} DBE {dbe}",
              vld = self.is_valid() as u32,
@@ -123,3 +466,171 @@ impl Display for Error {
          )
     }
 }
+
+impl core::error::Error for Error {}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Error {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        if let Some(configuration) = self.configuration {
+            defmt::write!(f, "DMA_CONFIGURATION({})", configuration);
+            return;
+        }
+        defmt::write!(f, "DMA_ES({=u32:#X})", self.es);
+        if let Some(transfer_id) = self.transfer_id {
+            defmt::write!(f, " transfer {=u32}", transfer_id);
+        }
+        if let Some(tcd) = &self.tcd {
+            defmt::write!(f, " {}", tcd);
+        }
+    }
+}
+
+impl ConfigurationError {
+    const fn description(self) -> &'static str {
+        match self {
+            ConfigurationError::ChannelBusy => "channel is busy",
+            ConfigurationError::InvalidChannel => "invalid channel index",
+            ConfigurationError::Alignment => "misaligned peripheral address",
+            ConfigurationError::InvalidLength => "invalid buffer length",
+            ConfigurationError::InvalidSignal => "invalid request signal",
+            ConfigurationError::Unsupported => "unsupported by the hardware",
+            ConfigurationError::InvalidValue => "setting out of range",
+            ConfigurationError::VerificationFailed => "data verification failed",
+            ConfigurationError::UnreachableMemory => "buffer outside of DMA memory map",
+            ConfigurationError::Overrun => "stream overrun",
+            ConfigurationError::DifferentControllers => "channels on different controllers",
+        }
+    }
+}
+
+impl Display for ConfigurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl core::error::Error for ConfigurationError {}
+
+#[cfg(feature = "ufmt")]
+mod ufmt_impls {
+    use super::{ConfigurationError, Error, ErrorKind};
+    use ufmt::{uDebug, uDisplay, uWrite, uwrite, Formatter};
+
+    impl ConfigurationError {
+        const fn name(self) -> &'static str {
+            match self {
+                ConfigurationError::ChannelBusy => "ChannelBusy",
+                ConfigurationError::InvalidChannel => "InvalidChannel",
+                ConfigurationError::Alignment => "Alignment",
+                ConfigurationError::InvalidLength => "InvalidLength",
+                ConfigurationError::InvalidSignal => "InvalidSignal",
+                ConfigurationError::Unsupported => "Unsupported",
+                ConfigurationError::InvalidValue => "InvalidValue",
+                ConfigurationError::VerificationFailed => "VerificationFailed",
+                ConfigurationError::UnreachableMemory => "UnreachableMemory",
+                ConfigurationError::Overrun => "Overrun",
+                ConfigurationError::DifferentControllers => "DifferentControllers",
+            }
+        }
+    }
+
+    impl uDebug for ConfigurationError {
+        fn fmt<W>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error>
+        where
+            W: uWrite + ?Sized,
+        {
+            f.write_str(self.name())
+        }
+    }
+
+    impl uDisplay for ConfigurationError {
+        fn fmt<W>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error>
+        where
+            W: uWrite + ?Sized,
+        {
+            f.write_str(self.description())
+        }
+    }
+
+    impl uDebug for ErrorKind {
+        fn fmt<W>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error>
+        where
+            W: uWrite + ?Sized,
+        {
+            let name = match self {
+                ErrorKind::Configuration(configuration) => {
+                    return uwrite!(f, "Configuration({:?})", configuration);
+                }
+                ErrorKind::Cancelled => "Cancelled",
+                ErrorKind::SourceBus => "SourceBus",
+                ErrorKind::DestinationBus => "DestinationBus",
+                ErrorKind::SourceAddress => "SourceAddress",
+                ErrorKind::DestinationAddress => "DestinationAddress",
+                ErrorKind::SourceOffset => "SourceOffset",
+                ErrorKind::DestinationOffset => "DestinationOffset",
+                ErrorKind::NbytesCiter => "NbytesCiter",
+                ErrorKind::ScatterGather => "ScatterGather",
+                ErrorKind::ChannelPriority => "ChannelPriority",
+                ErrorKind::GroupPriority => "GroupPriority",
+                ErrorKind::Unknown => "Unknown",
+            };
+            f.write_str(name)
+        }
+    }
+
+    /// Matches the `Debug` output, without the TCD snapshot.
+    impl uDebug for Error {
+        fn fmt<W>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error>
+        where
+            W: uWrite + ?Sized,
+        {
+            if let Some(configuration) = self.configuration {
+                return uwrite!(f, "DMA_CONFIGURATION({:?})", configuration);
+            }
+            f.write_str("DMA_ES(0x")?;
+            for shift in (0..32).step_by(4).rev() {
+                let nibble = (self.es >> shift) & 0xF;
+                f.write_char(
+                    char::from_digit(nibble, 16)
+                        .unwrap_or('?')
+                        .to_ascii_uppercase(),
+                )?;
+            }
+            f.write_str(")")?;
+            if let Some(transfer_id) = self.transfer_id {
+                uwrite!(f, " transfer {}", transfer_id)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Matches the `Display` output.
+    impl uDisplay for Error {
+        fn fmt<W>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error>
+        where
+            W: uWrite + ?Sized,
+        {
+            if let Some(configuration) = self.configuration {
+                return uwrite!(f, "DMA configuration error: {:?}", configuration);
+            }
+            uwrite!(
+                f,
+                "DMA_ES: VLD {} ECX {} GPE {} CPE {} ERRCHN {} SAE {} SOE {} DAE {} DOE {} NCE {} SGE {} SBE {} DBE {}",
+                self.is_valid() as u32,
+                self.is_cancelled() as u32,
+                self.is_group_priority() as u32,
+                self.is_channel_priority() as u32,
+                self.channel_number(),
+                self.is_source_address() as u32,
+                self.is_source_offset() as u32,
+                self.is_destination_address() as u32,
+                self.is_destination_offset() as u32,
+                self.is_loop_configuration() as u32,
+                self.is_scatter_gather() as u32,
+                self.is_source_bus() as u32,
+                self.is_destination_bus() as u32
+            )
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! A channel that's shared by reference
+//!
+//! A transfer future needs a `&mut Channel` for as long as it runs. That's
+//! awkward when the channel lives in a shared resource, like an RTIC
+//! `#[shared]` resource or a `static`. The resource's lock can't be held
+//! across an `.await`, and every status query would need the lock.
+//!
+//! An [`Exclusive`] channel is [`Sync`], so you can share it by reference.
+//! Any context can query the channel's state without a lock. A context
+//! that wants to run a transfer [`lease`](Exclusive::lease)s the channel.
+//! There's at most one lease at a time, and the lease dereferences to a
+//! `&mut Channel`. Use the lease to create a transfer future, and drop the
+//! lease once the transfer is done.
+//!
+//! ```no_run
+//! use imxrt_dma::{exclusive::Exclusive, Dma};
+//! # const DMA_PTR: *const () = core::ptr::null() as _;
+//! # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+//!
+//! static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+//!
+//! // In an RTIC app, this could be a #[shared] resource that's accessed by
+//! // reference.
+//! let channel = Exclusive::new(DMA.try_channel(7).unwrap());
+//!
+//! let mut lease = channel.lease().unwrap();
+//! assert!(channel.lease().is_none());
+//! assert!(channel.is_leased());
+//!
+//! // Use `&mut lease` with memcpy, or the peripheral transfers...
+//! # let _: &mut imxrt_dma::channel::Channel = &mut lease;
+//! drop(lease);
+//!
+//! // Status queries never need a lease.
+//! if channel.is_error() {
+//!     let error = channel.error_status();
+//!     // Log the error...
+//! }
+//! ```
+
+use crate::{channel::Channel, Error};
+
+use core::{
+    cell::UnsafeCell,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// A channel that can be shared by reference
+///
+/// See the [module documentation](crate::exclusive) for more information.
+pub struct Exclusive {
+    channel: UnsafeCell<Channel>,
+    /// Another handle to the channel, only used for status queries.
+    status: Channel,
+    leased: AtomicBool,
+}
+
+// Safety: a lease is the only way to get the channel. There's at most one
+// lease at a time. Status queries only read registers.
+unsafe impl Sync for Exclusive {}
+
+impl Exclusive {
+    /// Wrap `channel`, so that it can be shared by reference
+    pub fn new(channel: Channel) -> Self {
+        // Safety: the alias is only used for status queries.
+        let status = unsafe { channel.alias() };
+        Exclusive {
+            channel: UnsafeCell::new(channel),
+            status,
+            leased: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the channel
+    pub fn into_inner(self) -> Channel {
+        self.channel.into_inner()
+    }
+
+    /// Lease the channel
+    ///
+    /// Returns `None` if the channel is already leased.
+    pub fn lease(&self) -> Option<Lease<'_>> {
+        if self.leased.swap(true, Ordering::Acquire) {
+            return None;
+        }
+        Some(Lease { exclusive: self })
+    }
+
+    /// Returns `true` if the channel is leased
+    pub fn is_leased(&self) -> bool {
+        self.leased.load(Ordering::Relaxed)
+    }
+
+    /// Returns the DMA channel number
+    pub fn channel(&self) -> usize {
+        self.status.channel()
+    }
+
+    /// Indicates if the DMA transfer has completed
+    pub fn is_complete(&self) -> bool {
+        self.status.is_complete()
+    }
+
+    /// Indicates if the DMA channel is in an error state
+    pub fn is_error(&self) -> bool {
+        self.status.is_error()
+    }
+
+    /// Indicates if this DMA channel is actively transferring data
+    pub fn is_active(&self) -> bool {
+        self.status.is_active()
+    }
+
+    /// Indicates if this DMA channel is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.status.is_enabled()
+    }
+
+    /// Returns `true` if the DMA channel is receiving a service signal from hardware
+    pub fn is_hardware_signaling(&self) -> bool {
+        self.status.is_hardware_signaling()
+    }
+
+    /// Returns the current major loop iteration count
+    ///
+    /// See [`Channel::current_transfer_iterations`].
+    pub fn current_transfer_iterations(&self) -> u16 {
+        self.status.current_transfer_iterations()
+    }
+
+    /// Returns the error status
+    ///
+    /// See [`Channel::error_status`] for more information.
+    pub fn error_status(&self) -> Error {
+        self.status.error_status()
+    }
+}
+
+/// Exclusive access to a channel
+///
+/// `Lease` dereferences to a [`Channel`]. Dropping the lease lets another
+/// context lease the channel.
+pub struct Lease<'a> {
+    exclusive: &'a Exclusive,
+}
+
+impl Deref for Lease<'_> {
+    type Target = Channel;
+    fn deref(&self) -> &Channel {
+        // Safety: the lease has exclusive access to the channel.
+        unsafe { &*self.exclusive.channel.get() }
+    }
+}
+
+impl DerefMut for Lease<'_> {
+    fn deref_mut(&mut self) -> &mut Channel {
+        // Safety: the lease has exclusive access to the channel.
+        unsafe { &mut *self.exclusive.channel.get() }
+    }
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        self.exclusive.leased.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,82 @@
+//! Minimal executors for transfer futures
+//!
+//! If your application doesn't have an async executor, use these functions
+//! to run a transfer future to completion.
+//!
+//! - [`block_on`] polls the future in a busy loop. It never sleeps, and it
+//!   doesn't need DMA interrupts.
+//! - [`wfi_on`] waits for an interrupt between polls. Enable the channel's
+//!   interrupt, and call [`on_interrupt`](crate::Dma::on_interrupt) from the
+//!   interrupt handler, so that the CPU wakes when the transfer completes.
+//!
+//! Neither function uses the future's waker; it's a no-op.
+//!
+//! ```no_run
+//! use imxrt_dma::{exec, memcpy};
+//! # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+//!
+//! let mut channel = DMA.try_channel(7).unwrap();
+//! let source = [4u32, 5, 6, 7];
+//! let mut destination = [0u32; 4];
+//!
+//! exec::block_on(memcpy::memcpy(&source, &mut destination, &mut channel)).unwrap();
+//! ```
+//!
+//! This module requires the `"exec"` feature.
+
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+/// Poll `future` until it completes
+///
+/// ```
+/// use imxrt_dma::exec;
+///
+/// assert_eq!(exec::block_on(async { 5 }), 5);
+/// ```
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Poll `future` until it completes, waiting for an interrupt between polls
+///
+/// `wfi_on` polls the future with interrupts masked. If the future isn't
+/// ready, it executes `WFI`, then unmasks interrupts so that the pending
+/// interrupt runs. An interrupt that arrives after the poll still wakes the
+/// CPU, so it's never missed. The future must eventually complete after
+/// some interrupt; otherwise, the CPU sleeps forever.
+///
+/// Don't call `wfi_on` from a critical section or an interrupt handler. It
+/// unmasks interrupts after each poll.
+///
+/// On a hosted target, there's no `WFI`, and this behaves like [`block_on`].
+pub fn wfi_on<F: Future>(future: F) -> F::Output {
+    #[cfg(target_os = "none")]
+    {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            cortex_m::interrupt::disable();
+            let poll = future.as_mut().poll(&mut cx);
+            if poll.is_pending() {
+                cortex_m::asm::wfi();
+            }
+            // Safety: we're not in a critical section.
+            unsafe { cortex_m::interrupt::enable() };
+            if let Poll::Ready(output) = poll {
+                return output;
+            }
+        }
+    }
+    #[cfg(not(target_os = "none"))]
+    block_on(future)
+}
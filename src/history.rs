@@ -0,0 +1,178 @@
+//! A record of recent DMA errors
+
+use crate::{critical, Error};
+use core::cell::RefCell;
+
+use cortex_m::interrupt::Mutex;
+
+/// The number of errors kept by each driver
+pub const HISTORY_LEN: usize = 8;
+
+/// An error observed by [`on_interrupt`](crate::Dma::on_interrupt)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ErrorRecord {
+    channel: usize,
+    es: u32,
+    timestamp: Option<u32>,
+    transfer_id: Option<u32>,
+}
+
+impl ErrorRecord {
+    const EMPTY: Self = ErrorRecord {
+        channel: 0,
+        es: 0,
+        timestamp: None,
+        transfer_id: None,
+    };
+
+    /// Returns the channel that was in an error state
+    pub const fn channel(&self) -> usize {
+        self.channel
+    }
+    /// Returns the error status
+    ///
+    /// The error doesn't include a TCD snapshot.
+    pub const fn error(&self) -> Error {
+        Error::new(self.es)
+            .with_channel(self.channel)
+            .with_transfer_id(self.transfer_id)
+    }
+    /// Returns the time of the error, if there was a timestamp source
+    ///
+    /// See [`set_error_timestamp`](crate::Dma::set_error_timestamp).
+    pub const fn timestamp(&self) -> Option<u32> {
+        self.timestamp
+    }
+    /// Returns the ID of the transfer that failed, if it's known
+    ///
+    /// See [`Error::transfer_id`].
+    pub const fn transfer_id(&self) -> Option<u32> {
+        self.transfer_id
+    }
+}
+
+/// A copy of a driver's most recent errors
+///
+/// Use [`error_history`](crate::Dma::error_history) to get a copy. The history
+/// holds the last [`HISTORY_LEN`] errors; older errors are overwritten.
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorHistory {
+    records: [ErrorRecord; HISTORY_LEN],
+    /// The total number of errors ever recorded. The next record
+    /// goes at `count % HISTORY_LEN`.
+    count: u32,
+    timestamp: Option<fn() -> u32>,
+}
+
+impl ErrorHistory {
+    pub(crate) const fn new() -> Self {
+        ErrorHistory {
+            records: [ErrorRecord::EMPTY; HISTORY_LEN],
+            count: 0,
+            timestamp: None,
+        }
+    }
+
+    fn push(&mut self, channel: usize, error: Error) {
+        let timestamp = self.timestamp.map(|timestamp| timestamp());
+        self.records[self.count as usize % HISTORY_LEN] = ErrorRecord {
+            channel,
+            es: error.raw(),
+            timestamp,
+            transfer_id: error.transfer_id(),
+        };
+        self.count = self.count.wrapping_add(1);
+    }
+
+    /// Returns the number of errors in the history
+    pub fn len(&self) -> usize {
+        (self.count as usize).min(HISTORY_LEN)
+    }
+
+    /// Returns `true` if there are no errors in the history
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the number of errors recorded since the history was cleared
+    ///
+    /// If this is larger than [`len`](Self::len), some errors were overwritten.
+    pub fn total(&self) -> u32 {
+        self.count
+    }
+
+    /// Iterate over the errors, from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = &ErrorRecord> + '_ {
+        let start = self.count as usize - self.len();
+        (start..self.count as usize).map(|index| &self.records[index % HISTORY_LEN])
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ErrorHistory {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "ErrorHistory {{ total: {}, records: [", self.count);
+        for (index, record) in self.iter().enumerate() {
+            if index > 0 {
+                defmt::write!(f, ", ");
+            }
+            defmt::write!(f, "{}", record);
+        }
+        defmt::write!(f, "] }}");
+    }
+}
+
+pub(crate) type SharedErrorHistory = Mutex<RefCell<ErrorHistory>>;
+
+/// Record `error` for `channel`
+pub(crate) fn record(history: &SharedErrorHistory, channel: usize, error: Error) {
+    critical::free(|cs| history.borrow(cs).borrow_mut().push(channel, error));
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    crate::Dma<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Returns a copy of the most recent errors
+    ///
+    /// [`on_interrupt`](Self::on_interrupt) records an error when it finds a channel
+    /// in an error state. Use the history in bug reports, or to find errors that no
+    /// transfer future observed.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::Dma;
+    /// static DMA: Dma<32> = // Handle to DMA driver.
+    /// # unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// for record in DMA.error_history().iter() {
+    ///     let (channel, error) = (record.channel(), record.error());
+    ///     // Log the error...
+    /// }
+    /// ```
+    pub fn error_history(&self) -> ErrorHistory {
+        critical::free(|cs| *self.error_history.borrow(cs).borrow())
+    }
+
+    /// Remove all errors from the history
+    ///
+    /// This keeps the timestamp source.
+    pub fn clear_error_history(&self) {
+        critical::free(|cs| {
+            let mut history = self.error_history.borrow(cs).borrow_mut();
+            *history = ErrorHistory {
+                timestamp: history.timestamp,
+                ..ErrorHistory::new()
+            };
+        });
+    }
+
+    /// Set a function that timestamps each error in the history
+    ///
+    /// `timestamp` runs in the interrupt handler that called
+    /// [`on_interrupt`](Self::on_interrupt). For example, it could read a
+    /// free-running timer. `None` removes the timestamp source. There's no
+    /// timestamp source by default.
+    pub fn set_error_timestamp(&self, timestamp: Option<fn() -> u32>) {
+        critical::free(|cs| self.error_history.borrow(cs).borrow_mut().timestamp = timestamp);
+    }
+}
@@ -1,23 +1,42 @@
 //! DMA interrupt support
 
-use crate::{channel::Channel, Error};
+use crate::{
+    channel::{Channel, TransferStatus},
+    critical, trace, Error,
+};
 use core::{
-    cell::RefCell,
+    cell::Cell,
     future::Future,
     marker::PhantomPinned,
+    ops::Range,
     pin::Pin,
-    sync::atomic,
+    sync::atomic::{self, AtomicU32},
     task::{Context, Poll, Waker},
 };
 
-use cortex_m::interrupt::{self, Mutex};
+use cortex_m::interrupt::Mutex;
 
-impl<const CHANNELS: usize> super::Dma<CHANNELS> {
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    super::Dma<CHANNELS, WAKERS, WATCHERS>
+{
     /// Handle a DMA interrupt
     ///
     /// Checks the interrupt status for the channel identified by `channel`.
-    /// If the channel completed its transfer, `on_interrupt` wakes the channel's
-    /// waker.
+    /// If the channel generated an interrupt, or if the channel completed its
+    /// transfer, `on_interrupt` wakes the channel's waker, and any tasks waiting
+    /// in [`wait_complete`](Self::wait_complete). If the channel is
+    /// in an error state, `on_interrupt` also calls the error handler; see
+    /// [`set_error_handler`](Self::set_error_handler). Otherwise, if the channel
+    /// interrupted, it calls the progress handler; see
+    /// [`set_progress_handler`](Self::set_progress_handler).
+    ///
+    /// The eDMA error status register only describes the most recent error.
+    /// `on_interrupt` latches the channel's error
+    /// status, so that the channel's transfer future reports its own error,
+    /// even if another channel fails before the future is polled. If another
+    /// channel fails before `on_interrupt` runs for this channel, the latched
+    /// error names this channel, but its cause is lost; its
+    /// [`kind`](crate::Error::kind) is [`Unknown`](crate::ErrorKind::Unknown).
     ///
     /// Consider calling `on_interrupt` in a DMA channel's interrupt handler:
     ///
@@ -48,26 +67,221 @@ impl<const CHANNELS: usize> super::Dma<CHANNELS> {
     /// Panics if `channel` is greater than or equal to the maximum number of channels.
     #[inline(always)]
     pub unsafe fn on_interrupt(&'static self, channel: usize) {
+        // Safety: caller upholds the same requirements.
+        unsafe { self.erase().on_interrupt(channel) }
+    }
+}
+
+impl crate::DmaRef {
+    /// Handle a DMA interrupt
+    ///
+    /// See [`Dma::on_interrupt`](crate::Dma::on_interrupt) for more information.
+    ///
+    /// # Safety
+    ///
+    /// This should only be used when the associated DMA channel is exclusively referenced
+    /// by a DMA transfer future. Caller must ensure that `on_interrupt` is called in
+    /// the correct interrupt handler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than or equal to the maximum number of channels.
+    #[cfg_attr(not(feature = "hot-section"), inline(always))]
+    #[cfg_attr(feature = "hot-section", inline(never))]
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.on_interrupt"
+    )]
+    pub unsafe fn on_interrupt(&self, channel: usize) {
         let channel = self.channel(channel);
-        if channel.is_interrupt() {
+        let interrupted = channel.is_interrupt();
+        if interrupted {
             channel.clear_interrupt();
         }
 
-        if channel.is_complete() | channel.is_error() {
-            interrupt::free(|cs| {
-                let waker = self.wakers[channel.channel()].borrow(cs);
-                let mut waker = waker.borrow_mut();
-                if let Some(waker) = waker.take() {
+        let errored = channel.is_error();
+        if errored {
+            let (error, new) = channel.latch_error();
+            if new {
+                crate::history::record(self.error_history, channel.channel(), error);
+            }
+            let handler = critical::free(|cs| self.error_handler.borrow(cs).get());
+            if let Some(handler) = handler {
+                handler(channel.channel(), error);
+            }
+        } else if interrupted {
+            crate::progress::notify(self.progress_handler, &channel);
+        }
+
+        if interrupted | channel.is_complete() | errored {
+            if let Some(waker) = channel.waker {
+                // Wake outside of the critical section.
+                if let Some(waker) = critical::free(|cs| waker.borrow(cs).take()) {
                     waker.wake();
                 }
-            });
+            }
+            crate::watch::wake(self, channel.channel());
         }
     }
 }
 
-pub(crate) type SharedWaker = Mutex<RefCell<Option<Waker>>>;
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    super::Dma<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Set a handler for all channel errors
+    ///
+    /// [`on_interrupt`](Self::on_interrupt) calls `handler` with the channel number
+    /// and error status whenever it finds a channel in an error state. Use this to
+    /// handle faults for the whole system, without relying on each transfer future
+    /// to observe its error. `handler` runs in the interrupt handler that called
+    /// `on_interrupt`, so keep it short.
+    ///
+    /// The error stays set until the channel's transfer future observes it. If
+    /// `on_interrupt` runs again before then, it calls `handler` again. The
+    /// [error history](Self::error_history) only records the error once.
+    ///
+    /// `None` removes the handler. There's no handler by default.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::{Dma, Error};
+    /// static DMA: Dma<32> = // Handle to DMA driver.
+    /// # unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// fn on_dma_error(channel: usize, error: Error) {
+    ///     // Log the error, or put the system into a safe state...
+    /// }
+    ///
+    /// DMA.set_error_handler(Some(on_dma_error));
+    /// ```
+    pub fn set_error_handler(&self, handler: Option<fn(usize, Error)>) {
+        critical::free(|cs| self.error_handler.borrow(cs).set(handler));
+    }
+
+    /// Wait for a peripheral to request service from `channel`
+    ///
+    /// The future resolves once the channel's hardware request status shows
+    /// that its peripheral is signaling the DMA controller. Use this to diagnose
+    /// a peripheral that never triggers its DMA channel, or to sequence start-up
+    /// steps. This doesn't allocate, enable, or otherwise change the channel.
+    ///
+    /// There's no interrupt for a hardware request, so the future wakes itself
+    /// each time it's polled, until the request arrives. Don't use this for
+    /// long waits on an executor that needs to sleep.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::Dma;
+    /// static DMA: Dma<32> = // Handle to DMA driver.
+    /// # unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// # async fn f() {
+    /// DMA.wait_hardware_request(7).await;
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than or equal to the maximum number of channels.
+    pub fn wait_hardware_request(&'static self, channel: usize) -> HardwareRequest {
+        HardwareRequest {
+            // Safety: the future only reads the channel's hardware request status.
+            channel: unsafe { self.channel(channel) },
+        }
+    }
+
+    /// Wait for all channels to become idle
+    ///
+    /// The future resolves once no channel is active, and no channel is
+    /// receiving a hardware request; see [`is_any_active`](Self::is_any_active).
+    /// Use this before shutting down the DMA controller, or before reconfiguring
+    /// memory that in-flight transfers might access. This doesn't stop new
+    /// transfers from starting.
+    ///
+    /// Like [`wait_hardware_request`](Self::wait_hardware_request), the future
+    /// wakes itself each time it's polled, until the channels are idle.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::Dma;
+    /// static DMA: Dma<32> = // Handle to DMA driver.
+    /// # unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// # async fn f() {
+    /// DMA.wait_idle().await;
+    /// // Safe to repartition memory...
+    /// # }
+    /// ```
+    pub fn wait_idle(&'static self) -> Idle<CHANNELS, WAKERS, WATCHERS> {
+        Idle { dma: self }
+    }
+}
+
+impl Channel {
+    /// Register `waker` to be woken by [`on_interrupt`](crate::Dma::on_interrupt)
+    ///
+    /// This replaces any previously-registered waker. One task at a time waits
+    /// on a channel's waker; other tasks wait in [`wait_complete`](crate::Dma::wait_complete).
+    /// If the registered waker would wake the same task, it's kept, and `waker`
+    /// isn't cloned.
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.register_waker"
+    )]
+    pub(crate) fn register_waker(&self, waker: &Waker) {
+        let Some(shared) = self.waker else {
+            return;
+        };
+        // Drop the replaced waker outside of the critical section. Waking it
+        // would let two tasks that poll the same channel wake each other forever.
+        let _replaced = critical::free(|cs| {
+            let shared = shared.borrow(cs);
+            match shared.take() {
+                Some(registered) if registered.will_wake(waker) => {
+                    shared.set(Some(registered));
+                    None
+                }
+                registered => {
+                    shared.set(Some(waker.clone()));
+                    registered
+                }
+            }
+        });
+    }
+
+    /// Remove any registered waker
+    pub(crate) fn clear_waker(&self) {
+        let Some(shared) = self.waker else {
+            return;
+        };
+        let _cleared = critical::free(|cs| shared.borrow(cs).take());
+    }
+}
+
+/// A channel's waker slot
+///
+/// A `Cell`, not a `RefCell`: the slot is only ever swapped, so it doesn't need
+/// a borrow flag. That's two words per channel, instead of three.
+pub(crate) type SharedWaker = Mutex<Cell<Option<Waker>>>;
+pub(crate) type SharedErrorHandler = Mutex<Cell<Option<fn(usize, Error)>>>;
 #[allow(clippy::declare_interior_mutable_const)] // Very convenient, and usage for static init deemed OK in clippy docs
-pub(crate) const NO_WAKER: SharedWaker = Mutex::new(RefCell::new(None));
+pub(crate) const NO_WAKER: SharedWaker = Mutex::new(Cell::new(None));
+#[allow(clippy::declare_interior_mutable_const)] // See NO_WAKER
+pub(crate) const NO_ERROR_HANDLER: SharedErrorHandler = Mutex::new(Cell::new(None));
+#[allow(clippy::declare_interior_mutable_const)] // See NO_WAKER
+pub(crate) const NO_ERROR: AtomicU32 = AtomicU32::new(0);
+#[allow(clippy::declare_interior_mutable_const)] // See NO_WAKER
+pub(crate) const NO_TRANSFER_ID: AtomicU32 = AtomicU32::new(0);
+
+/// The ID of the next transfer
+static NEXT_TRANSFER_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Allocate a transfer ID, which is never zero
+fn next_transfer_id() -> u32 {
+    loop {
+        let id = NEXT_TRANSFER_ID.fetch_add(1, atomic::Ordering::Relaxed);
+        if id != 0 {
+            return id;
+        }
+    }
+}
 
 /// The core DMA transfer future
 ///
@@ -75,7 +289,11 @@ pub(crate) const NO_WAKER: SharedWaker = Mutex::new(RefCell::new(None));
 /// initiate a DMA transfer when it is first polled. You may then poll it
 /// to understand when the transfer completes.
 ///
-/// To cancel a transfer, drop the `Transfer`.
+/// To cancel a transfer, drop the `Transfer`. If another execution context
+/// needs to cancel the transfer, use [`Channel::cancel`]. The `Transfer` then
+/// resolves with an error that [is cancelled](Error::is_cancelled). A `Transfer`
+/// also resolves this way if its channel is disabled before the transfer
+/// completes.
 ///
 /// If you've enabled DMA interrupts, consider using [`on_interrupt`](crate::Dma::on_interrupt)
 /// to wake an executor when the DMA transfer completes, The interrupt interface assumes that you've
@@ -98,6 +316,32 @@ pub(crate) const NO_WAKER: SharedWaker = Mutex::new(RefCell::new(None));
 /// `Transfer` is designed to the DMA `Channel` public interface. If you need to implement
 /// your own transfer future, you may do so.
 ///
+/// # Polling cost
+///
+/// Each poll checks for an error, then for completion, then that the channel is
+/// still enabled, and stops at the first check that's true. On an eDMA controller,
+/// a pending transfer costs three register reads: ERR, the TCD's CSR, and ERQ. An
+/// error latched by [`on_interrupt`](crate::Dma::on_interrupt) costs no register
+/// reads. eDMA3 and eDMA4 channels need at most two reads.
+///
+/// # Memory ordering
+///
+/// Before it enables the channel, `Transfer` issues a sequentially-consistent
+/// fence. Your writes to the source memory happen before the DMA controller
+/// can read it. When the transfer resolves `Ok`, `Transfer` issues an acquire
+/// fence after it observes completion. Your reads of the destination memory
+/// happen after the DMA controller's writes. These fences order CPU accesses;
+/// they don't maintain the data cache. See the `"cache"` feature for that.
+///
+/// # Bounds checks
+///
+/// In debug builds, the transfers created by [`memcpy`](crate::memcpy) and
+/// [`peripheral`](crate::peripheral) know the memory that they may touch. Each
+/// poll checks that the channel's source and destination addresses are still
+/// within that memory, and panics if they aren't. The panic catches a corrupted
+/// TCD, or a wrong offset, before the DMA controller writes over unrelated memory.
+/// A transfer that you create with [`new`](Self::new) isn't checked.
+///
 /// ```no_run
 /// use imxrt_dma::{channel::Channel, Transfer};
 ///
@@ -112,9 +356,31 @@ pub(crate) const NO_WAKER: SharedWaker = Mutex::new(RefCell::new(None));
 /// ```
 pub struct Transfer<'a> {
     channel: &'a Channel,
+    id: u32,
+    /// Set once the transfer enables the channel
+    started: bool,
+    /// The memory that the transfer may touch, if known
+    #[cfg(debug_assertions)]
+    bounds: Option<Bounds>,
     _pinned: PhantomPinned,
 }
 
+/// The source and destination memory of a transfer
+///
+/// Each range includes its end, since an address may point one past
+/// its buffer before the last address adjustment.
+#[cfg(debug_assertions)]
+struct Bounds {
+    source: Range<usize>,
+    destination: Range<usize>,
+}
+
+/// Returns the addresses of `len` elements starting at `address`
+pub(crate) fn span<E>(address: *const E, len: usize) -> Range<usize> {
+    let start = address as usize;
+    start..start.saturating_add(core::mem::size_of::<E>().saturating_mul(len))
+}
+
 impl<'a> Transfer<'a> {
     /// Create a new `Transfer` that performs the DMA transfer described by `channel`
     ///
@@ -125,51 +391,223 @@ impl<'a> Transfer<'a> {
     pub unsafe fn new(channel: &'a Channel) -> Self {
         Transfer {
             channel,
+            id: next_transfer_id(),
+            started: false,
+            #[cfg(debug_assertions)]
+            bounds: None,
             _pinned: PhantomPinned,
         }
     }
+
+    /// Limit the transfer's addresses to `source` and `destination`
+    ///
+    /// Only checked in debug builds. See the "Bounds checks" section.
+    #[cfg_attr(not(debug_assertions), allow(unused_mut, unused_variables))]
+    pub(crate) fn bounded(mut self, source: Range<usize>, destination: Range<usize>) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            self.bounds = Some(Bounds {
+                source,
+                destination,
+            });
+        }
+        self
+    }
+
+    /// Returns this transfer's ID
+    ///
+    /// Every transfer gets a new ID when it's created, even across DMA drivers.
+    /// IDs increase, starting at one, and they're never zero; after 2^32 - 1
+    /// transfers, they wrap around. The ID appears in trace events, and in the
+    /// transfer's [errors](Error::transfer_id). Use it to correlate log messages
+    /// with a specific transfer.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
 }
 
 impl Future for Transfer<'_> {
     type Output = Result<(), Error>;
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.poll"
+    )]
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        interrupt::free(|cs| {
-            let waker = self.channel.waker.borrow(cs);
-            let mut waker = waker.borrow_mut();
-            *waker = Some(cx.waker().clone());
-        });
+        // Safety: we don't move anything out of the transfer.
+        let this = unsafe { self.get_unchecked_mut() };
+        this.channel.register_waker(cx.waker());
+        #[cfg(debug_assertions)]
+        this.check_bounds();
 
         loop {
             // This driver is only expecting to catch synchronous errors
             // (those that manifest once we enable the transfer). If there
             // is a misconfiguration that only the hardware detects, we expect
             // to see it as soon as we loop back around after the enable.
-            if self.channel.is_error() {
-                let es = self.channel.error_status();
-                self.channel.clear_error();
-                return Poll::Ready(Err(es));
-            } else if self.channel.is_complete() {
-                self.channel.clear_complete();
-                return Poll::Ready(Ok(()));
-            } else if self.channel.is_enabled() {
-                return Poll::Pending;
-            } else {
-                atomic::fence(atomic::Ordering::SeqCst);
-                unsafe { self.channel.enable() };
+            match this.channel.transfer_status() {
+                TransferStatus::Error => {
+                    let es = this
+                        .channel
+                        .latched_error_status()
+                        .with_transfer_id(Some(this.id));
+                    this.channel.clear_error();
+                    #[cfg(feature = "telemetry")]
+                    if !es.is_cancelled() {
+                        crate::telemetry::record_error(this.channel.telemetry);
+                    }
+                    trace::event!(
+                        "DMA{}: transfer {} error {:#X}",
+                        this.channel.channel(),
+                        this.id,
+                        es.raw()
+                    );
+                    return Poll::Ready(Err(es));
+                }
+                TransferStatus::Complete => return Poll::Ready(this.complete()),
+                TransferStatus::Enabled => return Poll::Pending,
+                TransferStatus::Disabled if this.started => {
+                    // Someone disabled the channel. A disable-on-completion
+                    // may have raced with the completion check.
+                    if this.channel.is_complete() {
+                        return Poll::Ready(this.complete());
+                    }
+                    trace::event!(
+                        "DMA{}: transfer {} cancelled",
+                        this.channel.channel(),
+                        this.id
+                    );
+                    return Poll::Ready(Err(this
+                        .channel
+                        .cancelled_error()
+                        .with_transfer_id(Some(this.id))));
+                }
+                TransferStatus::Disabled => {
+                    this.channel.set_transfer_id(this.id);
+                    trace::event!(
+                        "DMA{}: transfer {} enabled",
+                        this.channel.channel(),
+                        this.id
+                    );
+                    atomic::fence(atomic::Ordering::SeqCst);
+                    unsafe { this.channel.enable() };
+                    this.started = true;
+                }
             }
         }
     }
 }
 
+impl Transfer<'_> {
+    /// Panic if the channel's addresses left the transfer's bounds
+    #[cfg(debug_assertions)]
+    fn check_bounds(&self) {
+        let Some(bounds) = &self.bounds else {
+            return;
+        };
+        for (name, address, range) in [
+            (
+                "source",
+                self.channel.source_address() as u32,
+                &bounds.source,
+            ),
+            (
+                "destination",
+                self.channel.destination_address() as u32,
+                &bounds.destination,
+            ),
+        ] {
+            // The DMA controller's addresses are 32 bits.
+            let start = range.start as u32;
+            let len = (range.end - range.start) as u32;
+            assert!(
+                address.wrapping_sub(start) <= len,
+                "DMA channel {}: transfer {} {name} address {address:#010X} is outside of \
+                 {start:#010X}..={:#010X}",
+                self.channel.channel(),
+                self.id,
+                start.wrapping_add(len)
+            );
+        }
+    }
+
+    /// Finish a transfer that the hardware completed
+    #[cfg_attr(
+        all(feature = "hot-section", target_os = "none"),
+        link_section = ".imxrt_dma.hot.complete"
+    )]
+    fn complete(&self) -> Result<(), Error> {
+        // Order the DONE read before the caller's reads of the transferred memory.
+        atomic::fence(atomic::Ordering::Acquire);
+        self.channel.clear_complete();
+        #[cfg(feature = "telemetry")]
+        crate::telemetry::record_complete(
+            self.channel.telemetry,
+            u64::from(self.channel.minor_loop_bytes())
+                * u64::from(self.channel.beginning_transfer_iterations()),
+        );
+        trace::event!(
+            "DMA{}: transfer {} complete",
+            self.channel.channel(),
+            self.id
+        );
+        Ok(())
+    }
+}
+
 impl Drop for Transfer<'_> {
     fn drop(&mut self) {
+        if self.started && self.channel.is_enabled() {
+            trace::event!(
+                "DMA{}: transfer {} cancelled by drop",
+                self.channel.channel(),
+                self.id
+            );
+        }
         self.channel.disable();
         self.channel.clear_complete();
         self.channel.clear_error();
-        interrupt::free(|cs| {
-            let waker = self.channel.waker.borrow(cs);
-            let mut waker = waker.borrow_mut();
-            *waker = None;
-        });
+        self.channel.clear_waker();
+        #[cfg(feature = "clock-gating")]
+        self.channel.release_clock();
+    }
+}
+
+/// A future that resolves when a peripheral requests service from a DMA channel
+///
+/// Use [`wait_hardware_request`](crate::Dma::wait_hardware_request) to create this future.
+pub struct HardwareRequest {
+    channel: Channel,
+}
+
+/// A future that resolves when all DMA channels are idle
+///
+/// Use [`wait_idle`](crate::Dma::wait_idle) to create this future.
+pub struct Idle<const CHANNELS: usize, const WAKERS: usize = CHANNELS, const WATCHERS: usize = 1> {
+    dma: &'static super::Dma<CHANNELS, WAKERS, WATCHERS>,
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize> Future
+    for Idle<CHANNELS, WAKERS, WATCHERS>
+{
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.dma.is_any_active() {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    }
+}
+
+impl Future for HardwareRequest {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.channel.is_hardware_signaling() {
+            Poll::Ready(())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
     }
 }
@@ -22,6 +22,10 @@
 //! `DMA` and `DMAMUX` constants for the addresses. You're always responsible
 //! for configuring the number of DMA channels.
 //!
+//! Alternatively, enable the feature for your chip family, and use the
+//! preconfigured driver from the `chips` module. See the [features](#features)
+//! for more information.
+//!
 //! With those three parameters, assign a `Dma` to a static. Then, use that
 //! object to create DMA [`Channel`](crate::channel::Channel)s.
 //!
@@ -37,6 +41,11 @@
 //! let mut channel = unsafe { DMA.channel(7) };
 //! ```
 //!
+//! To have the driver track which channels you've allocated, use
+//! [`try_channel`](Dma::try_channel). It returns `None` if the channel
+//! is already allocated. To allocate every channel at once, use
+//! [`channels`](Dma::channels).
+//!
 //! Once you have a channel, you can use the higher-level DMA APIs, like
 //!
 //! - [`memcpy`](crate::memcpy::memcpy) for memory copies.
@@ -50,7 +59,84 @@
 //! through various [`peripheral`] traits.
 //!
 //! For a lower-level API, use the [`channel`] objects and helper
-//! functions.
+//! functions. If you're using circular transfers, see [`ring`] for a
+//! buffer that always meets the hardware's size and alignment requirements.
+//! For linear buffers that don't share cache lines with other data, see
+//! [`buffer`].
+//! To share a channel by reference, like in an RTIC shared resource, see
+//! [`exclusive`].
+//! To restart a channel from an interrupt handler with a transfer that you
+//! prepared ahead of time, see [`armed`].
+//!
+//! # Hosted targets
+//!
+//! The driver also builds for hosted targets, like `x86_64-unknown-linux-gnu`.
+//! On a hosted target, critical sections are a global, reentrant lock instead
+//! of masked interrupts, and cache maintenance does nothing. Use this to unit
+//! test DMA-using code on your development machine. Enable the `"sim"` feature
+//! to simulate the DMA controller.
+//!
+//! # Panic-free use
+//!
+//! Methods that panic on invalid inputs have fallible alternatives that return
+//! a [`ConfigurationError`] instead. If your firmware can't panic, use these:
+//!
+//! - [`Dma::checked_channel`] and [`Dma::try_channel`] instead of [`Dma::channel`].
+//! - [`Channel::checked_steal`](channel::Channel::checked_steal) instead of
+//!   [`Channel::steal`](channel::Channel::steal).
+//! - The `try_` variants of channel and controller setters, like
+//!   [`Channel::try_set_channel_configuration`](channel::Channel::try_set_channel_configuration),
+//!   [`Channel::try_set_transfer_iterations`](channel::Channel::try_set_transfer_iterations),
+//!   [`Dma::try_configure`], and [`Dma::try_set_priorities`].
+//! - [`channel::try_set_source_circular_buffer`] and
+//!   [`channel::try_set_destination_circular_buffer`].
+//! - [`memcpy::try_memcpy`], [`peripheral::try_read`], [`peripheral::try_write`],
+//!   and [`peripheral::try_full_duplex`], which check buffer lengths.
+//!
+//! # Hot paths in RAM
+//!
+//! If your program executes in place from flash, flash wait states slow down
+//! the DMA completion path. The `"hot-section"` feature places that path in
+//! its own linker sections, named `.imxrt_dma.hot.*`: [`on_interrupt`](Dma::on_interrupt),
+//! the [`Transfer`] poll, and the channel status checks that they use. Your
+//! linker script decides where those sections go. For example, to run them
+//! from ITCM, add the sections to the output section that's copied to ITCM:
+//!
+//! ```text
+//! .itcm : {
+//!     /* ... */
+//!     *(.imxrt_dma.hot .imxrt_dma.hot.*)
+//! } > ITCM AT> FLASH
+//! ```
+//!
+//! Without the feature, `on_interrupt` is inlined into your interrupt handler.
+//! With the feature, it's never inlined, so that it stays in its section. Other
+//! functions, like a [clock gate](Dma::set_clock_gate) or an [error handler](Dma::set_error_handler),
+//! stay wherever you put them. The sections only apply to embedded targets.
+//!
+//! # Features
+//!
+//! | Feature      | Description                                              |
+//! | ------------ | -------------------------------------------------------- |
+//! | `"heapless"` | Adapters for `heapless` SPSC queues. See the `spsc` module. |
+//! | `"cache"`    | Data cache maintenance for [`memcpy`], [`peripheral`], and [`ring`] buffers. |
+//! | `"clock-gating"` | Gate the DMA controller's clock while the driver is idle. See `Dma::set_automatic_clock_gating`. |
+//! | `"defmt"`    | `defmt::Format` implementations for errors, configurations, and diagnostic types. |
+//! | `"embedded-graphics"` | `Element` implementations for `Rgb565` and `RawU16` pixels. |
+//! | `"ufmt"`     | `ufmt` formatting for [`Error`] and its related types.    |
+//! | `"embassy-sync"` | Adapters between DMA streams and `embassy-sync` pipes and channels. See the `bridge` module. |
+//! | `"embassy"`  | `InterruptHandler` types for embassy's `bind_interrupts!`. See the `chips` module. |
+//! | `"trace-log"`, `"trace-defmt"` | Debug events when transfers start, complete, fail, or cancel, using `log` or `defmt`. |
+//! | `"exec"`     | `block_on` and `wfi_on` for running transfer futures without an executor. See the `exec` module. |
+//! | `"hot-section"` | Linker sections for the DMA completion path, so you can place it in RAM. See "Hot paths in RAM." |
+//! | `"command-log"` | A log of the driver's recent register writes. See the `command_log` module. |
+//! | `"profile"`  | Setup latency and throughput measurements using the DWT cycle counter. See the `profile` module. |
+//! | `"telemetry"` | Per-channel counts of bytes moved, completed transfers, and errors. See `Dma::telemetry`. |
+//! | `"sim"`      | A host-side DMA simulator. See the `sim` module. Implies `"std"`. |
+//! | `"std"`      | Links the standard library.                              |
+//! | `"test-util"` | Fake peripherals for the simulator. See the `mock` module. Implies `"sim"`. |
+//! | `"edma34"`   | Support for eDMA3 and eDMA4 controllers, like those on the i.MX RT 1180. See `Dma::new_edma3` and `Dma::new_edma4`. |
+//! | `"imxrt1010"`, `"imxrt1020"`, `"imxrt1060"`, `"imxrt1170"` | Preconfigured drivers for each chip family. See the `chips` module. |
 //!
 //! ### License
 //!
@@ -67,18 +153,69 @@
 
 #![no_std]
 
+#[cfg(any(feature = "std", not(target_os = "none")))]
+extern crate std;
+
+pub mod armed;
+pub mod bound;
+#[cfg(feature = "embassy-sync")]
+pub mod bridge;
+pub mod buffer;
+#[cfg(feature = "cache")]
+mod cache;
 pub mod channel;
+pub mod chips;
+mod clock;
+#[cfg(feature = "command-log")]
+pub mod command_log;
+mod controller;
+mod critical;
 mod element;
 mod error;
+pub mod exclusive;
+#[cfg(feature = "exec")]
+pub mod exec;
+mod history;
 mod interrupt;
 pub mod memcpy;
+mod memory;
+#[cfg(feature = "test-util")]
+pub mod mock;
 pub mod peripheral;
+pub mod pool;
+#[cfg(feature = "profile")]
+pub mod profile;
+mod progress;
 mod ral;
+mod registers;
+pub mod ring;
+pub mod scatter_gather;
+#[cfg(feature = "sim")]
+pub mod sim;
+#[cfg(feature = "heapless")]
+pub mod spsc;
+#[cfg(feature = "telemetry")]
+mod telemetry;
+mod trace;
+mod watch;
+pub mod watchdog;
 
+pub use controller::{Arbitration, Capabilities, DebugDump, DmaConfig, DmaState, Profile};
 pub use element::Element;
-pub use error::Error;
-pub use interrupt::Transfer;
-pub use ral::tcd::BandwidthControl;
+pub use error::{ConfigurationError, Error, ErrorKind};
+pub use history::{ErrorHistory, ErrorRecord, HISTORY_LEN};
+pub use interrupt::{HardwareRequest, Idle, Transfer};
+pub use progress::Progress;
+pub use ral::tcd::{BandwidthControl, Snapshot as TcdSnapshot};
+pub use registers::{DmaRegisters, DmamuxRegisters};
+#[cfg(feature = "telemetry")]
+pub use telemetry::{ChannelTelemetry, Telemetry};
+pub use watch::Completion;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::element::private::Sealed;
+}
 
 /// A DMA result
 pub type Result<T> = core::result::Result<T, Error>;
@@ -90,16 +227,154 @@ pub type Result<T> = core::result::Result<T, Error>;
 ///
 /// `Dma` allocates [`Channel`](channel::Channel)s. `Channel` provides
 /// the interface for scheduling transfers.
-pub struct Dma<const CHANNELS: usize> {
-    controller: ral::Static<ral::dma::RegisterBlock>,
-    multiplexer: ral::Static<ral::dmamux::RegisterBlock>,
-    wakers: [SharedWaker; CHANNELS],
+///
+/// `WAKERS` is the number of channel wakers, used by [`on_interrupt`](Self::on_interrupt)
+/// to wake transfer futures. It's either `CHANNELS` (the default), or zero. If your
+/// system only polls transfers, use [`PolledDma`] to save the RAM used by wakers.
+///
+/// Each waker is two words. On a 32-bit MCU, the wakers of a `Dma<32>` use 256
+/// bytes. Every driver latches errors and transfer IDs for each channel, whatever
+/// its `WAKERS`.
+///
+/// `WATCHERS` is the number of tasks that can wait on each channel with
+/// [`wait_complete`](Self::wait_complete). It's one by default. Each watcher is
+/// three words, plus the critical section's borrow flag. Use zero if no task
+/// waits with `wait_complete`.
+pub struct Dma<const CHANNELS: usize, const WAKERS: usize = CHANNELS, const WATCHERS: usize = 1> {
+    controller: Controller,
+    wakers: [SharedWaker; WAKERS],
+    /// Error status latched by `on_interrupt`, one per channel.
+    errors: [AtomicU32; CHANNELS],
+    /// The ID of each channel's most recent transfer, one per channel.
+    transfer_ids: [AtomicU32; CHANNELS],
+    /// Bitmask of channels allocated by `try_channel`, 32 channels per word.
+    allocated: [AtomicU32; 2],
+    error_handler: SharedErrorHandler,
+    error_history: SharedErrorHistory,
+    progress_handler: progress::SharedProgressHandler,
+    clock: Clock,
+    /// Activity counters, one per channel.
+    #[cfg(feature = "telemetry")]
+    telemetry: [telemetry::SharedTelemetry; CHANNELS],
+    /// The memory that the DMA controller can reach.
+    memory_map: memory::SharedMemoryMap,
+    /// Tasks waiting on channel events, `WATCHERS` per channel.
+    watchers: [[watch::SharedWatcher; WATCHERS]; CHANNELS],
+}
+
+/// The multiplexer used by an eDMA controller
+#[derive(Clone, Copy)]
+enum Multiplexer {
+    /// The i.MX RT DMAMUX
+    Dmamux(ral::Static<ral::dmamux::RegisterBlock>),
+    /// A user-provided multiplexer
+    Custom(&'static dyn channel::Multiplexer),
+}
+
+/// The DMA controller registers
+#[derive(Clone, Copy)]
+enum Controller {
+    /// An eDMA controller, with a separate multiplexer
+    EDma {
+        controller: ral::Static<ral::dma::RegisterBlock>,
+        multiplexer: Multiplexer,
+    },
+    /// An eDMA3 controller
+    #[cfg(feature = "edma34")]
+    EDma3(ral::Static<ral::edma34::RegisterBlock>),
+    /// An eDMA4 controller
+    #[cfg(feature = "edma34")]
+    EDma4(ral::Static<ral::edma34::RegisterBlock>),
 }
 
 // Safety: OK to allocate a DMA driver in a static context.
-unsafe impl<const CHANNELS: usize> Sync for Dma<CHANNELS> {}
+unsafe impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize> Sync
+    for Dma<CHANNELS, WAKERS, WATCHERS>
+{
+}
+
+/// A DMA driver without channel wakers
+///
+/// A `PolledDma` uses less RAM than a [`Dma`], since it doesn't store a waker for
+/// each channel. Transfer futures still work, but [`on_interrupt`](Dma::on_interrupt)
+/// can't wake them; poll the futures until they complete.
+///
+/// ```
+/// use imxrt_dma::PolledDma;
+/// # const DMA_PTR: *const () = core::ptr::null() as _;
+/// # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+///
+/// static DMA: PolledDma<32> = unsafe { PolledDma::new(DMA_PTR, DMAMUX_PTR) };
+/// let channel = DMA.try_channel(7).unwrap();
+/// ```
+pub type PolledDma<const CHANNELS: usize, const WATCHERS: usize = 1> = Dma<CHANNELS, 0, WATCHERS>;
+
+/// A handle to a DMA driver that isn't generic over the channel count
+///
+/// Use [`Dma::erase`] to create a `DmaRef`. A HAL can store and pass around a
+/// `DmaRef` without adding a `CHANNELS` parameter to its own types. `DmaRef`
+/// allocates channels and handles interrupts just like the `Dma` that created it;
+/// the two share channel allocations, wakers, the error handler, and the error history.
+///
+/// ```
+/// use imxrt_dma::{Dma, DmaRef};
+/// # const DMA_PTR: *const () = core::ptr::null() as _;
+/// # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+///
+/// static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+///
+/// struct Hal {
+///     dma: DmaRef,
+/// }
+///
+/// let hal = Hal { dma: DMA.erase() };
+/// assert_eq!(hal.dma.channel_count(), 32);
+/// let channel = hal.dma.try_channel(7).unwrap();
+/// assert!(DMA.try_channel(7).is_none());
+/// ```
+#[derive(Clone, Copy)]
+pub struct DmaRef {
+    controller: Controller,
+    channels: usize,
+    /// Empty if the driver doesn't have wakers.
+    wakers: &'static [SharedWaker],
+    errors: &'static [AtomicU32],
+    transfer_ids: &'static [AtomicU32],
+    allocated: &'static [AtomicU32; 2],
+    error_handler: &'static SharedErrorHandler,
+    error_history: &'static SharedErrorHistory,
+    progress_handler: &'static progress::SharedProgressHandler,
+    clock: &'static Clock,
+    #[cfg(feature = "telemetry")]
+    telemetry: &'static [telemetry::SharedTelemetry],
+    memory_map: &'static memory::SharedMemoryMap,
+    /// `WATCHERS` per channel.
+    watchers: &'static [watch::SharedWatcher],
+}
+
+// Safety: refers to a DMA driver, which is Sync.
+unsafe impl Send for DmaRef {}
+// Safety: refers to a DMA driver, which is Sync.
+unsafe impl Sync for DmaRef {}
+
+impl DmaRef {
+    /// Returns the number of channels supported by the DMA controller
+    pub const fn channel_count(&self) -> usize {
+        self.channels
+    }
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    From<&'static Dma<CHANNELS, WAKERS, WATCHERS>> for DmaRef
+{
+    fn from(dma: &'static Dma<CHANNELS, WAKERS, WATCHERS>) -> Self {
+        dma.erase()
+    }
+}
 
-impl<const CHANNELS: usize> Dma<CHANNELS> {
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    Dma<CHANNELS, WAKERS, WATCHERS>
+{
     /// Create the DMA driver.
     ///
     /// Note that this can evaluate at compile time. Consider using this to
@@ -107,7 +382,14 @@ impl<const CHANNELS: usize> Dma<CHANNELS> {
     /// allocate DMA channels.
     ///
     /// `CHANNELS` specifies the total number of channels supported by the DMA
-    /// controller. It's referenced when allocating channels.
+    /// controller. It's referenced when allocating channels. A driver supports
+    /// at most 64 channels; a larger `CHANNELS` fails to compile.
+    ///
+    /// ```compile_fail
+    /// use imxrt_dma::Dma;
+    ///
+    /// static DMA: Dma<65> = unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    /// ```
     ///
     /// # Safety
     ///
@@ -118,14 +400,277 @@ impl<const CHANNELS: usize> Dma<CHANNELS> {
     ///
     /// An incorrect `CHANNELS` value prevents proper bounds checking when
     /// allocating channels. This may result in DMA channels that point to
-    /// invalid memory.
+    /// invalid memory. With a chip feature, use the chip module's `dma` function;
+    /// an incorrect `CHANNELS` value fails to compile. See the [`chips`] module.
     pub const unsafe fn new(controller: *const (), multiplexer: *const ()) -> Self {
-        Self {
+        Self::with_controller(Controller::EDma {
+            controller: ral::Static(controller.cast()),
+            multiplexer: Multiplexer::Dmamux(ral::Static(multiplexer.cast())),
+        })
+    }
+
+    /// Create the DMA driver from register block adapters.
+    ///
+    /// This is like [`new`](Self::new), but it takes the register addresses from
+    /// `controller` and `multiplexer`. A HAL can implement [`DmaRegisters`] and
+    /// [`DmamuxRegisters`] for its own peripheral types, whatever `imxrt-ral`
+    /// release they use.
+    ///
+    /// ```
+    /// use imxrt_dma::{Dma, DmaRegisters, DmamuxRegisters};
+    ///
+    /// // A HAL type that owns the DMA peripherals.
+    /// struct DmaPeripherals {
+    ///     # dma: *const (), dmamux: *const (),
+    ///     // dma: ral::dma::DMA,
+    ///     // dmamux: ral::dmamux::DMAMUX,
+    /// }
+    ///
+    /// impl DmaRegisters for DmaPeripherals {
+    ///     fn dma_registers(&self) -> *const () {
+    ///         # self.dma
+    ///         // &*self.dma as *const _ as *const ()
+    ///     }
+    /// }
+    ///
+    /// impl DmamuxRegisters for DmaPeripherals {
+    ///     fn dmamux_registers(&self) -> *const () {
+    ///         # self.dmamux
+    ///         // &*self.dmamux as *const _ as *const ()
+    ///     }
+    /// }
+    ///
+    /// # let peripherals = DmaPeripherals { dma: core::ptr::null(), dmamux: core::ptr::null() };
+    /// // Safety: the peripherals point to the DMA and DMAMUX, and the
+    /// // chip has 32 DMA channels.
+    /// let dma: Dma<32> = unsafe { Dma::from_registers(&peripherals, &peripherals) };
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// Caller must make sure that the adapters return pointers to the DMA controller
+    /// and DMA multiplexer register blocks, and that `CHANNELS` is correct. See
+    /// [`new`](Self::new) for more information.
+    pub unsafe fn from_registers<C, M>(controller: &C, multiplexer: &M) -> Self
+    where
+        C: DmaRegisters + ?Sized,
+        M: DmamuxRegisters + ?Sized,
+    {
+        // Safety: caller upholds the requirements.
+        unsafe { Self::new(controller.dma_registers(), multiplexer.dmamux_registers()) }
+    }
+
+    /// Create a DMA driver that uses a custom multiplexer.
+    ///
+    /// Use this if your DMA controller's request multiplexer doesn't match the
+    /// i.MX RT DMAMUX, or if there's no multiplexer. The driver calls `multiplexer`
+    /// whenever a channel's configuration changes; see
+    /// [`Multiplexer`](channel::Multiplexer) for more information.
+    ///
+    /// # Safety
+    ///
+    /// Caller must make sure that `controller` is a pointer to the start of the
+    /// DMA controller register block, and that `CHANNELS` is correct. See
+    /// [`new`](Self::new) for more information.
+    pub const unsafe fn with_multiplexer(
+        controller: *const (),
+        multiplexer: &'static dyn channel::Multiplexer,
+    ) -> Self {
+        Self::with_controller(Controller::EDma {
             controller: ral::Static(controller.cast()),
-            multiplexer: ral::Static(multiplexer.cast()),
-            wakers: [NO_WAKER; CHANNELS],
+            multiplexer: Multiplexer::Custom(multiplexer),
+        })
+    }
+
+    /// Create a DMA driver for an eDMA3 controller.
+    ///
+    /// eDMA3 controllers, like DMA3 on the i.MX RT 1180, integrate the request
+    /// multiplexer. `controller` is a pointer to the start of the controller's
+    /// management page.
+    ///
+    /// # Safety
+    ///
+    /// Caller must make sure that `controller` is a pointer to the start of an
+    /// eDMA3 register block, and that `CHANNELS` is correct for the controller.
+    /// See [`new`](Self::new) for more information.
+    #[cfg(feature = "edma34")]
+    pub const unsafe fn new_edma3(controller: *const ()) -> Self {
+        Self::with_controller(Controller::EDma3(ral::Static(controller.cast())))
+    }
+
+    /// Create a DMA driver for an eDMA4 controller.
+    ///
+    /// eDMA4 controllers, like DMA4 on the i.MX RT 1180, integrate the request
+    /// multiplexer, and support up to 64 channels. `controller` is a pointer to
+    /// the start of the controller's management page.
+    ///
+    /// # Safety
+    ///
+    /// Caller must make sure that `controller` is a pointer to the start of an
+    /// eDMA4 register block, and that `CHANNELS` is correct for the controller.
+    /// See [`new`](Self::new) for more information.
+    #[cfg(feature = "edma34")]
+    pub const unsafe fn new_edma4(controller: *const ()) -> Self {
+        Self::with_controller(Controller::EDma4(ral::Static(controller.cast())))
+    }
+
+    /// Use `gate` to enable the DMA controller's clock
+    ///
+    /// The driver calls `gate(true)` before it creates its first channel, or when
+    /// you call [`enable_clock`](Self::enable_clock). It calls `gate` only once.
+    /// Implement `gate` with your chip's clock control module; for instance, on
+    /// an i.MX RT 1060, set the DMA clock gate in CCM CCGR5. Set the clock gate
+    /// before you use the driver.
+    ///
+    /// If you don't provide a clock gate, you're responsible for enabling the
+    /// DMA controller's clock before using the driver.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::Dma;
+    /// # const DMA_PTR: *const () = core::ptr::null() as _;
+    /// # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+    ///
+    /// fn dma_clock_gate(enable: bool) {
+    ///     // Access your CCM, and set the DMA clock gate...
+    /// }
+    ///
+    /// static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+    ///
+    /// DMA.set_clock_gate(dma_clock_gate);
+    /// let channel = DMA.try_channel(7); // Calls dma_clock_gate(true)
+    /// ```
+    pub fn set_clock_gate(&self, gate: fn(bool)) {
+        self.clock.set_gate(gate);
+    }
+
+    /// Enable the DMA controller's clock using the clock gate
+    ///
+    /// Call this before you configure the controller, if you haven't yet
+    /// created a channel. Does nothing if there's no clock gate, or if the
+    /// clock is already enabled. See [`set_clock_gate`](Self::set_clock_gate).
+    ///
+    /// With the `"clock-gating"` feature, the clock stays enabled until you
+    /// call `release_clock`, even if the driver gates the clock when it's idle.
+    pub fn enable_clock(&self) {
+        #[cfg(feature = "clock-gating")]
+        self.clock.hold();
+        #[cfg(not(feature = "clock-gating"))]
+        self.clock.enable();
+    }
+
+    /// Stop holding the clock enabled after [`enable_clock`](Self::enable_clock)
+    ///
+    /// With automatic clock gating, this gates the clock if no channel is using
+    /// the controller. Otherwise, the clock stays enabled. This requires the
+    /// `"clock-gating"` feature.
+    #[cfg(feature = "clock-gating")]
+    pub fn release_clock(&self) {
+        self.clock.release_hold();
+    }
+
+    /// Gate the DMA controller's clock while the driver is idle
+    ///
+    /// When `automatic` is `true`, the driver tracks the channels that are using
+    /// the controller. A channel starts using the controller when a transfer
+    /// programs it, or when it's enabled or started, and stops when it's
+    /// disabled. When a transfer future is dropped, and no channel is using the
+    /// controller, the driver calls the [clock gate](Self::set_clock_gate) with
+    /// `false`. The next transfer calls the clock gate with `true`, before it
+    /// touches the hardware.
+    ///
+    /// A channel that stays enabled, like a channel that's servicing a circular
+    /// buffer, keeps the clock enabled. Queries of a channel's status don't
+    /// enable the clock, and neither do controller-wide functions, like
+    /// [`configure`](Self::configure) and [`set_priorities`](Self::set_priorities).
+    /// Before you use them, or before you program a channel yourself, call
+    /// [`enable_clock`](Self::enable_clock). The clock stays enabled until you
+    /// [`release_clock`](Self::release_clock). [`is_any_active`](Self::is_any_active)
+    /// knows that every channel is idle while the clock is gated. Turning off
+    /// automatic gating enables the clock.
+    ///
+    /// Does nothing without a clock gate. This requires the `"clock-gating"` feature.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::Dma;
+    /// # const DMA_PTR: *const () = core::ptr::null() as _;
+    /// # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+    ///
+    /// fn dma_clock_gate(enable: bool) {
+    ///     // Access your CCM, and set the DMA clock gate...
+    /// }
+    ///
+    /// static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+    ///
+    /// DMA.set_clock_gate(dma_clock_gate);
+    /// DMA.set_automatic_clock_gating(true);
+    /// ```
+    #[cfg(feature = "clock-gating")]
+    pub fn set_automatic_clock_gating(&self, automatic: bool) {
+        self.clock.set_automatic(automatic);
+    }
+
+    /// Returns `true` if the driver gates the DMA controller's clock while it's idle
+    ///
+    /// See [`set_automatic_clock_gating`](Self::set_automatic_clock_gating).
+    #[cfg(feature = "clock-gating")]
+    pub fn is_automatic_clock_gating(&self) -> bool {
+        self.clock.is_automatic()
+    }
+
+    /// Returns a [`DmaRef`] handle to this driver
+    pub const fn erase(&'static self) -> DmaRef {
+        DmaRef {
+            controller: self.controller,
+            channels: CHANNELS,
+            wakers: &self.wakers,
+            errors: &self.errors,
+            transfer_ids: &self.transfer_ids,
+            allocated: &self.allocated,
+            error_handler: &self.error_handler,
+            error_history: &self.error_history,
+            progress_handler: &self.progress_handler,
+            clock: &self.clock,
+            #[cfg(feature = "telemetry")]
+            telemetry: &self.telemetry,
+            memory_map: &self.memory_map,
+            watchers: self.watchers.as_flattened(),
+        }
+    }
+
+    const WAKERS_MATCH_CHANNELS: () = assert!(
+        WAKERS == CHANNELS || WAKERS == 0,
+        "WAKERS must be either CHANNELS or zero"
+    );
+
+    /// The allocation bitmask tracks at most 64 channels.
+    const CHANNELS_FIT_ALLOCATION: () =
+        assert!(CHANNELS <= 64, "A DMA driver supports at most 64 channels");
+
+    const fn with_controller(controller: Controller) -> Self {
+        let () = Self::WAKERS_MATCH_CHANNELS;
+        let () = Self::CHANNELS_FIT_ALLOCATION;
+        Self {
+            controller,
+            wakers: [NO_WAKER; WAKERS],
+            errors: [NO_ERROR; CHANNELS],
+            transfer_ids: [NO_TRANSFER_ID; CHANNELS],
+            allocated: [AtomicU32::new(0), AtomicU32::new(0)],
+            error_handler: NO_ERROR_HANDLER,
+            error_history: Mutex::new(RefCell::new(ErrorHistory::new())),
+            progress_handler: progress::NO_PROGRESS_HANDLER,
+            clock: Clock::new(),
+            #[cfg(feature = "telemetry")]
+            telemetry: [telemetry::NO_TELEMETRY; CHANNELS],
+            memory_map: memory::NO_MEMORY_MAP,
+            watchers: [const { [watch::NO_WATCHER; WATCHERS] }; CHANNELS],
         }
     }
 }
 
-use interrupt::{SharedWaker, NO_WAKER};
+use clock::Clock;
+use core::{cell::RefCell, sync::atomic::AtomicU32};
+use cortex_m::interrupt::Mutex;
+use history::SharedErrorHistory;
+use interrupt::{
+    SharedErrorHandler, SharedWaker, NO_ERROR, NO_ERROR_HANDLER, NO_TRANSFER_ID, NO_WAKER,
+};
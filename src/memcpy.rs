@@ -2,8 +2,8 @@
 
 use crate::{
     channel::{self, Channel},
-    interrupt::Transfer,
-    Element, Error,
+    interrupt::{self, Transfer},
+    ConfigurationError, Element, Error,
 };
 
 use core::{
@@ -19,6 +19,8 @@ use core::{
 pub struct Memcpy<'a, E> {
     transfer: Transfer<'a>,
     channel: &'a Channel,
+    #[cfg(feature = "cache")]
+    destination: crate::cache::Region,
     _elem: core::marker::PhantomData<(&'a E, &'a mut E)>,
 }
 
@@ -28,6 +30,10 @@ pub struct Memcpy<'a, E> {
 /// for enabling any interrupts, and calling [`on_interrupt`](crate::Dma::on_interrupt)
 /// if the interrupt fires. Otherwise, you may poll the transfer until completion.
 ///
+/// With the `"cache"` feature, `memcpy` cleans the data cache for `source` and
+/// `destination` before the transfer starts, and invalidates the cache for
+/// `destination` when the transfer completes.
+///
 /// # Example
 ///
 /// Transfer 5 `u32`s between a source and destination buffer. The transfer completes when
@@ -61,9 +67,16 @@ pub fn memcpy<'a, E: Element>(
     channel: &'a mut Channel,
 ) -> Memcpy<'a, E> {
     channel.disable();
+    channel.claim_clock();
 
     channel.set_disable_on_completion(true);
 
+    #[cfg(feature = "cache")]
+    {
+        crate::cache::Region::of(source).before_dma_read();
+        crate::cache::Region::of(destination).before_dma_write();
+    }
+
     // Safety: buffers borrowed by `memcpy`, and will be valid
     // while a transfer is in progress.
     unsafe {
@@ -79,6 +92,12 @@ pub fn memcpy<'a, E: Element>(
     // loop. TBD...
     channel.set_channel_configuration(channel::Configuration::Off);
 
+    crate::trace::start(
+        channel.channel(),
+        crate::trace::MEMORY_TO_MEMORY,
+        source.len().min(destination.len()),
+    );
+
     // Transfer all elements in a single major loop
     //
     // Safety: transferring the minimum number of bytes between buffers,
@@ -90,14 +109,111 @@ pub fn memcpy<'a, E: Element>(
         channel.set_transfer_iterations(1);
     }
 
+    let len = source.len().min(destination.len());
     Memcpy {
         // Safety: transfer is properly prepared
-        transfer: unsafe { Transfer::new(channel) },
+        transfer: unsafe { Transfer::new(channel) }.bounded(
+            interrupt::span(source.as_ptr(), len),
+            interrupt::span(destination.as_ptr(), len),
+        ),
         channel,
+        #[cfg(feature = "cache")]
+        destination: crate::cache::Region::of(destination),
         _elem: core::marker::PhantomData,
     }
 }
 
+/// Like [`memcpy()`], but checks the transfer before touching the hardware
+///
+/// Returns [`InvalidLength`](ConfigurationError::InvalidLength) if either buffer
+/// is empty, or if the copy is larger than the channel's minor loop byte count.
+/// Returns [`UnreachableMemory`](ConfigurationError::UnreachableMemory) if either
+/// buffer is outside of the driver's [memory map](crate::Dma::set_memory_map).
+/// If there's an error, the channel and buffers are unchanged.
+pub fn try_memcpy<'a, E: Element>(
+    source: &'a [E],
+    destination: &'a mut [E],
+    channel: &'a mut Channel,
+) -> Result<Memcpy<'a, E>, Error> {
+    channel.check_buffer(source)?;
+    channel.check_buffer(destination)?;
+    let len = source.len().min(destination.len());
+    let bytes = core::mem::size_of::<E>().checked_mul(len);
+    match bytes.and_then(|bytes| u32::try_from(bytes).ok()) {
+        Some(bytes) if bytes != 0 && bytes <= channel.minor_loop_bytes_limit() => {
+            Ok(memcpy(source, destination, channel))
+        }
+        _ => Err(Error::configuration(ConfigurationError::InvalidLength)),
+    }
+}
+
+/// The seed of the self-test pattern
+///
+/// Alternating bits, so that a stuck or shorted data line shows up in the
+/// copy. Each word rotates the seed, and mixes in its index, so that a
+/// misplaced word is also detected.
+const SELF_TEST_SEED: u32 = 0xA5C3_5A3C;
+
+/// Returns the self-test pattern word at `index`
+fn self_test_pattern(index: usize) -> u32 {
+    SELF_TEST_SEED.rotate_left(index as u32) ^ index as u32
+}
+
+impl Channel {
+    /// Check the DMA path with a memory-to-memory transfer
+    ///
+    /// `self_test` writes a pattern into the first half of `scratch`, and the
+    /// pattern's complement into the second half. It then [`memcpy`]s the
+    /// first half into the second half, and checks that the second half holds
+    /// the pattern. Run the self-test at power-on, before the application
+    /// trusts the channel with peripheral data. Place `scratch` in the memory
+    /// that your transfers will use.
+    ///
+    /// `self_test` reconfigures the channel just like `memcpy`; configure the
+    /// channel again before its next transfer. You're responsible for enabling
+    /// the channel's interrupt, or for polling the future.
+    ///
+    /// # Errors
+    ///
+    /// - [`InvalidLength`](ConfigurationError::InvalidLength) if `scratch` has
+    ///   fewer than two words, or if half of `scratch` is larger than the
+    ///   channel can copy at once.
+    /// - [`VerificationFailed`](ConfigurationError::VerificationFailed) if the
+    ///   transfer completes, but the copy doesn't match the pattern.
+    /// - any hardware error reported by the transfer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+    /// # async fn f() -> imxrt_dma::Result<()> {
+    /// let mut channel = DMA.try_channel(7).unwrap();
+    /// let mut scratch = [0u32; 32];
+    /// channel.self_test(&mut scratch).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn self_test(&mut self, scratch: &mut [u32]) -> Result<(), Error> {
+        let (source, destination) = scratch.split_at_mut(scratch.len() / 2);
+        let destination = &mut destination[..source.len()];
+        for (index, (src, dst)) in source.iter_mut().zip(destination.iter_mut()).enumerate() {
+            *src = self_test_pattern(index);
+            *dst = !self_test_pattern(index);
+        }
+
+        try_memcpy(source, destination, self)?.await?;
+
+        if destination
+            .iter()
+            .enumerate()
+            .all(|(index, &word)| word == self_test_pattern(index))
+        {
+            Ok(())
+        } else {
+            Err(Error::configuration(ConfigurationError::VerificationFailed))
+        }
+    }
+}
+
 impl<E> Future for Memcpy<'_, E> {
     type Output = Result<(), Error>;
 
@@ -108,6 +224,11 @@ impl<E> Future for Memcpy<'_, E> {
         if poll.is_pending() && !self.channel.is_active() {
             self.channel.start();
         }
+        #[cfg(feature = "cache")]
+        if let Poll::Ready(Ok(())) = poll {
+            // Safety: the transfer is complete.
+            unsafe { self.destination.after_dma_write() };
+        }
         poll
     }
 }
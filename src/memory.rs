@@ -0,0 +1,72 @@
+//! The memory that the DMA controller can reach
+
+use crate::{channel::Channel, critical, ConfigurationError, Dma, Error};
+use core::{cell::Cell, ops::Range};
+
+use cortex_m::interrupt::Mutex;
+
+pub(crate) type SharedMemoryMap = Mutex<Cell<&'static [Range<usize>]>>;
+#[allow(clippy::declare_interior_mutable_const)] // See NO_WAKER
+pub(crate) const NO_MEMORY_MAP: SharedMemoryMap = Mutex::new(Cell::new(&[]));
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    Dma<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Set the memory regions that the DMA controller can reach
+    ///
+    /// Each region is a range of addresses. Once there's a memory map, the
+    /// `try_` transfer functions, like [`try_memcpy`](crate::memcpy::try_memcpy)
+    /// and [`try_read`](crate::peripheral::try_read), check that each buffer
+    /// is within a single region. If it isn't, they return an error with
+    /// [`ConfigurationError::UnreachableMemory`]. Use this to catch a buffer
+    /// that's in memory that your DMA controller can't reach, like a stack in
+    /// a TCM that isn't connected to the DMA.
+    ///
+    /// An empty memory map, the default, allows all buffers. The memory map
+    /// applies to all of this driver's channels, including channels that you
+    /// already have. It doesn't apply to [stolen](crate::channel::Channel::steal)
+    /// channels.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::Dma;
+    /// static DMA: Dma<32> = // Handle to DMA driver.
+    /// # unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// static MEMORY_MAP: [core::ops::Range<usize>; 2] = [
+    ///     0x2020_0000..0x2030_0000, // OCRAM
+    ///     0x8000_0000..0x8200_0000, // SDRAM
+    /// ];
+    /// DMA.set_memory_map(&MEMORY_MAP);
+    /// ```
+    pub fn set_memory_map(&self, regions: &'static [Range<usize>]) {
+        critical::free(|cs| self.memory_map.borrow(cs).set(regions));
+    }
+
+    /// Returns the memory map
+    ///
+    /// See [`set_memory_map`](Self::set_memory_map) for more information.
+    pub fn memory_map(&self) -> &'static [Range<usize>] {
+        critical::free(|cs| self.memory_map.borrow(cs).get())
+    }
+}
+
+impl Channel {
+    /// Check that the DMA controller can reach all of `buffer`
+    pub(crate) fn check_buffer<E>(&self, buffer: &[E]) -> Result<(), Error> {
+        let Some(memory_map) = self.memory_map else {
+            return Ok(());
+        };
+        let regions = critical::free(|cs| memory_map.borrow(cs).get());
+        let start = buffer.as_ptr() as usize;
+        let end = start.saturating_add(core::mem::size_of_val(buffer));
+        if regions.is_empty()
+            || regions
+                .iter()
+                .any(|region| region.start <= start && end <= region.end)
+        {
+            Ok(())
+        } else {
+            Err(Error::configuration(ConfigurationError::UnreachableMemory))
+        }
+    }
+}
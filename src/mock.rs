@@ -0,0 +1,339 @@
+//! Fake peripherals for host-side tests
+//!
+//! [`MockSource`] and [`MockDestination`] implement [`Source`] and [`Destination`].
+//! They're also simulated [`Peripheral`]s, so you can [`attach`](crate::sim::Simulator::attach)
+//! them to a [`Simulator`](crate::sim::Simulator). Once attached, a mock requests
+//! DMA service through the simulated DMAMUX whenever its FIFO is ready, and it
+//! handles the simulator's reads and writes of its data register. Use the mocks
+//! to test a DMA integration, or code that uses this crate's transfer futures,
+//! without hardware.
+//!
+//! A mock is a handle to shared state. Clone the mock, attach one handle to the
+//! simulator, and use the other handle in your code and your assertions.
+//!
+//! ```
+//! use imxrt_dma::{mock::MockSource, peripheral, sim::Simulator};
+//!
+//! let sim = Simulator::<32>::new();
+//! let mut channel = sim.dma().try_channel(3).unwrap();
+//!
+//! let mut uart = MockSource::<u8>::new(9);
+//! sim.attach(uart.clone());
+//! uart.push(b"hello");
+//!
+//! let mut buffer = [0u8; 5];
+//! // Safety: the buffer outlives the transfer.
+//! unsafe { sim.map(&buffer) };
+//! sim.block_on(peripheral::read(&mut channel, &mut uart, &mut buffer)).unwrap();
+//!
+//! assert_eq!(&buffer, b"hello");
+//! assert!(!uart.is_enabled());
+//! ```
+//!
+//! This module requires the `"test-util"` feature.
+
+use crate::{
+    peripheral::{Destination, Source},
+    sim::Peripheral,
+    Element,
+};
+
+use core::cell::{Cell, RefCell};
+use std::{boxed::Box, collections::VecDeque, rc::Rc, vec::Vec};
+
+/// State shared by all mocks
+struct Mock<E> {
+    signal: u32,
+    /// Backs the data register, so that it has a stable, aligned address.
+    register: Box<u64>,
+    fifo: RefCell<VecDeque<E>>,
+    enabled: Cell<bool>,
+    /// The number of successful DMA accesses of the data register.
+    accesses: Cell<usize>,
+    /// The number of accesses that succeed before the mock signals a bus error.
+    fail_after: Cell<Option<usize>>,
+}
+
+impl<E: Element> Mock<E> {
+    const ALIGNED: () = assert!(core::mem::align_of::<E>() <= core::mem::align_of::<u64>());
+
+    fn new(signal: u32) -> Self {
+        let () = Self::ALIGNED;
+        Mock {
+            signal,
+            register: Box::new(0),
+            fifo: RefCell::new(VecDeque::new()),
+            enabled: Cell::new(false),
+            accesses: Cell::new(0),
+            fail_after: Cell::new(None),
+        }
+    }
+
+    fn address(&self) -> *const E {
+        (&*self.register as *const u64).cast()
+    }
+
+    /// Returns `true` if `address` and `len` describe a DMA access of the data register
+    ///
+    /// Returns `None` if the access is for some other address.
+    fn access(&self, address: u32, len: usize) -> Option<bool> {
+        if address != self.address() as usize as u32 {
+            return None;
+        }
+        let fail = self
+            .fail_after
+            .get()
+            .is_some_and(|after| self.accesses.get() >= after);
+        Some(!fail && len == core::mem::size_of::<E>())
+    }
+}
+
+/// A fake peripheral that supplies DMA data
+///
+/// The mock has a receive FIFO that you fill with [`push`](Self::push). While
+/// the mock is enabled, it requests service when its FIFO holds at least
+/// [watermark](Self::set_watermark) elements. Each DMA read of the data
+/// register pops one element.
+///
+/// A DMA read of an empty FIFO is a bus error. So is a read whose size doesn't
+/// match `E`, and any DMA write of the data register.
+pub struct MockSource<E> {
+    mock: Rc<Mock<E>>,
+    watermark: Rc<Cell<usize>>,
+}
+
+impl<E> Clone for MockSource<E> {
+    fn clone(&self) -> Self {
+        MockSource {
+            mock: Rc::clone(&self.mock),
+            watermark: Rc::clone(&self.watermark),
+        }
+    }
+}
+
+impl<E: Element> MockSource<E> {
+    /// Create a disabled mock that uses the DMA request `signal`
+    ///
+    /// The FIFO is empty, and the watermark is one element.
+    pub fn new(signal: u32) -> Self {
+        MockSource {
+            mock: Rc::new(Mock::new(signal)),
+            watermark: Rc::new(Cell::new(1)),
+        }
+    }
+
+    /// Add `data` to the end of the receive FIFO
+    pub fn push(&self, data: &[E]) {
+        self.mock.fifo.borrow_mut().extend(data);
+    }
+
+    /// Returns the number of elements in the receive FIFO
+    pub fn remaining(&self) -> usize {
+        self.mock.fifo.borrow().len()
+    }
+
+    /// Request service only when the FIFO holds at least `watermark` elements
+    ///
+    /// A watermark of zero requests service whenever the mock is enabled, even
+    /// if the FIFO is empty. Once the FIFO has fewer elements than the
+    /// watermark, it stops requesting until you `push` more data.
+    pub fn set_watermark(&self, watermark: usize) {
+        self.watermark.set(watermark);
+    }
+
+    /// Signal a bus error once the DMA controller has read `reads` elements
+    ///
+    /// `None` removes the error.
+    pub fn fail_after(&self, reads: Option<usize>) {
+        self.mock.fail_after.set(reads);
+    }
+
+    /// Returns the number of elements that the DMA controller read
+    pub fn reads(&self) -> usize {
+        self.mock.accesses.get()
+    }
+
+    /// Returns `true` if the source is enabled for DMA transfers
+    pub fn is_enabled(&self) -> bool {
+        self.mock.enabled.get()
+    }
+}
+
+// Safety: the data register is valid for the life of the mock. DMA accesses
+// are simulated.
+unsafe impl<E: Element> Source<E> for MockSource<E> {
+    fn source_signal(&self) -> u32 {
+        self.mock.signal
+    }
+    fn source_address(&self) -> *const E {
+        self.mock.address()
+    }
+    fn enable_source(&mut self) {
+        self.mock.enabled.set(true);
+    }
+    fn disable_source(&mut self) {
+        self.mock.enabled.set(false);
+    }
+}
+
+impl<E: Element> Peripheral for MockSource<E> {
+    fn is_requesting(&self, signal: u32) -> bool {
+        signal == self.mock.signal
+            && self.mock.enabled.get()
+            && self.mock.fifo.borrow().len() >= self.watermark.get()
+    }
+
+    fn read(&self, address: u32, bytes: &mut [u8]) -> Option<bool> {
+        if !self.mock.access(address, bytes.len())? {
+            return Some(false);
+        }
+        let Some(element) = self.mock.fifo.borrow_mut().pop_front() else {
+            return Some(false);
+        };
+        // Safety: the access matches the size of the element.
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                (&element as *const E).cast::<u8>(),
+                bytes.as_mut_ptr(),
+                bytes.len(),
+            )
+        };
+        self.mock.accesses.set(self.mock.accesses.get() + 1);
+        Some(true)
+    }
+
+    fn write(&self, address: u32, _: &[u8]) -> Option<bool> {
+        self.mock.access(address, 0).map(|_| false)
+    }
+}
+
+/// A fake peripheral that receives DMA data
+///
+/// Each DMA write of the data register adds one element to the mock's transmit
+/// FIFO. [`drain`](Self::drain) the FIFO to see what the DMA controller wrote.
+/// While the mock is enabled, it requests service when its FIFO has room. By
+/// default, the FIFO has no capacity limit.
+///
+/// A DMA write whose size doesn't match `E` is a bus error. So is any DMA read
+/// of the data register.
+///
+/// ```
+/// use imxrt_dma::{mock::MockDestination, peripheral, sim::Simulator};
+///
+/// let sim = Simulator::<32>::new();
+/// let mut channel = sim.dma().try_channel(0).unwrap();
+///
+/// let mut spi = MockDestination::<u16>::new(14);
+/// sim.attach(spi.clone());
+///
+/// let buffer = [1u16, 2, 3, 4];
+/// // Safety: the buffer outlives the transfer.
+/// unsafe { sim.map(&buffer) };
+/// sim.block_on(peripheral::write(&mut channel, &buffer, &mut spi)).unwrap();
+/// assert_eq!(spi.drain(), buffer);
+///
+/// // Fail the third write of the next transfer.
+/// spi.fail_after(Some(spi.writes() + 2));
+/// let error = sim
+///     .block_on(peripheral::write(&mut channel, &buffer, &mut spi))
+///     .unwrap_err();
+/// assert!(error.is_destination_bus_error());
+/// assert_eq!(spi.drain(), [1, 2]);
+/// ```
+pub struct MockDestination<E> {
+    mock: Rc<Mock<E>>,
+    capacity: Rc<Cell<Option<usize>>>,
+}
+
+impl<E> Clone for MockDestination<E> {
+    fn clone(&self) -> Self {
+        MockDestination {
+            mock: Rc::clone(&self.mock),
+            capacity: Rc::clone(&self.capacity),
+        }
+    }
+}
+
+impl<E: Element> MockDestination<E> {
+    /// Create a disabled mock that uses the DMA request `signal`
+    pub fn new(signal: u32) -> Self {
+        MockDestination {
+            mock: Rc::new(Mock::new(signal)),
+            capacity: Rc::new(Cell::new(None)),
+        }
+    }
+
+    /// Remove and return all elements in the transmit FIFO
+    pub fn drain(&self) -> Vec<E> {
+        self.mock.fifo.borrow_mut().drain(..).collect()
+    }
+
+    /// Limit the transmit FIFO to `capacity` elements
+    ///
+    /// A full FIFO stops requesting service until you [`drain`](Self::drain)
+    /// it. `None` removes the limit.
+    pub fn set_capacity(&self, capacity: Option<usize>) {
+        self.capacity.set(capacity);
+    }
+
+    /// Signal a bus error once the DMA controller has written `writes` elements
+    ///
+    /// `None` removes the error.
+    pub fn fail_after(&self, writes: Option<usize>) {
+        self.mock.fail_after.set(writes);
+    }
+
+    /// Returns the number of elements that the DMA controller wrote
+    pub fn writes(&self) -> usize {
+        self.mock.accesses.get()
+    }
+
+    /// Returns `true` if the destination is enabled for DMA transfers
+    pub fn is_enabled(&self) -> bool {
+        self.mock.enabled.get()
+    }
+}
+
+// Safety: the data register is valid for the life of the mock. DMA accesses
+// are simulated.
+unsafe impl<E: Element> Destination<E> for MockDestination<E> {
+    fn destination_signal(&self) -> u32 {
+        self.mock.signal
+    }
+    fn destination_address(&self) -> *const E {
+        self.mock.address()
+    }
+    fn enable_destination(&mut self) {
+        self.mock.enabled.set(true);
+    }
+    fn disable_destination(&mut self) {
+        self.mock.enabled.set(false);
+    }
+}
+
+impl<E: Element> Peripheral for MockDestination<E> {
+    fn is_requesting(&self, signal: u32) -> bool {
+        signal == self.mock.signal
+            && self.mock.enabled.get()
+            && self
+                .capacity
+                .get()
+                .is_none_or(|capacity| self.mock.fifo.borrow().len() < capacity)
+    }
+
+    fn read(&self, address: u32, _: &mut [u8]) -> Option<bool> {
+        self.mock.access(address, 0).map(|_| false)
+    }
+
+    fn write(&self, address: u32, bytes: &[u8]) -> Option<bool> {
+        if !self.mock.access(address, bytes.len())? {
+            return Some(false);
+        }
+        // Safety: the access matches the size of the element, and every bit
+        // pattern is a valid element.
+        let element = unsafe { bytes.as_ptr().cast::<E>().read_unaligned() };
+        self.mock.fifo.borrow_mut().push_back(element);
+        self.mock.accesses.set(self.mock.accesses.get() + 1);
+        Some(true)
+    }
+}
@@ -8,8 +8,12 @@
 //! Otherwise, you can poll the future in a loop.
 
 use super::{
+    armed::{ArmedPool, ArmedTcd},
+    buffer::PingPongBuffer,
     channel::{self, Channel, Configuration},
-    Element, Error, Transfer,
+    interrupt,
+    ring::{DmaRingBuffer, Length, Reader, Statistics, ValidLength},
+    ConfigurationError, Element, Error, Transfer,
 };
 
 use core::{
@@ -105,6 +109,8 @@ where
     channel: &'a Channel,
     source: &'a mut S,
     transfer: Transfer<'a>,
+    #[cfg(feature = "cache")]
+    buffer: crate::cache::Region,
     _elem: PhantomData<&'a mut E>,
 }
 
@@ -114,9 +120,15 @@ where
     E: Element,
 {
     type Output = Result<(), Error>;
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         // Safety: no movement from transfer future...
-        unsafe { self.map_unchecked_mut(|this| &mut this.transfer) }.poll(cx)
+        let poll = unsafe { self.as_mut().map_unchecked_mut(|this| &mut this.transfer) }.poll(cx);
+        #[cfg(feature = "cache")]
+        if let Poll::Ready(Ok(())) = poll {
+            // Safety: the transfer is complete.
+            unsafe { self.buffer.after_dma_write() };
+        }
+        poll
     }
 }
 
@@ -138,9 +150,12 @@ where
     E: Element,
 {
     channel.disable();
+    channel.claim_clock();
 
     channel.set_disable_on_completion(true);
     channel.set_channel_configuration(Configuration::enable(source.source_signal()));
+    #[cfg(feature = "cache")]
+    crate::cache::Region::of(buffer).before_dma_write();
     // Safety: hardware source address must be valid, otherwise impl is unsound.
     // Destination buffer lifetime captured by future. The combination of minor
     // loops and transfer iterations ensure that we do not exceed the end of the
@@ -152,6 +167,11 @@ where
         channel.set_transfer_iterations(buffer.len() as u16);
     }
 
+    crate::trace::start(
+        channel.channel(),
+        crate::trace::PERIPHERAL_TO_MEMORY,
+        buffer.len(),
+    );
     source.enable_source();
 }
 
@@ -160,6 +180,10 @@ where
 /// Consider using a DMA interrupt handler that calls [`on_interrupt()`](crate::Dma::on_interrupt)
 /// to wake the executor when the transfer completes. Otherwise, poll the future.
 ///
+/// With the `"cache"` feature, `read` cleans and invalidates the data cache for
+/// `buffer` before the transfer starts, and invalidates it again when the transfer
+/// completes.
+///
 /// # Example
 ///
 /// Receive 32 bytes from a LPUART peripheral. Wake the executor when the transfer completes.
@@ -211,12 +235,96 @@ where
     Read {
         channel,
         // Safety: transfer is correctly defined
-        transfer: unsafe { Transfer::new(channel) },
+        transfer: unsafe { Transfer::new(channel) }.bounded(
+            interrupt::span(source.source_address(), 1),
+            interrupt::span(buffer.as_ptr(), buffer.len()),
+        ),
         source,
+        #[cfg(feature = "cache")]
+        buffer: crate::cache::Region::of(buffer),
         _elem: PhantomData,
     }
 }
 
+/// Like [`read()`], but checks the transfer before touching the hardware
+///
+/// Returns a [configuration error](Error::configuration_error) if
+///
+/// - `buffer` is empty, or holds more elements than a transfer can move
+/// - the source address isn't aligned to the element size
+/// - the source signal doesn't fit in the multiplexer
+/// - `buffer` is outside of the driver's [memory map](crate::Dma::set_memory_map)
+///
+/// If there's an error, the channel, source, and buffer are unchanged.
+pub fn try_read<'a, S, E>(
+    channel: &'a mut Channel,
+    source: &'a mut S,
+    buffer: &'a mut [E],
+) -> Result<Read<'a, S, E>, Error>
+where
+    S: Source<E>,
+    E: Element,
+{
+    check_length(buffer.len())?;
+    check_address(source.source_address())?;
+    channel.check_source(source.source_signal())?;
+    channel.check_buffer(buffer)?;
+    Ok(read(channel, source, buffer))
+}
+
+/// The most elements that one transfer can move
+const MAX_LENGTH: usize = 0x7FFF;
+
+fn check_length(len: usize) -> Result<(), Error> {
+    if (1..=MAX_LENGTH).contains(&len) {
+        Ok(())
+    } else {
+        Err(Error::configuration(ConfigurationError::InvalidLength))
+    }
+}
+
+fn check_address<E: Element>(address: *const E) -> Result<(), Error> {
+    if (address as usize).is_multiple_of(core::mem::size_of::<E>()) {
+        Ok(())
+    } else {
+        Err(Error::configuration(ConfigurationError::Alignment))
+    }
+}
+
+/// Panics if the channels of a full-duplex transfer belong to different controllers
+fn assert_same_controller(rx_channel: &Channel, tx_channel: &Channel) {
+    assert!(
+        rx_channel.is_same_controller(tx_channel),
+        "RX and TX DMA channels are on different controllers"
+    );
+}
+
+/// Check a continuous transfer's channel for errors and cancellations
+fn check_continuous(channel: &Channel) -> Result<(), Error> {
+    if channel.is_error() {
+        Err(channel.latched_error_status())
+    } else if !channel.is_enabled() {
+        Err(channel.cancelled_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Stop a continuous transfer's channel, once its peripheral is disabled
+///
+/// Waits for the peripheral's request to drop, then disables the channel,
+/// clears its flags and waker, and releases its clock.
+fn stop_continuous(channel: &Channel) {
+    while channel.is_hardware_signaling() {}
+    channel.disable();
+    channel.clear_interrupt();
+    channel.clear_complete();
+    channel.clear_error();
+    channel.clear_waker();
+    #[cfg(feature = "clock-gating")]
+    channel.release_clock();
+}
+
 /// A DMA transfer that sends data to hardware
 ///
 /// The future resolves when the device has sent all provided data.
@@ -262,8 +370,11 @@ where
     E: Element,
 {
     channel.disable();
+    channel.claim_clock();
     channel.set_disable_on_completion(true);
     channel.set_channel_configuration(Configuration::enable(destination.destination_signal()));
+    #[cfg(feature = "cache")]
+    crate::cache::Region::of(buffer).before_dma_read();
     // Safety: hardware address must be valid, otherwise impl is unsound.
     // Source buffer lifetime captured by future. The combination of minor
     // loops and transfer iterations ensure that we do not exceed the end of the
@@ -275,6 +386,11 @@ where
         channel.set_transfer_iterations(buffer.len() as u16);
     }
 
+    crate::trace::start(
+        channel.channel(),
+        crate::trace::MEMORY_TO_PERIPHERAL,
+        buffer.len(),
+    );
     destination.enable_destination();
 }
 
@@ -283,6 +399,9 @@ where
 /// Consider using a DMA interrupt handler that calls [`on_interrupt()`](crate::Dma::on_interrupt)
 /// to wake the executor when the transfer completes. Otherwise, poll the future.
 ///
+/// With the `"cache"` feature, `write` cleans the data cache for `buffer` before
+/// the transfer starts.
+///
 /// # Example
 ///
 /// Send five bytes to a LPUART device. Wake the executor when the transfer completes.
@@ -334,13 +453,42 @@ where
     prepare_write(channel, buffer, destination);
     Write {
         channel,
-        destination,
         // Safety: transfer is correctly defined
-        transfer: unsafe { Transfer::new(channel) },
+        transfer: unsafe { Transfer::new(channel) }.bounded(
+            interrupt::span(buffer.as_ptr(), buffer.len()),
+            interrupt::span(destination.destination_address(), 1),
+        ),
+        destination,
         _elem: PhantomData,
     }
 }
 
+/// Like [`write()`], but checks the transfer before touching the hardware
+///
+/// Returns a [configuration error](Error::configuration_error) if
+///
+/// - `buffer` is empty, or holds more elements than a transfer can move
+/// - the destination address isn't aligned to the element size
+/// - the destination signal doesn't fit in the multiplexer
+/// - `buffer` is outside of the driver's [memory map](crate::Dma::set_memory_map)
+///
+/// If there's an error, the channel, destination, and buffer are unchanged.
+pub fn try_write<'a, D, E>(
+    channel: &'a mut Channel,
+    buffer: &'a [E],
+    destination: &'a mut D,
+) -> Result<Write<'a, D, E>, Error>
+where
+    D: Destination<E>,
+    E: Element,
+{
+    check_length(buffer.len())?;
+    check_address(destination.destination_address())?;
+    channel.check_source(destination.destination_signal())?;
+    channel.check_buffer(buffer)?;
+    Ok(write(channel, buffer, destination))
+}
+
 /// Indicates that a peripheral can read and write from a single buffer
 /// using two simultaneous DMA transfers
 ///
@@ -373,6 +521,8 @@ where
     tx_transfer: Transfer<'a>,
     tx_done: bool,
     peripheral: &'a mut P,
+    #[cfg(feature = "cache")]
+    buffer: crate::cache::Region,
     _elem: PhantomData<E>,
 }
 
@@ -382,6 +532,15 @@ where
 /// Consider using a DMA interrupt handler that calls [`on_interrupt()`](crate::Dma::on_interrupt)
 /// to wake the executor when the transfer completes. Otherwise, poll the future.
 ///
+/// With the `"cache"` feature, `full_duplex` cleans and invalidates the data cache
+/// for `buffer` before the transfer starts, and invalidates it again when the
+/// transfer completes.
+///
+/// # Panics
+///
+/// Panics if `rx_channel` and `tx_channel` belong to different DMA controllers.
+/// See [`Channel::is_same_controller`].
+///
 /// # Example
 ///
 /// Perform a full-duplex transfer of five `u32`s with a LPSPI peripheral. Generate an interrupt
@@ -447,21 +606,62 @@ where
     P: Bidirectional<E>,
     E: Element,
 {
+    assert_same_controller(rx_channel, tx_channel);
     prepare_write(tx_channel, buffer, peripheral);
     prepare_read(rx_channel, peripheral, buffer);
 
     FullDuplex {
         rx_channel,
-        rx_transfer: unsafe { Transfer::new(rx_channel) },
+        rx_transfer: unsafe { Transfer::new(rx_channel) }.bounded(
+            interrupt::span(peripheral.source_address(), 1),
+            interrupt::span(buffer.as_ptr(), buffer.len()),
+        ),
         rx_done: false,
         tx_channel,
-        tx_transfer: unsafe { Transfer::new(tx_channel) },
+        tx_transfer: unsafe { Transfer::new(tx_channel) }.bounded(
+            interrupt::span(buffer.as_ptr(), buffer.len()),
+            interrupt::span(peripheral.destination_address(), 1),
+        ),
         tx_done: false,
         peripheral,
+        #[cfg(feature = "cache")]
+        buffer: crate::cache::Region::of(buffer),
         _elem: PhantomData,
     }
 }
 
+/// Like [`full_duplex()`], but checks the transfer before touching the hardware
+///
+/// Returns a [configuration error](Error::configuration_error) for the same
+/// reasons as [`try_read()`] and [`try_write()`], and
+/// [`DifferentControllers`](ConfigurationError::DifferentControllers) if the
+/// channels belong to different DMA controllers. If there's an error, the
+/// channels, peripheral, and buffer are unchanged.
+pub fn try_full_duplex<'a, P, E>(
+    rx_channel: &'a mut Channel,
+    tx_channel: &'a mut Channel,
+    peripheral: &'a mut P,
+    buffer: &'a mut [E],
+) -> Result<FullDuplex<'a, P, E>, Error>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    if !rx_channel.is_same_controller(tx_channel) {
+        return Err(Error::configuration(
+            ConfigurationError::DifferentControllers,
+        ));
+    }
+    check_length(buffer.len())?;
+    check_address(peripheral.source_address())?;
+    check_address(peripheral.destination_address())?;
+    rx_channel.check_source(peripheral.source_signal())?;
+    tx_channel.check_source(peripheral.destination_signal())?;
+    rx_channel.check_buffer(buffer)?;
+    tx_channel.check_buffer(buffer)?;
+    Ok(full_duplex(rx_channel, tx_channel, peripheral, buffer))
+}
+
 impl<P, E> Future for FullDuplex<'_, P, E>
 where
     P: Bidirectional<E>,
@@ -493,6 +693,11 @@ where
         }
 
         if self.tx_done && self.rx_done {
+            #[cfg(feature = "cache")]
+            // Safety: both transfers are complete.
+            unsafe {
+                self.buffer.after_dma_write()
+            };
             Poll::Ready(Ok(()))
         } else {
             Poll::Pending
@@ -513,3 +718,896 @@ where
         // Drop the transfers to finish cancellation...
     }
 }
+
+/// A continuous full-duplex transfer over two buffers
+///
+/// A `FullDuplexStream` keeps a [`Bidirectional`] peripheral busy without
+/// stopping. Like [`full_duplex()`], each buffer is sent, element by element,
+/// and replaced with the received data. The two buffers alternate: while the
+/// DMA controller transfers one buffer, you process the other. The channels
+/// load each buffer's transfer from a pre-armed TCD using scatter / gather,
+/// so there's no gap between buffers.
+///
+/// Use [`next_buffer`](Self::next_buffer) to wait for the next buffer. The buffer holds the
+/// data received from the peripheral; replace it with the data to send next.
+/// The buffer returns to the DMA controller when you call `next_buffer` again. If you
+/// hold the buffer for too long, the DMA controller sends the buffer before
+/// you've refilled it, and receives over it.
+///
+/// Return each buffer before the DMA controller finishes the other buffer.
+/// If a channel is transferring the held buffer when you call `next_buffer`,
+/// `next_buffer` returns an error with [`Overrun`](ConfigurationError::Overrun);
+/// drop the stream to stop the transfers. The stream only knows which buffer
+/// each channel is transferring, and not how many buffers it finished. If you
+/// hold a buffer for two or more buffer periods, the channels may be back on
+/// the other buffer, and `next_buffer` can't detect the overrun.
+///
+/// To create the stream, use [`full_duplex_stream()`]. Dropping the stream
+/// stops both channels, and disables the peripheral.
+pub struct FullDuplexStream<'a, P, E, const N: usize>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    rx_channel: &'a Channel,
+    tx_channel: &'a Channel,
+    peripheral: &'a mut P,
+    /// RX images, then TX images, one per buffer.
+    tcds: &'a ArmedPool<4>,
+    buffers: &'a mut [[E; N]; 2],
+    /// The buffer that completes next.
+    next: usize,
+    /// The buffer that the user holds, if any.
+    held: Option<usize>,
+}
+
+/// Start a continuous full-duplex transfer using two DMA channels
+///
+/// `buffers` holds the data to send. The stream sends `buffers[0]`, then
+/// `buffers[1]`, then `buffers[0]` again, and so on, replacing each buffer with
+/// the received data. `tcds` holds the TCD images that the channels load for
+/// each buffer; place it in memory that the DMA controller can access. The
+/// channels are enabled before `full_duplex_stream` returns.
+///
+/// The RX channel interrupts after each buffer. Route the interrupt to
+/// [`on_interrupt()`](crate::Dma::on_interrupt) to wake the executor, or poll
+/// the stream's futures.
+///
+/// With the `"cache"` feature, the stream cleans and invalidates the data cache
+/// for each buffer before the DMA controller uses it, and invalidates it again
+/// before [`next_buffer`](FullDuplexStream::next_buffer) returns it.
+///
+/// # Safety
+///
+/// The DMA controller uses `buffers` and `tcds` until the stream is dropped.
+/// You must drop the stream; don't leak it with `core::mem::forget`, or any
+/// other way that skips the stream's `Drop`.
+///
+/// # Panics
+///
+/// Panics if `rx_channel` and `tx_channel` belong to different DMA controllers.
+/// See [`Channel::is_same_controller`].
+///
+/// # Example
+///
+/// Continuously exchange 16 `u16`s at a time with a LPSPI peripheral.
+///
+/// ```no_run
+/// use imxrt_dma::{armed::ArmedPool, peripheral, channel::Channel};
+/// # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+/// # struct X;
+/// # unsafe impl peripheral::Source<u16> for X {
+/// #   fn source_signal(&self) -> u32 { 0 }
+/// #   fn source_address(&self) -> *const u16 { panic!() }
+/// #   fn enable_source(&mut self) { panic!() }
+/// #   fn disable_source(&mut self) { panic!() }
+/// # }
+/// # unsafe impl peripheral::Destination<u16> for X {
+/// #   fn destination_signal(&self) -> u32 { 0 }
+/// #   fn destination_address(&self) -> *const u16 { panic!() }
+/// #   fn enable_destination(&mut self) { panic!() }
+/// #   fn disable_destination(&mut self) { panic!() }
+/// # }
+/// # unsafe impl peripheral::Bidirectional<u16> for X {}
+/// # fn read_sensor_command(_: &mut [u16; 16]) {}
+///
+/// # async fn f() -> imxrt_dma::Result<()> {
+/// let mut lpspi = // A LPSPI peripheral
+///     # X;
+/// let mut channel_7: Channel = // DMA channel 7
+///     # unsafe { DMA.channel(7) };
+/// let mut channel_8: Channel = // DMA channel 8
+///     # unsafe { DMA.channel(8) };
+///
+/// let mut tcds = ArmedPool::new();
+/// let mut buffers = [[0u16; 16]; 2];
+/// // Safety: the stream is dropped at the end of this scope.
+/// let mut stream = unsafe {
+///     peripheral::full_duplex_stream(
+///         &mut channel_7,
+///         &mut channel_8,
+///         &mut lpspi,
+///         &mut tcds,
+///         &mut buffers,
+///     )
+/// };
+///
+/// loop {
+///     let buffer = stream.next_buffer().await?;
+///     // Process the received samples in `buffer`, then refill it...
+///     read_sensor_command(buffer);
+/// }
+/// # }
+/// ```
+///
+/// With the `"test-util"` feature, test a stream on a host. Here, mocks stand
+/// in for the LPSPI. The transmitter holds one buffer at a time, and the stream
+/// detects an overrun when a buffer is held for too long.
+///
+/// ```
+/// # #[cfg(feature = "test-util")] {
+/// use imxrt_dma::{
+///     armed::ArmedPool,
+///     mock::{MockDestination, MockSource},
+///     peripheral::{self, Bidirectional, Destination, Source},
+///     sim::Simulator,
+///     ConfigurationError,
+/// };
+///
+/// /// A fake LPSPI.
+/// struct Spi {
+///     rx: MockSource<u8>,
+///     tx: MockDestination<u8>,
+/// }
+/// # unsafe impl Source<u8> for Spi {
+/// #     fn source_signal(&self) -> u32 { self.rx.source_signal() }
+/// #     fn source_address(&self) -> *const u8 { self.rx.source_address() }
+/// #     fn enable_source(&mut self) { self.rx.enable_source() }
+/// #     fn disable_source(&mut self) { self.rx.disable_source() }
+/// # }
+/// # unsafe impl Destination<u8> for Spi {
+/// #     fn destination_signal(&self) -> u32 { self.tx.destination_signal() }
+/// #     fn destination_address(&self) -> *const u8 { self.tx.destination_address() }
+/// #     fn enable_destination(&mut self) { self.tx.enable_destination() }
+/// #     fn disable_destination(&mut self) { self.tx.disable_destination() }
+/// # }
+/// unsafe impl Bidirectional<u8> for Spi {}
+///
+/// let sim = Simulator::<32>::new();
+/// let mut rx_channel = sim.dma().try_channel(1).unwrap();
+/// let mut tx_channel = sim.dma().try_channel(2).unwrap();
+/// let rx = MockSource::<u8>::new(3);
+/// let tx = MockDestination::<u8>::new(4);
+/// tx.set_capacity(Some(4));
+/// sim.attach(rx.clone());
+/// sim.attach(tx.clone());
+/// let mut spi = Spi { rx: rx.clone(), tx: tx.clone() };
+///
+/// let mut tcds = ArmedPool::new();
+/// let mut buffers = [[1u8, 2, 3, 4], [5, 6, 7, 8]];
+/// // Safety: the buffers and images outlive the stream.
+/// unsafe {
+///     sim.map(tcds.tcds_mut());
+///     sim.map(&buffers);
+/// }
+/// // Safety: the stream is dropped below.
+/// let mut stream = unsafe {
+///     peripheral::full_duplex_stream(
+///         &mut rx_channel,
+///         &mut tx_channel,
+///         &mut spi,
+///         &mut tcds,
+///         &mut buffers,
+///     )
+/// };
+///
+/// rx.push(&[10, 11, 12, 13]);
+/// let buffer = sim.block_on(stream.next_buffer()).unwrap();
+/// assert_eq!(*buffer, [10, 11, 12, 13]);
+/// assert_eq!(tx.drain(), [1, 2, 3, 4]);
+/// *buffer = [21, 22, 23, 24];
+///
+/// rx.push(&[14, 15, 16, 17]);
+/// let buffer = sim.block_on(stream.next_buffer()).unwrap();
+/// assert_eq!(*buffer, [14, 15, 16, 17]);
+/// assert_eq!(tx.drain(), [5, 6, 7, 8]);
+///
+/// // The first buffer was refilled before it was sent again.
+/// rx.push(&[18, 19, 20, 21]);
+/// let buffer = sim.block_on(stream.next_buffer()).unwrap();
+/// assert_eq!(*buffer, [18, 19, 20, 21]);
+/// assert_eq!(tx.drain(), [21, 22, 23, 24]);
+///
+/// // Hold the first buffer while the channels finish the second buffer, and
+/// // move back to the first buffer.
+/// rx.push(&[0; 4]);
+/// sim.run();
+/// assert_eq!(tx.drain().len(), 4);
+/// let error = sim.block_on(stream.next_buffer()).unwrap_err();
+/// assert_eq!(error.configuration_error(), Some(ConfigurationError::Overrun));
+///
+/// drop(stream);
+/// assert!(!rx.is_enabled() && !tx.is_enabled());
+/// # }
+/// ```
+pub unsafe fn full_duplex_stream<'a, P, E, const N: usize>(
+    rx_channel: &'a mut Channel,
+    tx_channel: &'a mut Channel,
+    peripheral: &'a mut P,
+    tcds: &'a mut ArmedPool<4>,
+    buffers: &'a mut [[E; N]; 2],
+) -> FullDuplexStream<'a, P, E, N>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    let () = FullDuplexStream::<P, E, N>::VALID_LENGTH;
+    assert_same_controller(rx_channel, tx_channel);
+
+    rx_channel.disable();
+    tx_channel.disable();
+    rx_channel.claim_clock();
+    tx_channel.claim_clock();
+    rx_channel.set_channel_configuration(Configuration::enable(peripheral.source_signal()));
+    tx_channel.set_channel_configuration(Configuration::enable(peripheral.destination_signal()));
+
+    let size = core::mem::size_of::<E>();
+    let [rx0, rx1, tx0, tx1] = tcds.tcds_mut();
+    for (index, (rx, tx)) in [(&mut *rx0, &mut *tx0), (&mut *rx1, &mut *tx1)]
+        .into_iter()
+        .enumerate()
+    {
+        let buffer = buffers[index].as_ptr() as u32;
+        *rx = ArmedTcd::new()
+            .source::<E>(peripheral.source_address() as u32, 0)
+            .destination::<E>(buffer, size as i16)
+            .minor_loop_bytes(size as u32)
+            .iterations(N as u16)
+            .destination_last_adjustment(-((size * N) as i32))
+            .interrupt_on_completion(true);
+        *tx = ArmedTcd::new()
+            .source::<E>(buffer, size as i16)
+            .destination::<E>(peripheral.destination_address() as u32, 0)
+            .minor_loop_bytes(size as u32)
+            .iterations(N as u16)
+            .source_last_adjustment(-((size * N) as i32));
+    }
+    rx0.chain(rx1);
+    rx1.chain(rx0);
+    tx0.chain(tx1);
+    tx1.chain(tx0);
+
+    #[cfg(feature = "cache")]
+    crate::cache::Region::of(buffers.as_slice()).before_dma_write();
+
+    crate::trace::start(
+        rx_channel.channel(),
+        crate::trace::PERIPHERAL_TO_MEMORY,
+        2 * N,
+    );
+    crate::trace::start(
+        tx_channel.channel(),
+        crate::trace::MEMORY_TO_PERIPHERAL,
+        2 * N,
+    );
+
+    // Safety: the images describe the buffers and the peripheral, and the
+    // caller keeps them valid until the stream is dropped.
+    unsafe {
+        rx0.load(rx_channel);
+        tx0.load(tx_channel);
+    }
+    peripheral.enable_source();
+    peripheral.enable_destination();
+
+    FullDuplexStream {
+        rx_channel,
+        tx_channel,
+        peripheral,
+        tcds,
+        buffers,
+        next: 0,
+        held: None,
+    }
+}
+
+impl<'a, P, E, const N: usize> FullDuplexStream<'a, P, E, N>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    const VALID_LENGTH: () = assert!(
+        N >= 1 && N <= MAX_LENGTH,
+        "Buffers must hold between 1 and 0x7FFF elements"
+    );
+
+    /// Wait for the next received buffer
+    ///
+    /// The future resolves with the buffer that the DMA controller just
+    /// finished. Calling `next_buffer` returns the previous buffer to the DMA
+    /// controller. See [`FullDuplexStream`] for more information.
+    pub fn next_buffer(&mut self) -> NextBuffer<'_, 'a, P, E, N> {
+        NextBuffer { stream: Some(self) }
+    }
+
+    /// Returns the index of the buffer that `channel` is transferring
+    ///
+    /// Each image links to the other buffer's image, so the channel's last
+    /// destination adjustment identifies the image that it loaded.
+    fn active(&self, channel: &Channel, images: &[ArmedTcd]) -> usize {
+        let sga = channel.tcd().DLAST_SGA.read() as u32;
+        usize::from(sga == &images[0] as *const ArmedTcd as usize as u32)
+    }
+}
+
+impl<P, E, const N: usize> Drop for FullDuplexStream<'_, P, E, N>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    fn drop(&mut self) {
+        self.peripheral.disable_destination();
+        self.peripheral.disable_source();
+        stop_continuous(self.tx_channel);
+        stop_continuous(self.rx_channel);
+    }
+}
+
+/// Waits for the next buffer of a [`FullDuplexStream`]
+///
+/// Use [`FullDuplexStream::next_buffer`] to create this future.
+pub struct NextBuffer<'s, 'a, P, E, const N: usize>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    stream: Option<&'s mut FullDuplexStream<'a, P, E, N>>,
+}
+
+impl<'s, P, E, const N: usize> Future for NextBuffer<'s, '_, P, E, N>
+where
+    P: Bidirectional<E>,
+    E: Element,
+{
+    type Output = Result<&'s mut [E; N], Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let stream = self
+            .stream
+            .as_mut()
+            .expect("NextBuffer polled after it resolved");
+        let (rx_images, tx_images) = stream.tcds.tcds().split_at(2);
+
+        if let Some(held) = stream.held.take() {
+            // If either channel already moved on to the held buffer, it
+            // used the buffer before it was refilled. A channel that moved
+            // on twice is back on the other buffer; that goes unnoticed.
+            if stream.active(stream.tx_channel, tx_images) == held
+                || stream.active(stream.rx_channel, rx_images) == held
+            {
+                return Poll::Ready(Err(Error::configuration(ConfigurationError::Overrun)));
+            }
+            #[cfg(feature = "cache")]
+            crate::cache::Region::of(&stream.buffers[held]).before_dma_write();
+        }
+
+        stream.rx_channel.register_waker(cx.waker());
+        check_continuous(stream.rx_channel)?;
+        check_continuous(stream.tx_channel)?;
+        // The RX channel moves to the other buffer once it receives this one.
+        if stream.active(stream.rx_channel, rx_images) == stream.next {
+            return Poll::Pending;
+        }
+        stream.rx_channel.clear_interrupt();
+        // Order the address read before the reads of the received data.
+        core::sync::atomic::fence(core::sync::atomic::Ordering::Acquire);
+
+        let stream = self.stream.take().unwrap();
+        let index = stream.next;
+        stream.next = 1 - index;
+        stream.held = Some(index);
+        let buffer = &mut stream.buffers[index];
+        #[cfg(feature = "cache")]
+        // Safety: the DMA controller is done writing this buffer.
+        unsafe {
+            crate::cache::Region::of(buffer).after_dma_write()
+        };
+        Poll::Ready(Ok(buffer))
+    }
+}
+
+/// A continuous transfer from a peripheral into a ring
+///
+/// The DMA channel endlessly writes the source's data into a
+/// [`DmaRingBuffer`], wrapping around the end of the ring. Use
+/// [`read`](ReadRing::read) to copy the data out of the ring as it arrives.
+/// The ring tracks the channel's destination address, so there's no gap in
+/// the reception between reads. This is the usual way to receive from a
+/// LPUART without losing data.
+///
+/// The channel interrupts halfway through the ring, and at the end of the ring.
+/// Route the interrupt to [`on_interrupt()`](crate::Dma::on_interrupt) to wake
+/// the executor, or poll the futures. Read often enough that the DMA controller
+/// doesn't overwrite data that you haven't read; the ring can't detect this.
+/// See [`Reader`] for more information.
+///
+/// To create the ring, use [`read_ring()`]. Dropping the ring stops the
+/// channel, and disables the source.
+pub struct ReadRing<'a, S, E, const N: usize>
+where
+    S: Source<E>,
+    E: Element,
+    Length<E, N>: ValidLength,
+{
+    channel: &'a Channel,
+    source: &'a mut S,
+    reader: Reader<'a, E, N>,
+}
+
+/// Start a continuous transfer from `source` into `ring`
+///
+/// The channel is enabled before `read_ring` returns. `read_ring` overwrites the
+/// channel's transfer, including its interrupt and disable on completion settings.
+///
+/// With the `"cache"` feature, `read_ring` cleans the data cache for `ring`,
+/// and [`read`](ReadRing::read) invalidates it before copying data out of the ring.
+///
+/// # Safety
+///
+/// The DMA controller writes into `ring` until the `ReadRing` is dropped. You
+/// must drop the `ReadRing`; don't leak it with `core::mem::forget`, or any
+/// other way that skips its `Drop`.
+///
+/// # Example
+///
+/// Receive bytes from a LPUART peripheral, without gaps between reads.
+///
+/// ```no_run
+/// use imxrt_dma::{peripheral, channel::Channel, ring::DmaRingBuffer};
+/// # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+/// # struct X;
+/// # unsafe impl peripheral::Source<u8> for X {
+/// #   fn source_signal(&self) -> u32 { 0 }
+/// #   fn source_address(&self) -> *const u8 { panic!() }
+/// #   fn enable_source(&mut self) { panic!() }
+/// #   fn disable_source(&mut self) { panic!() }
+/// # }
+/// # fn parse(_: &[u8]) {}
+///
+/// # async fn f() -> imxrt_dma::Result<()> {
+/// let mut lpuart = // A LPUART peripheral
+///     # X;
+/// let mut channel_7: Channel = // DMA channel 7
+///     # unsafe { DMA.channel(7) };
+///
+/// let mut ring = DmaRingBuffer::<u8, 256>::new(0);
+/// // Safety: the ring is dropped at the end of this scope.
+/// let mut rx = unsafe { peripheral::read_ring(&mut channel_7, &mut lpuart, &mut ring) };
+///
+/// let mut buffer = [0; 64];
+/// loop {
+///     let len = rx.read(&mut buffer).await?;
+///     parse(&buffer[..len]);
+/// }
+/// # }
+/// ```
+///
+/// With the `"test-util"` feature, test the ring on a host. A short read leaves
+/// data in the ring, and the next read continues across the end of the ring.
+///
+/// ```
+/// # #[cfg(feature = "test-util")] {
+/// use imxrt_dma::{mock::MockSource, peripheral, ring::DmaRingBuffer, sim::Simulator};
+///
+/// let sim = Simulator::<32>::new();
+/// let mut channel = sim.dma().try_channel(4).unwrap();
+/// let uart = MockSource::<u8>::new(5);
+/// sim.attach(uart.clone());
+/// let mut lpuart = uart.clone();
+///
+/// let mut ring = DmaRingBuffer::<u8, 32>::new(0);
+/// // Safety: the ring outlives the transfer.
+/// unsafe { sim.map(ring.as_slice()) };
+/// // Safety: the ring is dropped below.
+/// let mut rx = unsafe { peripheral::read_ring(&mut channel, &mut lpuart, &mut ring) };
+///
+/// let data: Vec<u8> = (0..40).collect();
+/// uart.push(&data[..24]);
+/// sim.run();
+/// let mut short = [0; 16];
+/// assert_eq!(sim.block_on(rx.read(&mut short)).unwrap(), 16);
+/// assert_eq!(short, data[..16]);
+/// assert_eq!(rx.available(), 8);
+///
+/// // The channel wraps around the end of the 32 byte ring.
+/// uart.push(&data[24..]);
+/// sim.run();
+/// let mut buffer = [0; 64];
+/// let len = sim.block_on(rx.read(&mut buffer)).unwrap();
+/// assert_eq!(buffer[..len], data[16..]);
+/// assert_eq!(rx.statistics().wraps, 1);
+///
+/// drop(rx);
+/// assert!(!uart.is_enabled());
+/// # }
+/// ```
+pub unsafe fn read_ring<'a, S, E, const N: usize>(
+    channel: &'a mut Channel,
+    source: &'a mut S,
+    ring: &'a mut DmaRingBuffer<E, N>,
+) -> ReadRing<'a, S, E, N>
+where
+    S: Source<E>,
+    E: Element,
+    Length<E, N>: ValidLength,
+{
+    channel.disable();
+    channel.claim_clock();
+    channel.set_disable_on_completion(false);
+    channel.set_interrupt_on_completion(true);
+    channel.set_interrupt_on_half(true);
+    channel.set_channel_configuration(Configuration::enable(source.source_signal()));
+    ring.clean();
+    // Safety: hardware source address must be valid, otherwise impl is unsound.
+    // The ring's modulo keeps every write in the ring, and the caller keeps the
+    // ring valid until the channel is disabled.
+    unsafe {
+        channel::set_source_hardware(channel, source.source_address());
+        ring.set_as_destination(channel);
+        channel.set_minor_loop_bytes(core::mem::size_of::<E>() as u32);
+        channel.set_transfer_iterations(N.min(MAX_LENGTH) as u16);
+    }
+    channel.clear_complete();
+    channel.clear_interrupt();
+    channel.clear_error();
+
+    let channel: &'a Channel = channel;
+    // Safety: the channel writes into the ring with the ring's modulo, and
+    // the ring holds the only reference to the channel.
+    let reader = unsafe { Reader::new(channel, ring) };
+    crate::trace::start(channel.channel(), crate::trace::PERIPHERAL_TO_MEMORY, N);
+    // Safety: the transfer is defined, and the caller upholds the ring's lifetime.
+    unsafe { channel.enable() };
+    source.enable_source();
+    ReadRing {
+        channel,
+        source,
+        reader,
+    }
+}
+
+impl<S, E, const N: usize> ReadRing<'_, S, E, N>
+where
+    S: Source<E>,
+    E: Element,
+    Length<E, N>: ValidLength,
+{
+    /// Wait for data, then copy it into `buffer`
+    ///
+    /// The future resolves as soon as there's at least one unread element. It
+    /// copies as many unread elements as fit in `buffer`, and returns the number
+    /// of elements copied. Elements that don't fit stay in the ring for the next
+    /// read. If `buffer` is empty, `read` returns zero without waiting.
+    ///
+    /// Returns an error if the channel fails, or if someone disables the channel.
+    pub async fn read(&mut self, buffer: &mut [E]) -> Result<usize, Error> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        core::future::poll_fn(|cx| {
+            // Register before checking, so that an interrupt isn't lost.
+            self.channel.register_waker(cx.waker());
+            check_continuous(self.channel)?;
+            if self.reader.available() > 0 {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await?;
+
+        let (first, second) = self.reader.peek_slices();
+        let len = first.copy_to_slice(buffer);
+        let len = len + second.copy_to_slice(&mut buffer[len..]);
+        Ok(self.reader.consume(len))
+    }
+
+    /// Returns the number of unread elements
+    pub fn available(&self) -> usize {
+        self.reader.available()
+    }
+
+    /// Returns the statistics collected since the ring started
+    pub fn statistics(&self) -> Statistics {
+        self.reader.statistics()
+    }
+}
+
+impl<S, E, const N: usize> Drop for ReadRing<'_, S, E, N>
+where
+    S: Source<E>,
+    E: Element,
+    Length<E, N>: ValidLength,
+{
+    fn drop(&mut self) {
+        self.source.disable_source();
+        stop_continuous(self.channel);
+    }
+}
+
+/// A continuous, double-buffered transfer from a peripheral
+///
+/// The DMA channel fills the first half of a [`PingPongBuffer`], then the
+/// second half, then the first half again, and so on, without a gap between
+/// the halves. While the DMA
+/// controller fills one buffer, you process the other.
+///
+/// [`next_half`](ReadPingPong::next_half) waits for the DMA controller to fill
+/// a half, then returns the half. The half returns to the DMA controller
+/// when you call `next_half` again; you can't hold both halves. If you hold the
+/// half for too long, the DMA controller writes over it.
+///
+/// Return each half before the DMA controller fills the other half. If the
+/// channel is filling the held half when you call `next_half`, `next_half`
+/// returns an error with [`Overrun`](ConfigurationError::Overrun); drop the
+/// transfer to stop the channel. Like a [`FullDuplexStream`], the transfer
+/// misses overruns of two or more half periods.
+///
+/// To create the transfer, use [`read_ping_pong()`]. Dropping the transfer
+/// stops the channel, and disables the source.
+pub struct ReadPingPong<'a, S, E, const N: usize>
+where
+    S: Source<E>,
+    E: Element,
+{
+    channel: &'a Channel,
+    source: &'a mut S,
+    buffers: &'a mut [[E; N]; 2],
+    /// The buffer that completes next.
+    next: usize,
+    /// The buffer that the user holds, if any.
+    held: Option<usize>,
+}
+
+/// Start a continuous, double-buffered transfer from `source` into `buffer`
+///
+/// The channel is enabled before `read_ping_pong` returns. It treats `buffer`
+/// as one major loop, and it interrupts when each half is full: halfway
+/// through the major loop, and at the end of the major loop. Route the interrupt
+/// to [`on_interrupt()`](crate::Dma::on_interrupt) to wake the executor, or poll
+/// the futures. `read_ping_pong` overwrites the channel's transfer, including its
+/// interrupt and disable on completion settings.
+///
+/// With the `"cache"` feature, the transfer cleans and invalidates the data cache
+/// for each buffer before the DMA controller fills it, and invalidates it again
+/// before [`next_half`](ReadPingPong::next_half) returns it.
+///
+/// # Safety
+///
+/// The DMA controller writes into `buffer` until the transfer is dropped. You
+/// must drop the transfer; don't leak it with `core::mem::forget`, or any other
+/// way that skips its `Drop`.
+///
+/// # Example
+///
+/// Capture 64 ADC samples at a time, without a gap between captures.
+///
+/// ```no_run
+/// use imxrt_dma::{buffer::PingPongBuffer, peripheral, channel::Channel};
+/// # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+/// # struct X;
+/// # unsafe impl peripheral::Source<u16> for X {
+/// #   fn source_signal(&self) -> u32 { 0 }
+/// #   fn source_address(&self) -> *const u16 { panic!() }
+/// #   fn enable_source(&mut self) { panic!() }
+/// #   fn disable_source(&mut self) { panic!() }
+/// # }
+/// # fn process(_: &[u16; 64]) {}
+///
+/// # async fn f() -> imxrt_dma::Result<()> {
+/// let mut adc = // An ADC peripheral
+///     # X;
+/// let mut channel_7: Channel = // DMA channel 7
+///     # unsafe { DMA.channel(7) };
+///
+/// let mut buffer = PingPongBuffer::<u16, 64>::new(0);
+/// // Safety: the transfer is dropped at the end of this scope.
+/// let mut capture = unsafe { peripheral::read_ping_pong(&mut channel_7, &mut adc, &mut buffer) };
+///
+/// loop {
+///     let samples = capture.next_half().await?;
+///     process(samples);
+/// }
+/// # }
+/// ```
+///
+/// With the `"test-util"` feature, test the transfer on a host. Holding a half
+/// while the channel fills the other half is an overrun.
+///
+/// ```
+/// # #[cfg(feature = "test-util")] {
+/// use imxrt_dma::{
+///     buffer::PingPongBuffer, mock::MockSource, peripheral, sim::Simulator, ConfigurationError,
+/// };
+///
+/// let sim = Simulator::<32>::new();
+/// let mut channel = sim.dma().try_channel(6).unwrap();
+/// let samples = MockSource::<u8>::new(7);
+/// sim.attach(samples.clone());
+/// let mut adc = samples.clone();
+///
+/// let mut buffer = PingPongBuffer::<u8, 32>::new(0);
+/// // Safety: the buffer outlives the transfer.
+/// unsafe { sim.map(buffer.halves()) };
+/// // Safety: the transfer is dropped below.
+/// let mut capture = unsafe { peripheral::read_ping_pong(&mut channel, &mut adc, &mut buffer) };
+///
+/// // Three halves: the first, the second, then the first again.
+/// let data: Vec<u8> = (0..96).collect();
+/// for chunk in data.chunks(32) {
+///     samples.push(chunk);
+///     let half = sim.block_on(capture.next_half()).unwrap();
+///     assert_eq!(half[..], *chunk);
+/// }
+///
+/// // Hold the first half while the channel fills the second half, and
+/// // moves back to the first half.
+/// samples.push(&[0; 32]);
+/// sim.run();
+/// let error = sim.block_on(capture.next_half()).unwrap_err();
+/// assert_eq!(error.configuration_error(), Some(ConfigurationError::Overrun));
+///
+/// drop(capture);
+/// assert!(!samples.is_enabled());
+/// # }
+/// ```
+pub unsafe fn read_ping_pong<'a, S, E, const N: usize>(
+    channel: &'a mut Channel,
+    source: &'a mut S,
+    buffer: &'a mut PingPongBuffer<E, N>,
+) -> ReadPingPong<'a, S, E, N>
+where
+    S: Source<E>,
+    E: Element,
+{
+    let () = ReadPingPong::<S, E, N>::VALID_LENGTH;
+    let buffers = buffer.halves_mut();
+
+    channel.disable();
+    channel.claim_clock();
+    channel.set_disable_on_completion(false);
+    channel.set_interrupt_on_completion(true);
+    channel.set_interrupt_on_half(true);
+    channel.set_channel_configuration(Configuration::enable(source.source_signal()));
+    #[cfg(feature = "cache")]
+    crate::cache::Region::of(buffers.as_slice()).before_dma_write();
+
+    let size = core::mem::size_of::<E>();
+    // Safety: hardware source address must be valid, otherwise impl is unsound.
+    // The major loop covers both buffers, then returns to the first buffer. The
+    // caller keeps the buffers valid until the channel is disabled.
+    unsafe {
+        channel::set_source_hardware(channel, source.source_address());
+        channel.set_destination_address(buffers[0].as_ptr());
+        channel.set_destination_offset(size as i16);
+        channel.set_destination_attributes::<E>(0);
+        channel.set_destination_last_address_adjustment(-((2 * N * size) as i32));
+        channel.set_minor_loop_bytes(size as u32);
+        channel.set_transfer_iterations((2 * N) as u16);
+    }
+    channel.clear_complete();
+    channel.clear_interrupt();
+    channel.clear_error();
+
+    crate::trace::start(channel.channel(), crate::trace::PERIPHERAL_TO_MEMORY, 2 * N);
+    // Safety: the transfer is defined, and the caller upholds the buffers' lifetime.
+    unsafe { channel.enable() };
+    source.enable_source();
+
+    ReadPingPong {
+        channel,
+        source,
+        buffers,
+        next: 0,
+        held: None,
+    }
+}
+
+impl<'a, S, E, const N: usize> ReadPingPong<'a, S, E, N>
+where
+    S: Source<E>,
+    E: Element,
+{
+    const VALID_LENGTH: () = assert!(
+        N >= 1 && 2 * N <= MAX_LENGTH,
+        "Buffers must hold between 1 and 0x3FFF elements"
+    );
+
+    /// Wait for the next full buffer
+    ///
+    /// The future resolves with the buffer that the DMA controller just
+    /// filled. Calling `next_half` returns the previous buffer to the DMA
+    /// controller. See [`ReadPingPong`] for more information.
+    pub fn next_half(&mut self) -> NextHalf<'_, 'a, S, E, N> {
+        NextHalf {
+            transfer: Some(self),
+        }
+    }
+
+    /// Returns the index of the buffer that the channel is filling
+    fn active(&self) -> usize {
+        // Compare the low bits, like the hardware's 32-bit addresses.
+        let offset = (self.channel.destination_address() as u32)
+            .wrapping_sub(self.buffers.as_ptr() as u32) as usize;
+        (offset / (N * core::mem::size_of::<E>())).min(1)
+    }
+}
+
+impl<S, E, const N: usize> Drop for ReadPingPong<'_, S, E, N>
+where
+    S: Source<E>,
+    E: Element,
+{
+    fn drop(&mut self) {
+        self.source.disable_source();
+        stop_continuous(self.channel);
+    }
+}
+
+/// Waits for the next buffer of a [`ReadPingPong`]
+///
+/// Use [`ReadPingPong::next_half`] to create this future.
+pub struct NextHalf<'s, 'a, S, E, const N: usize>
+where
+    S: Source<E>,
+    E: Element,
+{
+    transfer: Option<&'s mut ReadPingPong<'a, S, E, N>>,
+}
+
+impl<'s, S, E, const N: usize> Future for NextHalf<'s, '_, S, E, N>
+where
+    S: Source<E>,
+    E: Element,
+{
+    type Output = Result<&'s mut [E; N], Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let transfer = self
+            .transfer
+            .as_mut()
+            .expect("NextHalf polled after it resolved");
+
+        if let Some(held) = transfer.held.take() {
+            // If the channel already moved on to the held buffer, it wrote
+            // over the buffer while the user held it. A channel that moved
+            // on twice is back on the other buffer; that goes unnoticed.
+            if transfer.active() == held {
+                return Poll::Ready(Err(Error::configuration(ConfigurationError::Overrun)));
+            }
+            #[cfg(feature = "cache")]
+            crate::cache::Region::of(&transfer.buffers[held]).before_dma_write();
+        }
+
+        transfer.channel.register_waker(cx.waker());
+        check_continuous(transfer.channel)?;
+        // The channel moves to the other buffer once it fills this one.
+        if transfer.active() == transfer.next {
+            return Poll::Pending;
+        }
+        // Order the address read before the reads of the received data.
+        core::sync::atomic::fence(core::sync::atomic::Ordering::Acquire);
+
+        let transfer = self.transfer.take().unwrap();
+        let index = transfer.next;
+        transfer.next = 1 - index;
+        transfer.held = Some(index);
+        let buffer = &mut transfer.buffers[index];
+        #[cfg(feature = "cache")]
+        // Safety: the DMA controller is done writing this buffer.
+        unsafe {
+            crate::cache::Region::of(buffer).after_dma_write()
+        };
+        Poll::Ready(Ok(buffer))
+    }
+}
@@ -0,0 +1,197 @@
+//! Reusable DMA channels
+//!
+//! A channel allocated by [`try_channel`](crate::Dma::try_channel) is allocated
+//! for the rest of the program. If your application opens and closes drivers,
+//! use a [`ChannelPool`] to recycle channels. Dropping a [`PooledChannel`]
+//! returns its channel to the pool.
+//!
+//! ```no_run
+//! use imxrt_dma::{pool::ChannelPool, Dma};
+//! # const DMA_PTR: *const () = core::ptr::null() as _;
+//! # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+//!
+//! static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+//! static POOL: ChannelPool<32> = ChannelPool::new(&DMA, 8..10);
+//!
+//! let first = POOL.acquire().unwrap();
+//! let second = POOL.acquire().unwrap();
+//! assert!(POOL.acquire().is_none());
+//!
+//! assert_eq!(first.channel(), 8);
+//! drop(first);
+//! assert_eq!(POOL.acquire().unwrap().channel(), 8);
+//! ```
+//!
+//! With the `"sim"` feature, a pool works with a simulated DMA controller. A
+//! channel that's returned to the pool is reset for the next owner.
+//!
+//! ```
+//! # #[cfg(feature = "sim")] {
+//! use imxrt_dma::{pool::ChannelPool, sim::Simulator};
+//!
+//! let sim = Simulator::<32>::new();
+//! let pool = ChannelPool::new(sim.dma(), 8..10);
+//!
+//! let mut first = pool.acquire().unwrap();
+//! let second = pool.acquire().unwrap();
+//! assert!(pool.acquire().is_none());
+//! assert!(sim.dma().try_channel(8).is_none());
+//!
+//! first.set_interrupt_on_completion(true);
+//! drop(first);
+//! let mut again = pool.acquire().unwrap();
+//! assert_eq!(again.channel(), 8);
+//! assert!(!again.is_interrupt_on_completion());
+//!
+//! // The pool only releases the channel that it acquired.
+//! let mut other = sim.dma().try_channel(3).unwrap();
+//! core::mem::swap(&mut *again, &mut other);
+//! drop(again);
+//! assert!(sim.dma().try_channel(3).is_none());
+//! # drop(second);
+//! # }
+//! ```
+
+use crate::{
+    channel::{Channel, Configuration},
+    Dma,
+};
+
+use core::ops::{Deref, DerefMut, Range};
+
+/// A pool of reusable DMA channels
+///
+/// The pool hands out channels from a range of channel numbers. It shares
+/// allocation tracking with [`try_channel`](crate::Dma::try_channel) and
+/// [`channels`](crate::Dma::channels). If one of those methods allocates a channel
+/// in the pool's range, the pool can't acquire that channel. Likewise, a channel
+/// acquired from the pool can't be allocated by those methods until it's returned.
+///
+/// Like `try_channel`, the pool can't detect channels created by the unsafe
+/// [`channel`](crate::Dma::channel) method.
+pub struct ChannelPool<
+    const CHANNELS: usize,
+    const WAKERS: usize = CHANNELS,
+    const WATCHERS: usize = 1,
+> {
+    dma: &'static Dma<CHANNELS, WAKERS, WATCHERS>,
+    channels: Range<usize>,
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    ChannelPool<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Create a pool that manages the `channels` of `dma`
+    ///
+    /// Channel numbers in `channels` that are greater than or equal to `CHANNELS`
+    /// are never acquired.
+    pub const fn new(
+        dma: &'static Dma<CHANNELS, WAKERS, WATCHERS>,
+        channels: Range<usize>,
+    ) -> Self {
+        Self { dma, channels }
+    }
+
+    /// Acquire any available channel from the pool
+    ///
+    /// Returns `None` if every channel in the pool is in use.
+    pub fn acquire(&self) -> Option<PooledChannel<CHANNELS, WAKERS, WATCHERS>> {
+        self.channels
+            .clone()
+            .find_map(|index| self.acquire_channel(index))
+    }
+
+    /// Acquire any available channel that can work together with `partner`
+    ///
+    /// Returns `None` if `partner` belongs to a different DMA controller than
+    /// the pool, or if every channel in the pool is in use. Use this to find the
+    /// second channel of a [`full_duplex`](crate::peripheral::full_duplex)
+    /// transfer, or a [minor loop link](Channel::set_minor_loop_link).
+    pub fn acquire_partner(
+        &self,
+        partner: &Channel,
+    ) -> Option<PooledChannel<CHANNELS, WAKERS, WATCHERS>> {
+        if !partner.is_managed_by(self.dma.controller) {
+            return None;
+        }
+        self.acquire()
+    }
+
+    /// Acquire the channel described by `index`
+    ///
+    /// Returns `None` if `index` isn't in the pool, or if the channel is in use.
+    pub fn acquire_channel(
+        &self,
+        index: usize,
+    ) -> Option<PooledChannel<CHANNELS, WAKERS, WATCHERS>> {
+        if !self.channels.contains(&index) {
+            return None;
+        }
+        let channel = self.dma.try_channel(index)?;
+        Some(PooledChannel {
+            channel,
+            index,
+            dma: self.dma,
+        })
+    }
+}
+
+/// A DMA channel acquired from a [`ChannelPool`]
+///
+/// `PooledChannel` dereferences to a [`Channel`], so you can use it anywhere that
+/// you'd use a `Channel`. When it's dropped, the channel is disabled, its flags
+/// and transfer control descriptor are reset, its multiplexer configuration is
+/// turned off, and it's returned to the pool.
+///
+/// The pool resets and releases the channel that it acquired, even if you swap
+/// a different `Channel` into the `PooledChannel`.
+pub struct PooledChannel<
+    const CHANNELS: usize,
+    const WAKERS: usize = CHANNELS,
+    const WATCHERS: usize = 1,
+> {
+    channel: Channel,
+    /// The channel acquired from `dma`, independent of `channel`.
+    index: usize,
+    dma: &'static Dma<CHANNELS, WAKERS, WATCHERS>,
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize> Deref
+    for PooledChannel<CHANNELS, WAKERS, WATCHERS>
+{
+    type Target = Channel;
+    fn deref(&self) -> &Channel {
+        &self.channel
+    }
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize> DerefMut
+    for PooledChannel<CHANNELS, WAKERS, WATCHERS>
+{
+    fn deref_mut(&mut self) -> &mut Channel {
+        &mut self.channel
+    }
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize> Drop
+    for PooledChannel<CHANNELS, WAKERS, WATCHERS>
+{
+    fn drop(&mut self) {
+        // Safety: the pool allocated this channel, and it's not released
+        // until the reset is complete. A swapped-in channel isn't ours to reset.
+        let channel = &mut unsafe { self.dma.channel(self.index) };
+        channel.disable();
+        // Reset the channel with the clock enabled, then let it go.
+        channel.claim_clock();
+        channel.set_channel_configuration(Configuration::Off);
+        channel.reset();
+        channel.clear_complete();
+        channel.clear_error();
+        channel.clear_interrupt();
+        channel.clear_waker();
+        #[cfg(feature = "clock-gating")]
+        channel.release_clock();
+        // The next owner can't see this channel until it's released.
+        self.dma.release(self.index);
+    }
+}
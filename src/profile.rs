@@ -0,0 +1,232 @@
+//! Transfer profiling with the DWT cycle counter
+//!
+//! Use these measurements to tune bandwidth control, burst sizes, and
+//! priorities with numbers, instead of guesses. A [`Probe`] measures two
+//! intervals of one transfer:
+//!
+//! - the *setup latency*, from [`Probe::start`] until the transfer future has
+//!   enabled the channel. This covers the channel configuration, and the
+//!   first poll of the future.
+//! - the *transfer time*, from the end of the setup until the future resolves.
+//!
+//! [`Statistics`] accumulates many measurements, and computes the achieved
+//! throughput.
+//!
+//! ```no_run
+//! use imxrt_dma::{memcpy, profile::{Probe, Statistics}};
+//! # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+//! const CORE_HZ: u32 = 600_000_000;
+//!
+//! # async fn f() -> imxrt_dma::Result<()> {
+//! let mut channel = DMA.try_channel(7).unwrap();
+//! let source = [0u32; 256];
+//! let mut destination = [0u32; 256];
+//! let mut statistics = Statistics::default();
+//!
+//! let probe = Probe::start();
+//! let transfer = memcpy::memcpy(&source, &mut destination, &mut channel);
+//! let (result, measurement) = probe
+//!     .measure(core::mem::size_of_val(&source), transfer)
+//!     .await;
+//! result?;
+//!
+//! statistics.record(&measurement);
+//! let throughput = statistics.bytes_per_second(CORE_HZ);
+//! # Ok(()) }
+//! ```
+//!
+//! Measurements use the DWT cycle counter, which you must enable before you
+//! measure. See `cortex_m::peripheral::DWT::enable_cycle_counter`. The counter
+//! is 32 bits, so a single interval must be shorter than 2^32 cycles. The
+//! transfer time ends when the future is polled after the transfer completes;
+//! a slow executor, or a missing DMA interrupt, inflates the transfer time.
+//!
+//! On a hosted target, there's no cycle counter, and [`cycles`] always
+//! returns zero.
+//!
+//! This module requires the `"profile"` feature.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Returns the current DWT cycle count
+///
+/// Returns zero on a hosted target.
+#[inline]
+pub fn cycles() -> u32 {
+    #[cfg(target_os = "none")]
+    {
+        // Safety: atomic read of a read-only (for us) register.
+        unsafe { (*cortex_m::peripheral::DWT::PTR).cyccnt.read() }
+    }
+    #[cfg(not(target_os = "none"))]
+    {
+        0
+    }
+}
+
+/// Measures one transfer
+///
+/// Start the probe before you set up the transfer. Then, use
+/// [`measure`](Self::measure) to wrap the transfer future.
+#[derive(Debug, Clone, Copy)]
+pub struct Probe {
+    start: u32,
+}
+
+impl Probe {
+    /// Start measuring the setup latency
+    pub fn start() -> Self {
+        Probe { start: cycles() }
+    }
+
+    /// Measure `future`, a transfer that moves `bytes` bytes
+    ///
+    /// The returned future resolves with the transfer's output, and the
+    /// measurement.
+    pub fn measure<F: Future>(self, bytes: usize, future: F) -> Measure<F> {
+        Measure {
+            future,
+            start: self.start,
+            setup_done: None,
+            bytes,
+        }
+    }
+}
+
+/// A transfer future that's being measured
+///
+/// Use [`Probe::measure`] to create this future.
+pub struct Measure<F> {
+    future: F,
+    start: u32,
+    /// The cycle count after the first poll of `future`
+    setup_done: Option<u32>,
+    bytes: usize,
+}
+
+impl<F: Future> Future for Measure<F> {
+    type Output = (F::Output, Measurement);
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: we don't move the future out of the measurement.
+        let this = unsafe { self.get_unchecked_mut() };
+        // Safety: the future is pinned inside of this pinned measurement.
+        let poll = unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx);
+        let now = cycles();
+        let setup_done = *this.setup_done.get_or_insert(now);
+        match poll {
+            Poll::Ready(output) => Poll::Ready((
+                output,
+                Measurement {
+                    bytes: this.bytes,
+                    setup_cycles: setup_done.wrapping_sub(this.start),
+                    transfer_cycles: now.wrapping_sub(setup_done),
+                },
+            )),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The measurement of one transfer
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Measurement {
+    /// The number of bytes moved by the transfer
+    pub bytes: usize,
+    /// Cycles from the start of the probe until the channel was enabled
+    pub setup_cycles: u32,
+    /// Cycles from the end of the setup until the transfer resolved
+    pub transfer_cycles: u32,
+}
+
+impl Measurement {
+    /// Returns the achieved throughput, given the core clock frequency
+    ///
+    /// Returns `None` if the transfer time is zero.
+    pub fn bytes_per_second(&self, core_hz: u32) -> Option<u64> {
+        throughput(self.bytes as u64, self.transfer_cycles as u64, core_hz)
+    }
+}
+
+/// Statistics about many measured transfers
+///
+/// [`record`](Self::record) each [`Measurement`]. Reset the statistics by
+/// replacing them with the default.
+///
+/// ```
+/// use imxrt_dma::profile::{Measurement, Statistics};
+///
+/// let mut statistics = Statistics::default();
+/// statistics.record(&Measurement { bytes: 1024, setup_cycles: 200, transfer_cycles: 600 });
+/// statistics.record(&Measurement { bytes: 1024, setup_cycles: 400, transfer_cycles: 1000 });
+///
+/// assert_eq!(statistics.average_setup_cycles(), Some(300));
+/// assert_eq!(statistics.max_transfer_cycles, 1000);
+/// // 2048 bytes in 1600 cycles at 100 MHz.
+/// assert_eq!(statistics.bytes_per_second(100_000_000), Some(128_000_000));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Statistics {
+    /// The number of recorded transfers
+    pub transfers: u32,
+    /// The total number of bytes moved
+    pub bytes: u64,
+    /// The total setup latency, in cycles
+    pub setup_cycles: u64,
+    /// The total transfer time, in cycles
+    pub transfer_cycles: u64,
+    /// The largest setup latency, in cycles
+    pub max_setup_cycles: u32,
+    /// The largest transfer time, in cycles
+    pub max_transfer_cycles: u32,
+}
+
+impl Statistics {
+    /// Add `measurement` to the statistics
+    pub fn record(&mut self, measurement: &Measurement) {
+        self.transfers = self.transfers.wrapping_add(1);
+        self.bytes = self.bytes.wrapping_add(measurement.bytes as u64);
+        self.setup_cycles = self
+            .setup_cycles
+            .wrapping_add(measurement.setup_cycles as u64);
+        self.transfer_cycles = self
+            .transfer_cycles
+            .wrapping_add(measurement.transfer_cycles as u64);
+        self.max_setup_cycles = self.max_setup_cycles.max(measurement.setup_cycles);
+        self.max_transfer_cycles = self.max_transfer_cycles.max(measurement.transfer_cycles);
+    }
+
+    /// Returns the average setup latency, in cycles
+    ///
+    /// Returns `None` if there are no recorded transfers.
+    pub fn average_setup_cycles(&self) -> Option<u64> {
+        self.setup_cycles.checked_div(self.transfers as u64)
+    }
+
+    /// Returns the average transfer time, in cycles
+    ///
+    /// Returns `None` if there are no recorded transfers.
+    pub fn average_transfer_cycles(&self) -> Option<u64> {
+        self.transfer_cycles.checked_div(self.transfers as u64)
+    }
+
+    /// Returns the achieved throughput across all recorded transfers, given
+    /// the core clock frequency
+    ///
+    /// The throughput only considers the transfer time, not the setup latency.
+    /// Returns `None` if the total transfer time is zero.
+    pub fn bytes_per_second(&self, core_hz: u32) -> Option<u64> {
+        throughput(self.bytes, self.transfer_cycles, core_hz)
+    }
+}
+
+/// Compute bytes per second, saturating on overflow
+fn throughput(bytes: u64, cycles: u64, core_hz: u32) -> Option<u64> {
+    let rate = (bytes as u128 * core_hz as u128).checked_div(cycles as u128)?;
+    Some(rate.min(u64::MAX as u128) as u64)
+}
@@ -0,0 +1,97 @@
+//! Progress notifications for long transfers
+
+use crate::{channel::Channel, Dma};
+use core::{
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// The progress of a channel's transfer
+///
+/// [`on_interrupt`](crate::Dma::on_interrupt) passes a `Progress` to the
+/// [progress handler](crate::Dma::set_progress_handler).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Progress {
+    completed: u16,
+    total: u16,
+}
+
+impl Progress {
+    /// Returns the number of major loop iterations that the channel completed
+    pub const fn completed(&self) -> u16 {
+        self.completed
+    }
+    /// Returns the number of major loop iterations in the transfer
+    pub const fn total(&self) -> u16 {
+        self.total
+    }
+    /// Returns `true` if the channel completed its major loop
+    pub const fn is_complete(&self) -> bool {
+        self.completed == self.total
+    }
+}
+
+/// The user's progress handler
+///
+/// A `fn(usize, Progress)`, or null if there's no handler. Atomic, so that
+/// `on_interrupt` doesn't need a critical section to find the handler.
+pub(crate) type SharedProgressHandler = AtomicPtr<()>;
+#[allow(clippy::declare_interior_mutable_const)] // See NO_WAKER
+pub(crate) const NO_PROGRESS_HANDLER: SharedProgressHandler = AtomicPtr::new(ptr::null_mut());
+
+/// Call the progress handler, if there is one, for `channel`'s interrupt
+#[inline(always)]
+pub(crate) fn notify(handler: &SharedProgressHandler, channel: &Channel) {
+    let handler = handler.load(Ordering::Acquire);
+    if handler.is_null() {
+        return;
+    }
+    // Safety: the only non-null values come from set_progress_handler.
+    let handler = unsafe { core::mem::transmute::<*mut (), fn(usize, Progress)>(handler) };
+    let total = channel.beginning_transfer_iterations();
+    let completed = if channel.is_complete() {
+        // The hardware reloads the current iterations when the major loop completes.
+        total
+    } else {
+        total.saturating_sub(channel.current_transfer_iterations())
+    };
+    handler(channel.channel(), Progress { completed, total });
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    Dma<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Set a handler for transfer progress
+    ///
+    /// [`on_interrupt`](Self::on_interrupt) calls `handler` with the channel
+    /// number and the transfer's progress whenever a channel interrupts without
+    /// an error. Use this to kick a watchdog during transfers that take longer
+    /// than the watchdog's timeout. `handler` runs in the interrupt handler
+    /// that called `on_interrupt`, so keep it short.
+    ///
+    /// A channel interrupts when its major loop is
+    /// [half complete](crate::channel::Channel::set_interrupt_on_half), and
+    /// when it [completes](crate::channel::Channel::set_interrupt_on_completion).
+    /// For more frequent notifications, split a long transfer into a chain of
+    /// [scatter-gather images](crate::armed::ArmedTcd::chain) that each
+    /// interrupt on completion.
+    ///
+    /// `None` removes the handler. There's no handler by default.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::{Dma, Progress};
+    /// static DMA: Dma<32> = // Handle to DMA driver.
+    /// # unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// fn on_dma_progress(channel: usize, progress: Progress) {
+    ///     // Kick the watchdog...
+    /// }
+    ///
+    /// DMA.set_progress_handler(Some(on_dma_progress));
+    /// ```
+    pub fn set_progress_handler(&self, handler: Option<fn(usize, Progress)>) {
+        let handler = handler.map_or(ptr::null_mut(), |handler| handler as *mut ());
+        self.progress_handler.store(handler, Ordering::Release);
+    }
+}
@@ -13,10 +13,132 @@
 
 pub mod dma;
 pub mod dmamux;
+#[cfg(feature = "edma34")]
+pub mod edma34;
 pub mod tcd;
 
 pub use ral_registers::{modify_reg, read_reg, write_reg};
-use ral_registers::{RORegister, RWRegister, WORegister};
+
+use core::cell::UnsafeCell;
+
+//
+// Register access
+//
+// The register types below are drop-in replacements for the `ral_registers`
+// types; the RAL macros only need their `read` and `write` methods. Every
+// access goes through `Bus`. On hardware, that's volatile MMIO. The `"sim"`
+// feature, and only that feature, selects the simulator's bus. It also
+// observes writes, so that tests can check the exact register sequence. With
+// the command log, both buses log writes.
+//
+
+/// A value held by a register
+pub trait Value: Copy {
+    /// Returns the value's bits, zero-extended
+    // Only the simulator and the command log observe values.
+    #[cfg_attr(not(any(feature = "sim", feature = "command-log")), allow(dead_code))]
+    fn bits(self) -> u32;
+}
+
+macro_rules! impl_value {
+    ($($ty:ty => $unsigned:ty),*) => {
+        $(impl Value for $ty {
+            fn bits(self) -> u32 {
+                self as $unsigned as u32
+            }
+        })*
+    };
+}
+
+impl_value!(u8 => u8, u16 => u16, u32 => u32, i16 => u16, i32 => u32);
+
+/// Performs register reads and writes
+pub trait Access {
+    /// Read the register at `register`
+    ///
+    /// # Safety
+    ///
+    /// `register` must be valid for reads.
+    unsafe fn read<T: Value>(register: *const T) -> T;
+    /// Write `value` to the register at `register`
+    ///
+    /// # Safety
+    ///
+    /// `register` must be valid for writes.
+    unsafe fn write<T: Value>(register: *mut T, value: T);
+}
+
+/// Memory-mapped IO, using volatile accesses
+pub struct Mmio;
+
+impl Access for Mmio {
+    #[inline(always)]
+    unsafe fn read<T: Value>(register: *const T) -> T {
+        // Safety: caller ensures the register is valid.
+        unsafe { register.read_volatile() }
+    }
+    #[inline(always)]
+    unsafe fn write<T: Value>(register: *mut T, value: T) {
+        // Safety: caller ensures the register is valid.
+        unsafe { register.write_volatile(value) };
+        #[cfg(feature = "command-log")]
+        crate::command_log::record(register as usize, core::mem::size_of::<T>(), value.bits());
+    }
+}
+
+/// The register access used by the driver
+#[cfg(not(feature = "sim"))]
+pub type Bus = Mmio;
+/// The register access used by the driver
+#[cfg(feature = "sim")]
+pub type Bus = crate::sim::Bus;
+
+/// A read-write register
+#[repr(transparent)]
+pub struct RWRegister<T> {
+    register: UnsafeCell<T>,
+}
+
+impl<T: Value> RWRegister<T> {
+    #[inline(always)]
+    pub fn read(&self) -> T {
+        // Safety: the register is valid for the life of the reference.
+        unsafe { Bus::read(self.register.get()) }
+    }
+    #[inline(always)]
+    pub fn write(&self, value: T) {
+        // Safety: the register is valid for the life of the reference.
+        unsafe { Bus::write(self.register.get(), value) }
+    }
+}
+
+/// A read-only register
+#[repr(transparent)]
+pub struct RORegister<T> {
+    register: UnsafeCell<T>,
+}
+
+impl<T: Value> RORegister<T> {
+    #[inline(always)]
+    pub fn read(&self) -> T {
+        // Safety: the register is valid for the life of the reference.
+        unsafe { Bus::read(self.register.get()) }
+    }
+}
+
+/// A write-only register
+#[repr(transparent)]
+pub struct WORegister<T> {
+    register: UnsafeCell<T>,
+}
+
+impl<T: Value> WORegister<T> {
+    #[inline(always)]
+    pub fn write(&self, value: T) {
+        // Safety: the register is valid for the life of the reference.
+        unsafe { Bus::write(self.register.get(), value) }
+    }
+}
 
 //
 // Helper types for static memory
@@ -1,5 +1,7 @@
 //! DMA register blocks and fields
 
+#![allow(non_snake_case, non_upper_case_globals)]
+
 use super::{tcd, RORegister, RWRegister, WORegister};
 
 use core::ops::Index;
@@ -75,3 +77,113 @@ impl Index<usize> for ChannelPriorityRegisters {
         &self.0[idx]
     }
 }
+
+pub mod CR {
+    /// Enable Debug
+    pub mod EDBG {
+        pub const offset: u32 = 1;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Enable Round Robin Channel Arbitration
+    pub mod ERCA {
+        pub const offset: u32 = 2;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Enable Round Robin Group Arbitration
+    pub mod ERGA {
+        pub const offset: u32 = 3;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Halt On Error
+    pub mod HOE {
+        pub const offset: u32 = 4;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Halt DMA Operations
+    pub mod HALT {
+        pub const offset: u32 = 5;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Enable Minor Loop Mapping
+    pub mod EMLM {
+        pub const offset: u32 = 7;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Channel Group 0 Priority
+    pub mod GRP0PRI {
+        pub const offset: u32 = 8;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Channel Group 1 Priority
+    pub mod GRP1PRI {
+        pub const offset: u32 = 10;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Error Cancel Transfer
+    pub mod ECX {
+        pub const offset: u32 = 16;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Cancel Transfer
+    pub mod CX {
+        pub const offset: u32 = 17;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+}
+
+pub mod DCHPRI {
+    /// Channel n Arbitration Priority
+    pub mod CHPRI {
+        pub const offset: u8 = 0;
+        pub const mask: u8 = 0xF << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Disable Preempt Ability
+    pub mod DPA {
+        pub const offset: u8 = 6;
+        pub const mask: u8 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Enable Channel Preemption
+    pub mod ECP {
+        pub const offset: u8 = 7;
+        pub const mask: u8 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+}
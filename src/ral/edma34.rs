@@ -0,0 +1,207 @@
+//! eDMA3 and eDMA4 register blocks
+//!
+//! Newer DMA controllers, like those in the i.MX RT 1180, have a
+//! management page, followed by one page of registers per channel.
+//! Each channel page holds the channel's control and status registers,
+//! its request source, and its TCD. The TCD layout matches the eDMA TCD.
+
+#![allow(non_snake_case, non_upper_case_globals)]
+
+use super::{tcd, RORegister, RWRegister};
+
+/// Management page registers
+#[repr(C)]
+pub struct RegisterBlock {
+    /// Management Page Control Register
+    pub CSR: RWRegister<u32>,
+    /// Management Page Error Status Register
+    pub ES: RORegister<u32>,
+}
+
+/// Per-channel registers
+#[repr(C)]
+pub struct ChannelRegisterBlock {
+    /// Channel Control and Status Register
+    pub CH_CSR: RWRegister<u32>,
+    /// Channel Error Status Register
+    pub CH_ES: RWRegister<u32>,
+    /// Channel Interrupt Status Register
+    pub CH_INT: RWRegister<u32>,
+    /// Channel System Bus Register
+    pub CH_SBR: RWRegister<u32>,
+    /// Channel Priority Register
+    pub CH_PRI: RWRegister<u32>,
+    /// Channel Multiplexor Configuration
+    pub CH_MUX: RWRegister<u32>,
+    _reserved0: [u32; 2],
+    /// Transfer Control Descriptor
+    pub TCD: tcd::RegisterBlock,
+}
+
+/// Offset of channel 0's page from the start of the management page
+pub const CHANNEL_OFFSET: usize = 0x1_0000;
+/// Distance between eDMA3 channel pages
+pub const EDMA3_CHANNEL_STRIDE: usize = 0x1_0000;
+/// Distance between eDMA4 channel pages
+pub const EDMA4_CHANNEL_STRIDE: usize = 0x8000;
+/// Offset of the eDMA3 hardware request status register
+pub const EDMA3_HRS_OFFSET: usize = 0x0C;
+/// Offset of the eDMA4 hardware request status registers, low then high
+pub const EDMA4_HRS_OFFSET: usize = 0x10;
+
+pub mod CSR {
+    /// Enable Debug
+    pub mod EDBG {
+        pub const offset: u32 = 1;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Enable Round Robin Channel Arbitration
+    pub mod ERCA {
+        pub const offset: u32 = 2;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Halt After Error
+    pub mod HAE {
+        pub const offset: u32 = 4;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Halt DMA Operations
+    pub mod HALT {
+        pub const offset: u32 = 5;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Error Cancel Transfer
+    pub mod ECX {
+        pub const offset: u32 = 8;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Cancel Transfer
+    pub mod CX {
+        pub const offset: u32 = 9;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+}
+
+pub mod ES {
+    /// Transfer Cancelled
+    pub mod ECX {
+        pub const offset: u32 = 8;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Error Channel Number or Cancelled Channel Number
+    pub mod ERRCHN {
+        pub const offset: u32 = 24;
+        pub const mask: u32 = 0x7F << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+}
+
+pub mod CH_CSR {
+    /// Enable DMA Request
+    pub mod ERQ {
+        pub const offset: u32 = 0;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Enable Asynchronous DMA Request in Stop Mode
+    pub mod EARQ {
+        pub const offset: u32 = 2;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Channel Done (write 1 to clear)
+    pub mod DONE {
+        pub const offset: u32 = 30;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Channel Active
+    pub mod ACTIVE {
+        pub const offset: u32 = 31;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+}
+
+pub mod CH_ES {
+    /// Error in channel (write 1 to clear)
+    pub mod ERR {
+        pub const offset: u32 = 31;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+}
+
+pub mod CH_PRI {
+    /// Arbitration Priority Level
+    pub mod APL {
+        pub const offset: u32 = 0;
+        pub const mask: u32 = 0x7 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Disable Preempt Ability
+    pub mod DPA {
+        pub const offset: u32 = 30;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Enable Channel Preemption
+    pub mod ECP {
+        pub const offset: u32 = 31;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+}
+
+pub mod CH_MUX {
+    /// Service Request Source
+    pub mod SRC {
+        pub const offset: u32 = 0;
+        pub const mask: u32 = 0x7F << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+}
+
+const _STATIC_ASSERT_CHANNEL_TCD_OFFSET: [u32; 1] =
+    [0; (0x20 == core::mem::offset_of!(ChannelRegisterBlock, TCD)) as usize];
@@ -23,6 +23,61 @@ pub struct RegisterBlock {
     pub BITER: RWRegister<u16>,
 }
 
+/// A copy of a TCD's registers
+///
+/// Field names match the transfer control descriptor registers described
+/// in your chip's reference manual.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Snapshot {
+    /// Source address
+    pub SADDR: u32,
+    /// Signed source address offset
+    pub SOFF: i16,
+    /// Destination transfer attributes
+    pub DATTR: u8,
+    /// Source transfer attributes
+    pub SATTR: u8,
+    /// Minor loop byte count, and minor loop offsets
+    pub NBYTES: u32,
+    /// Last source address adjustment
+    pub SLAST: i32,
+    /// Destination address
+    pub DADDR: u32,
+    /// Signed destination address offset
+    pub DOFF: i16,
+    /// Current major loop iteration count
+    pub CITER: u16,
+    /// Last destination address adjustment, or scatter / gather address
+    pub DLAST_SGA: i32,
+    /// Control and status
+    pub CSR: u16,
+    /// Beginning major loop iteration count
+    pub BITER: u16,
+}
+
+/// Returns the name of the TCD register at `offset` bytes, if there is one
+///
+/// Names match [`Snapshot`]'s fields.
+#[cfg(any(feature = "sim", feature = "command-log"))]
+pub fn register_name(offset: usize) -> Option<&'static str> {
+    Some(match offset {
+        0 => "SADDR",
+        4 => "SOFF",
+        6 => "DATTR",
+        7 => "SATTR",
+        8 => "NBYTES",
+        12 => "SLAST",
+        16 => "DADDR",
+        20 => "DOFF",
+        22 => "CITER",
+        24 => "DLAST_SGA",
+        28 => "CSR",
+        30 => "BITER",
+        _ => return None,
+    })
+}
+
 const _STATIC_ASSERT_TCD_32_BYTES: [u32; 1] =
     [0; (32 == core::mem::size_of::<RegisterBlock>()) as usize];
 
@@ -43,6 +98,44 @@ impl RegisterBlock {
         self.CSR.write(0);
         self.BITER.write(0);
     }
+
+    /// Copy all of the TCD's registers
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            SADDR: self.SADDR.read(),
+            SOFF: self.SOFF.read(),
+            DATTR: self.DATTR.read(),
+            SATTR: self.SATTR.read(),
+            NBYTES: self.NBYTES.read(),
+            SLAST: self.SLAST.read(),
+            DADDR: self.DADDR.read(),
+            DOFF: self.DOFF.read(),
+            CITER: self.CITER.read(),
+            DLAST_SGA: self.DLAST_SGA.read(),
+            CSR: self.CSR.read(),
+            BITER: self.BITER.read(),
+        }
+    }
+
+    /// Write all of the TCD's registers from a snapshot
+    ///
+    /// The status flags in CSR (ACTIVE, DONE) and the START bit aren't restored.
+    pub fn restore(&self, snapshot: &Snapshot) {
+        self.CSR.write(0);
+        self.SADDR.write(snapshot.SADDR);
+        self.SOFF.write(snapshot.SOFF);
+        self.DATTR.write(snapshot.DATTR);
+        self.SATTR.write(snapshot.SATTR);
+        self.NBYTES.write(snapshot.NBYTES);
+        self.SLAST.write(snapshot.SLAST);
+        self.DADDR.write(snapshot.DADDR);
+        self.DOFF.write(snapshot.DOFF);
+        self.BITER.write(snapshot.BITER);
+        self.CITER.write(snapshot.CITER);
+        self.DLAST_SGA.write(snapshot.DLAST_SGA);
+        self.CSR
+            .write(snapshot.CSR & !(CSR::ACTIVE::mask | CSR::DONE::mask | CSR::START::mask));
+    }
 }
 
 mod ATTR {
@@ -85,6 +178,20 @@ pub mod SATTR {
 
 pub mod CSR {
 
+    /// Channel Start
+    pub mod START {
+        /// Offset (0 bits)
+        pub const offset: u16 = 0;
+        /// Mask (1 bit: 1 << 0)
+        pub const mask: u16 = 1 << offset;
+        /// Read-only values (empty)
+        pub mod R {}
+        /// Write-only values (empty)
+        pub mod W {}
+        /// Read-write values (empty)
+        pub mod RW {}
+    }
+
     /// Enable an interrupt when major iteration count completes.
     pub mod INTMAJOR {
         /// Offset (1 bits)
@@ -99,6 +206,20 @@ pub mod CSR {
         pub mod RW {}
     }
 
+    /// Enable an interrupt when major counter is half complete.
+    pub mod INTHALF {
+        /// Offset (2 bits)
+        pub const offset: u16 = 2;
+        /// Mask (1 bit: 1 << 2)
+        pub const mask: u16 = 1 << offset;
+        /// Read-only values (empty)
+        pub mod R {}
+        /// Write-only values (empty)
+        pub mod W {}
+        /// Read-write values
+        pub mod RW {}
+    }
+
     /// Disable Request
     pub mod DREQ {
         /// Offset (3 bits)
@@ -127,6 +248,20 @@ pub mod CSR {
         pub mod RW {}
     }
 
+    /// Enable Scatter/Gather Processing
+    pub mod ESG {
+        /// Offset (4 bits)
+        pub const offset: u16 = 4;
+        /// Mask (1 bit: 1 << 4)
+        pub const mask: u16 = 1 << offset;
+        /// Read-only values (empty)
+        pub mod R {}
+        /// Write-only values (empty)
+        pub mod W {}
+        /// Read-write values (empty)
+        pub mod RW {}
+    }
+
     /// Bandwidth Control
     pub mod BWC {
         /// Offset (14 bits)
@@ -166,6 +301,41 @@ pub mod CSR {
     }
 }
 
+pub mod NBYTES {
+    /// Source Minor Loop Offset Enable
+    pub mod SMLOE {
+        pub const offset: u32 = 31;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Destination Minor Loop Offset Enable
+    pub mod DMLOE {
+        pub const offset: u32 = 30;
+        pub const mask: u32 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Minor Loop Offset
+    pub mod MLOFF {
+        pub const offset: u32 = 10;
+        pub const mask: u32 = 0xF_FFFF << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Minor Byte Transfer Count, when a minor loop offset is enabled
+    pub mod NBYTES {
+        pub const offset: u32 = 0;
+        pub const mask: u32 = 0x3FF << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+}
+
 pub mod CITER {
     /// Current Major Iteration Count
     pub mod CITER {
@@ -175,6 +345,30 @@ pub mod CITER {
         pub mod W {}
         pub mod RW {}
     }
+    /// Current Major Iteration Count, when ELINK is set
+    pub mod CITER_ELINK {
+        pub const offset: u16 = 0;
+        pub const mask: u16 = 0x1ff << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Minor Loop Link Channel Number, when ELINK is set
+    pub mod LINKCH {
+        pub const offset: u16 = 9;
+        pub const mask: u16 = 0x1f << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Enable channel-to-channel linking on minor loop complete
+    pub mod ELINK {
+        pub const offset: u16 = 15;
+        pub const mask: u16 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
 }
 
 pub mod BITER {
@@ -186,6 +380,30 @@ pub mod BITER {
         pub mod W {}
         pub mod RW {}
     }
+    /// Starting Major Iteration Count, when ELINK is set
+    pub mod BITER_ELINK {
+        pub const offset: u16 = 0;
+        pub const mask: u16 = 0x1ff << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Minor Loop Link Channel Number, when ELINK is set
+    pub mod LINKCH {
+        pub const offset: u16 = 9;
+        pub const mask: u16 = 0x1f << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
+    /// Enable channel-to-channel linking on minor loop complete
+    pub mod ELINK {
+        pub const offset: u16 = 15;
+        pub const mask: u16 = 1 << offset;
+        pub mod R {}
+        pub mod W {}
+        pub mod RW {}
+    }
 }
 
 /// Throttles the amount of bus bandwidth consumed by the eDMA
@@ -196,6 +414,7 @@ pub mod BITER {
 /// Some stalls may not occur to minimize startup latency. See the
 /// reference manual for more details.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u16)]
 pub enum BandwidthControl {
     /// DMA engine stalls for 4 cycles after each R/W.
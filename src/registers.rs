@@ -0,0 +1,49 @@
+//! Register block adapters
+//!
+//! These traits describe "something that knows where the registers are,"
+//! without depending on a specific `imxrt-ral` release. A HAL implements
+//! the traits for its own peripheral types, which usually hold a RAL
+//! instance. Then, the HAL can use [`Dma::from_registers`](crate::Dma::from_registers)
+//! to create a driver.
+
+/// Provides the address of an eDMA controller's registers
+///
+/// Raw pointers implement this trait, so you can use a RAL's register block
+/// pointer directly.
+pub trait DmaRegisters {
+    /// Returns a pointer to the start of the DMA controller register block
+    fn dma_registers(&self) -> *const ();
+}
+
+/// Provides the address of a DMAMUX's registers
+///
+/// Raw pointers implement this trait, so you can use a RAL's register block
+/// pointer directly.
+pub trait DmamuxRegisters {
+    /// Returns a pointer to the start of the DMA multiplexer register block
+    fn dmamux_registers(&self) -> *const ();
+}
+
+impl<T> DmaRegisters for *const T {
+    fn dma_registers(&self) -> *const () {
+        self.cast()
+    }
+}
+
+impl<T> DmamuxRegisters for *const T {
+    fn dmamux_registers(&self) -> *const () {
+        self.cast()
+    }
+}
+
+impl<T: DmaRegisters + ?Sized> DmaRegisters for &T {
+    fn dma_registers(&self) -> *const () {
+        (**self).dma_registers()
+    }
+}
+
+impl<T: DmamuxRegisters + ?Sized> DmamuxRegisters for &T {
+    fn dmamux_registers(&self) -> *const () {
+        (**self).dmamux_registers()
+    }
+}
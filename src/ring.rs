@@ -0,0 +1,740 @@
+//! Circular DMA buffers
+//!
+//! The DMA controller can wrap a source or destination address within a
+//! circular buffer using the TCD\[SMOD\] and TCD\[DMOD\] fields. The hardware
+//! requires that the buffer's size is a power of two, and that the buffer
+//! is aligned to its size. [`set_source_circular_buffer`] and
+//! [`set_destination_circular_buffer`] check these requirements at runtime,
+//! and panic if they're not met.
+//!
+//! [`DmaRingBuffer`] meets these requirements at compile time. Its length is
+//! a power of two, and its alignment matches its size in bytes. A length that
+//! isn't a power of two, or that's too large, fails to compile. It's also
+//! available as [`CircularBuffer`]. [`set_as_source`](DmaRingBuffer::set_as_source)
+//! and [`set_as_destination`](DmaRingBuffer::set_as_destination) program a
+//! channel's modulo from the ring's type, without any runtime checks.
+//!
+//! Use [`read`](DmaRingBuffer::read) and [`write`](DmaRingBuffer::write) to
+//! copy elements out of, or into, the ring. Both methods wrap around the end of
+//! the buffer, just like the DMA controller.
+//!
+//! # Cacheable memory
+//!
+//! If the ring is in cacheable memory, like OCRAM or SDRAM on an i.MX RT 1060,
+//! enable the `"cache"` feature. With this feature, `read` invalidates the data
+//! cache before copying elements out of the ring, and `write` cleans the data
+//! cache after copying elements into the ring. Before you start a DMA transfer
+//! that writes into the ring, [`clean`](DmaRingBuffer::clean) the entire ring
+//! so that no dirty cache lines overwrite data from the DMA controller.
+//!
+//! Cache maintenance works on whole cache lines. With the `"cache"` feature, a
+//! ring must be at least one cache line (32 bytes) long; a shorter ring fails to
+//! compile. The ring is aligned to its size, so it owns each line that holds its
+//! elements. Reading the ring only invalidates those lines; it never writes
+//! them back over the DMA controller's data.
+//!
+//! # Streaming
+//!
+//! When a DMA channel continuously writes into a ring, use a [`Reader`] to
+//! find the elements that you haven't read yet. [`peek_slices`](Reader::peek_slices)
+//! exposes those elements in place, so you can parse them without copying
+//! the whole run out of the ring. The DMA controller may write the ring at
+//! any time, so the elements are behind a [`RingSlice`], not a `&[E]`.
+//! [`consume`](Reader::consume) marks elements as read.
+//!
+//! To wait until the DMA controller has written a number of elements, use
+//! [`wait_available`](Reader::wait_available).
+//!
+//! A `Reader` keeps [`Statistics`] about the stream. Use them to size your
+//! ring based on how your application actually behaves.
+//!
+//! # Producer / consumer protocol
+//!
+//! The protocol is defined for a single core: the core whose firmware owns
+//! the [`Dma`](crate::Dma) driver. That core allocates the channel, runs
+//! [`on_interrupt`](crate::Dma::on_interrupt), and owns the `Reader`. Sharing a
+//! ring between cores isn't supported. Each core's firmware has its own driver,
+//! and two drivers can't manage the same controller, so the other core can't
+//! allocate the ring's channel or share its waker.
+//!
+//! A streaming ring has exactly one producer, the DMA controller, and exactly
+//! one consumer, the `Reader`. There are no shared software indices, so there
+//! are no atomic read-modify-write operations. The protocol is
+//!
+//! 1. The DMA controller writes an element into the ring, then advances the
+//!    channel's destination address. The destination address is the only
+//!    "write index."
+//! 2. The `Reader` reads the destination address with a volatile load, then
+//!    issues an acquire fence. On a Cortex-M, the fence is a `DMB`. Element
+//!    reads that follow the fence observe everything the DMA controller wrote
+//!    before the destination address advanced. In cacheable memory, the
+//!    `"cache"` feature also invalidates those elements; see
+//!    [cacheable memory](#cacheable-memory).
+//! 3. The `Reader` owns the read index. Nothing else reads or writes it, so
+//!    it's a plain integer. The DMA controller never observes the read index,
+//!    so it won't stop if the ring is full; see [`Reader`] for the consequences.
+//!
+//! [`on_interrupt`](crate::Dma::on_interrupt) never touches the ring or the
+//! read index. It clears the channel's interrupt flag, and wakes the channel's
+//! waker inside a critical section. [`WaitAvailable`] registers its waker
+//! (also in a critical section) *before* it checks for available elements.
+//! If an interrupt fires after the check, the waker is already registered,
+//! so the wake isn't lost. If the interrupt fires before the registration,
+//! the check observes the new elements.
+//!
+//! A `Reader` isn't `Send` or `Sync`. Use it from a single execution context.
+//! The critical section used for the waker masks interrupts on the driver's
+//! core, which is the only core that runs `on_interrupt`.
+//!
+//! [`set_source_circular_buffer`]: crate::channel::set_source_circular_buffer
+//! [`set_destination_circular_buffer`]: crate::channel::set_destination_circular_buffer
+
+use crate::{channel::Channel, element::Element};
+
+use core::{
+    cell::Cell,
+    future::Future,
+    marker::PhantomData,
+    ops::Range,
+    pin::Pin,
+    sync::atomic,
+    task::{Context, Poll},
+};
+
+/// A circular buffer with compile-time size and alignment guarantees
+///
+/// `DmaRingBuffer` holds `N` elements of type `E`. `N` must be a power
+/// of two, and the total size of the buffer can be no larger than 64KiB.
+/// The buffer is aligned to its size in bytes, so it can always be used
+/// with the DMA controller's modulo addressing.
+///
+/// ```
+/// use imxrt_dma::ring::DmaRingBuffer;
+///
+/// static mut BUFFER: DmaRingBuffer<u16, 256> = DmaRingBuffer::new(0);
+/// ```
+///
+/// A length that's not a power of two fails to compile.
+///
+/// ```compile_fail
+/// use imxrt_dma::ring::DmaRingBuffer;
+///
+/// static mut BUFFER: DmaRingBuffer<u16, 100> = DmaRingBuffer::new(0);
+/// ```
+#[repr(C)]
+pub struct DmaRingBuffer<E, const N: usize>
+where
+    Length<E, N>: ValidLength,
+{
+    _alignment: [<Length<E, N> as ValidLength>::Alignment; 0],
+    buffer: [E; N],
+}
+
+impl<E: Element, const N: usize> DmaRingBuffer<E, N>
+where
+    Length<E, N>: ValidLength,
+{
+    /// The modulo value for this buffer
+    ///
+    /// Use this for the `modulo` argument of
+    /// [`set_source_attributes`](crate::channel::Channel::set_source_attributes)
+    /// and [`set_destination_attributes`](crate::channel::Channel::set_destination_attributes).
+    pub const MODULO: u8 = (N * core::mem::size_of::<E>()).trailing_zeros() as u8;
+
+    /// Catches mistakes in the `valid_lengths!` tables.
+    const LAYOUT: () = assert!(
+        core::mem::align_of::<Self>() == core::mem::size_of::<Self>(),
+        "DMA ring buffer alignment does not match its size"
+    );
+
+    /// A ring that's aligned to its size, and at least one cache line long,
+    /// owns the cache lines that hold its elements.
+    #[cfg(feature = "cache")]
+    const OWNS_CACHE_LINES: () = assert!(
+        core::mem::size_of::<Self>() >= crate::cache::LINE_SIZE,
+        "With the \"cache\" feature, a DMA ring buffer must be at least 32 bytes"
+    );
+
+    /// Create a ring buffer, with all elements set to `elem`
+    pub const fn new(elem: E) -> Self {
+        let () = Self::LAYOUT;
+        Self {
+            _alignment: [],
+            buffer: [elem; N],
+        }
+    }
+
+    /// Returns the number of elements in the buffer
+    ///
+    /// This is always `N`.
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    /// Returns `false`, since ring buffers always have at least one element
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns a pointer to the start of the buffer
+    pub const fn as_ptr(&self) -> *const E {
+        self.buffer.as_ptr()
+    }
+
+    /// Returns a mutable pointer to the start of the buffer
+    pub fn as_mut_ptr(&mut self) -> *mut E {
+        self.buffer.as_mut_ptr()
+    }
+
+    /// Returns the buffer as a slice
+    ///
+    /// The slice satisfies all the requirements of
+    /// [`set_source_circular_buffer`](crate::channel::set_source_circular_buffer).
+    pub const fn as_slice(&self) -> &[E] {
+        &self.buffer
+    }
+
+    /// Returns the buffer as a mutable slice
+    ///
+    /// The slice satisfies all the requirements of
+    /// [`set_destination_circular_buffer`](crate::channel::set_destination_circular_buffer).
+    pub fn as_mut_slice(&mut self) -> &mut [E] {
+        &mut self.buffer
+    }
+
+    /// Copy elements out of the ring, starting at `index`
+    ///
+    /// `index` wraps around the end of the ring. If `out` is longer than the
+    /// ring, `read` stops after copying every element once. Returns the number
+    /// of elements copied.
+    ///
+    /// With the `"cache"` feature, `read` invalidates the data cache for the
+    /// elements before copying them.
+    ///
+    /// ```
+    /// use imxrt_dma::ring::DmaRingBuffer;
+    ///
+    /// let mut ring = DmaRingBuffer::<u8, 32>::new(0);
+    /// for (index, elem) in ring.as_mut_slice().iter_mut().enumerate() {
+    ///     *elem = index as u8;
+    /// }
+    ///
+    /// // Index 62 is index 30, and the read wraps around the end of the ring.
+    /// let mut out = [0; 4];
+    /// assert_eq!(ring.read(62, &mut out), 4);
+    /// assert_eq!(out, [30, 31, 0, 1]);
+    ///
+    /// // A long read copies each element once.
+    /// let mut out = [0xFF; 40];
+    /// assert_eq!(ring.read(8, &mut out), 32);
+    /// assert_eq!(out[..2], [8, 9]);
+    /// assert_eq!(out[23..26], [31, 0, 1]);
+    /// assert_eq!(out[32..], [0xFF; 8]);
+    /// ```
+    pub fn read(&self, index: usize, out: &mut [E]) -> usize {
+        let len = out.len().min(N);
+        let (first, second) = segments::<N>(index, len);
+        let (head, tail) = out[..len].split_at_mut(first.len());
+        for (out, range) in [(head, first), (tail, second)] {
+            self.elements(range).copy_to_slice(out);
+        }
+        len
+    }
+
+    /// Returns the elements in `range`, without borrowing them as a slice
+    ///
+    /// With the `"cache"` feature, this invalidates the data cache for the
+    /// elements.
+    fn elements(&self, range: Range<usize>) -> RingSlice<'_, E> {
+        let elems = RingSlice {
+            // Safety: segments keep the range inside the ring.
+            ptr: unsafe { self.as_ptr().add(range.start) },
+            len: range.len(),
+            _ring: PhantomData,
+        };
+        #[cfg(feature = "cache")]
+        {
+            let () = Self::OWNS_CACHE_LINES;
+            // Safety: the ring owns every line that covers its elements.
+            // The CPU doesn't write the ring while the DMA controller
+            // writes it, so no dirty lines are lost.
+            unsafe {
+                crate::cache::invalidate_lines(
+                    elems.ptr.cast(),
+                    elems.len * core::mem::size_of::<E>(),
+                )
+            };
+        }
+        elems
+    }
+
+    /// Copy elements into the ring, starting at `index`
+    ///
+    /// `index` wraps around the end of the ring. If `elems` is longer than
+    /// the ring, `write` stops after writing every element once. Returns the
+    /// number of elements copied.
+    ///
+    /// With the `"cache"` feature, `write` cleans the data cache after copying
+    /// the elements.
+    ///
+    /// ```
+    /// use imxrt_dma::ring::DmaRingBuffer;
+    ///
+    /// let mut ring = DmaRingBuffer::<u8, 32>::new(0);
+    /// assert_eq!(ring.write(30, &[1, 2, 3, 4]), 4);
+    /// assert_eq!(ring.as_slice()[30..], [1, 2]);
+    /// assert_eq!(ring.as_slice()[..2], [3, 4]);
+    ///
+    /// // A long write stops after writing every element once.
+    /// let elems: [u8; 40] = core::array::from_fn(|index| index as u8);
+    /// assert_eq!(ring.write(16, &elems), 32);
+    /// assert_eq!(ring.as_slice()[16], 0);
+    /// assert_eq!(ring.as_slice()[15], 31);
+    /// ```
+    pub fn write(&mut self, index: usize, elems: &[E]) -> usize {
+        let len = elems.len().min(N);
+        let (first, second) = segments::<N>(index, len);
+        let (head, tail) = elems[..len].split_at(first.len());
+        for (elems, range) in [(head, first), (tail, second)] {
+            let dst = &mut self.buffer[range];
+            dst.copy_from_slice(elems);
+            #[cfg(feature = "cache")]
+            crate::cache::clean(dst.as_ptr().cast(), core::mem::size_of_val(dst));
+        }
+        len
+    }
+
+    /// Set this ring as `channel`'s circular source
+    ///
+    /// This is like [`set_source_circular_buffer`](crate::channel::set_source_circular_buffer),
+    /// but the ring's size and alignment are already checked, so it never panics.
+    ///
+    /// # Safety
+    ///
+    /// The ring must stay valid for the lifetime of the transfer, and for all
+    /// subsequent transfers performed by this DMA channel with this ring.
+    pub unsafe fn set_as_source(&self, channel: &mut Channel) {
+        channel.set_source_address(self.as_ptr());
+        channel.set_source_offset(core::mem::size_of::<E>() as i16);
+        channel.set_source_attributes::<E>(Self::MODULO);
+        channel.set_source_last_address_adjustment(0);
+    }
+
+    /// Set this ring as `channel`'s circular destination
+    ///
+    /// This is like [`set_destination_circular_buffer`](crate::channel::set_destination_circular_buffer),
+    /// but the ring's size and alignment are already checked, so it never panics.
+    ///
+    /// # Safety
+    ///
+    /// The ring must stay valid for the lifetime of the transfer, and for all
+    /// subsequent transfers performed by this DMA channel with this ring.
+    pub unsafe fn set_as_destination(&mut self, channel: &mut Channel) {
+        channel.set_destination_address(self.as_ptr());
+        channel.set_destination_offset(core::mem::size_of::<E>() as i16);
+        channel.set_destination_attributes::<E>(Self::MODULO);
+        channel.set_destination_last_address_adjustment(0);
+    }
+
+    /// Clean the data cache for the entire ring
+    ///
+    /// Call `clean` before the DMA controller starts writing into the ring.
+    /// Without the `"cache"` feature, this does nothing.
+    pub fn clean(&self) {
+        #[cfg(feature = "cache")]
+        crate::cache::clean(self.as_ptr().cast(), core::mem::size_of_val(&self.buffer));
+    }
+}
+
+/// Another name for [`DmaRingBuffer`]
+///
+/// A `CircularBuffer` has the size and alignment that the DMA controller's
+/// modulo addressing requires.
+pub type CircularBuffer<E, const N: usize> = DmaRingBuffer<E, N>;
+
+/// Reads the elements that a DMA channel writes into a [`DmaRingBuffer`]
+///
+/// The `Reader` compares its read position with the channel's destination
+/// address. Elements between the two positions have been written by the DMA
+/// controller, but not yet read by software.
+///
+/// The `Reader` can't tell if the DMA controller wrote more than `N` elements
+/// since the last time you consumed elements. Make sure to consume elements
+/// quickly enough to keep up with the transfer.
+///
+/// # Example
+///
+/// ```no_run
+/// use imxrt_dma::{channel::{self, Channel}, ring::{DmaRingBuffer, Reader}};
+/// # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+///
+/// static mut RING: DmaRingBuffer<u8, 256> = DmaRingBuffer::new(0);
+///
+/// let mut channel: Channel = // DMA channel 7
+///     # unsafe { DMA.channel(7) };
+/// // Safety: RING is only used with this channel.
+/// let ring = unsafe { &mut *core::ptr::addr_of_mut!(RING) };
+/// unsafe { channel::set_destination_circular_buffer(&mut channel, ring.as_mut_slice()) };
+/// // Define the rest of the transfer, then enable the channel...
+///
+/// // Safety: channel is writing into the ring.
+/// let mut reader = unsafe { Reader::new(&channel, ring) };
+///
+/// let (first, second) = reader.peek_slices();
+/// let newlines = first.iter().chain(second.iter()).filter(|&b| b == b'\n').count();
+/// let unread = first.len() + second.len();
+/// reader.consume(unread);
+/// ```
+pub struct Reader<'a, E, const N: usize>
+where
+    Length<E, N>: ValidLength,
+{
+    channel: &'a Channel,
+    ring: &'a DmaRingBuffer<E, N>,
+    /// Index of the next element to read.
+    read: usize,
+    /// Largest number of unread elements we've observed.
+    high_water_mark: Cell<usize>,
+    wraps: u32,
+    total: u64,
+}
+
+impl<'a, E: Element, const N: usize> Reader<'a, E, N>
+where
+    Length<E, N>: ValidLength,
+{
+    /// Create a reader for the `ring` that `channel` is writing
+    ///
+    /// The reader starts at the channel's current destination address. There
+    /// are no unread elements until the DMA controller writes more data.
+    ///
+    /// # Safety
+    ///
+    /// `channel` must be configured to write into `ring` with the ring's
+    /// [`MODULO`](DmaRingBuffer::MODULO). The channel must not be
+    /// reconfigured while the reader exists.
+    pub unsafe fn new(channel: &'a Channel, ring: &'a DmaRingBuffer<E, N>) -> Self {
+        let mut reader = Reader {
+            channel,
+            ring,
+            read: 0,
+            high_water_mark: Cell::new(0),
+            wraps: 0,
+            total: 0,
+        };
+        reader.read = reader.write_index();
+        reader
+    }
+
+    /// Returns the index of the next element that the DMA controller will write
+    ///
+    /// Elements before this index are safe to read; see the protocol described
+    /// in the module documentation.
+    fn write_index(&self) -> usize {
+        let daddr = self.channel.destination_address() as usize;
+        // Element reads must not happen before we sample the write index.
+        atomic::fence(atomic::Ordering::Acquire);
+        (daddr.wrapping_sub(self.ring.as_ptr() as usize) / core::mem::size_of::<E>()) % N
+    }
+
+    /// Returns the number of unread elements
+    pub fn available(&self) -> usize {
+        let available = (self.write_index() + N - self.read) % N;
+        if available > self.high_water_mark.get() {
+            self.high_water_mark.set(available);
+        }
+        available
+    }
+
+    /// Returns the statistics collected since the reader was created, or
+    /// since the last call to [`reset_statistics`](Self::reset_statistics)
+    pub fn statistics(&self) -> Statistics {
+        Statistics {
+            high_water_mark: self.high_water_mark.get(),
+            wraps: self.wraps,
+            total: self.total,
+        }
+    }
+
+    /// Reset all statistics to zero
+    pub fn reset_statistics(&mut self) {
+        self.high_water_mark.set(0);
+        self.wraps = 0;
+        self.total = 0;
+    }
+
+    /// Returns the unread elements, without marking them as read
+    ///
+    /// The unread elements may wrap around the end of the ring. The first
+    /// slice holds the oldest elements. The second slice, which may be empty,
+    /// holds the elements that wrapped around to the start of the ring.
+    ///
+    /// With the `"cache"` feature, this invalidates the data cache for the
+    /// returned elements.
+    pub fn peek_slices(&self) -> (RingSlice<'_, E>, RingSlice<'_, E>) {
+        let (first, second) = segments::<N>(self.read, self.available());
+        (self.ring.elements(first), self.ring.elements(second))
+    }
+
+    /// Wait until at least `count` elements are available
+    ///
+    /// The future resolves with the number of available elements. `count` is
+    /// capped at `N - 1`, the most elements that the reader can distinguish
+    /// from an empty ring.
+    ///
+    /// The future checks the available elements each time it's polled. To
+    /// wake the executor, enable channel interrupts and call
+    /// [`on_interrupt`](crate::Dma::on_interrupt) in the channel's interrupt
+    /// handler. The beginning major iteration count sets how often you're
+    /// woken:
+    ///
+    /// - with [`set_interrupt_on_completion`](Channel::set_interrupt_on_completion),
+    ///   the channel interrupts after each major loop.
+    /// - with [`set_interrupt_on_half`](Channel::set_interrupt_on_half), the
+    ///   channel also interrupts halfway through each major loop.
+    ///
+    /// For example, to wake at least every 16 elements when the minor loop
+    /// transfers one element, set the transfer iterations to 32 and enable both
+    /// interrupts. The iteration count doesn't need to match the ring length;
+    /// the ring's modulo keeps the destination address in the ring.
+    pub fn wait_available(&self, count: usize) -> WaitAvailable<'_, 'a, E, N> {
+        WaitAvailable {
+            reader: self,
+            count: count.min(N - 1),
+        }
+    }
+
+    /// Mark up to `count` elements as read
+    ///
+    /// Returns the number of elements consumed. This is less than `count` if
+    /// there are fewer than `count` unread elements.
+    pub fn consume(&mut self, count: usize) -> usize {
+        let count = count.min(self.available());
+        let read = self.read + count;
+        if read >= N {
+            self.wraps = self.wraps.wrapping_add(1);
+        }
+        self.read = read % N;
+        self.total = self.total.wrapping_add(count as u64);
+        count
+    }
+}
+
+/// Elements in a [`DmaRingBuffer`] that the DMA controller may be writing
+///
+/// [`Reader::peek_slices`] returns the unread elements as `RingSlice`s. A
+/// `RingSlice` is like a `&[E]`, but it doesn't let you borrow the elements,
+/// since the DMA controller can write the ring at any time. Instead, its
+/// methods copy elements out of the ring with volatile reads.
+///
+/// ```no_run
+/// use imxrt_dma::ring::{DmaRingBuffer, Reader};
+/// # let channel: imxrt_dma::channel::Channel = unsafe { imxrt_dma::channel::Channel::steal(core::ptr::null(), core::ptr::null(), 7) };
+/// # let ring = DmaRingBuffer::<u8, 256>::new(0);
+/// # let reader = unsafe { Reader::new(&channel, &ring) };
+///
+/// let (first, second) = reader.peek_slices();
+/// let mut line = [0; 64];
+/// let len = first.copy_to_slice(&mut line);
+/// let len = len + second.copy_to_slice(&mut line[len..]);
+/// ```
+#[derive(Clone, Copy)]
+pub struct RingSlice<'r, E> {
+    ptr: *const E,
+    len: usize,
+    _ring: PhantomData<&'r [E]>,
+}
+
+impl<'r, E: Element> RingSlice<'r, E> {
+    /// Returns the number of elements
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no elements
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a pointer to the first element
+    pub const fn as_ptr(&self) -> *const E {
+        self.ptr
+    }
+
+    /// Copy the element at `index` out of the ring
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<E> {
+        // Safety: the element is in the ring, and the DMA controller may
+        // write it at any time.
+        (index < self.len).then(|| unsafe { self.ptr.add(index).read_volatile() })
+    }
+
+    /// Returns an iterator that copies each element out of the ring
+    pub fn iter(&self) -> impl Iterator<Item = E> + 'r {
+        let elems = *self;
+        (0..elems.len).filter_map(move |index| elems.get(index))
+    }
+
+    /// Copy elements into `out`, starting with the first element
+    ///
+    /// Copies the smaller of `out.len()` and [`len()`](Self::len) elements.
+    /// Returns the number of elements copied.
+    pub fn copy_to_slice(&self, out: &mut [E]) -> usize {
+        let len = out.len().min(self.len);
+        for (index, dst) in out[..len].iter_mut().enumerate() {
+            // Safety: the element is in the ring, and the DMA controller may
+            // write it at any time.
+            *dst = unsafe { self.ptr.add(index).read_volatile() };
+        }
+        len
+    }
+}
+
+/// Statistics about a ring stream
+///
+/// The high-water mark is the largest number of unread elements observed
+/// when checking for, peeking at, or consuming elements. If it's close to
+/// the ring's length, the reader isn't keeping up, and you may need a larger
+/// ring.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Statistics {
+    /// The largest number of unread elements observed
+    pub high_water_mark: usize,
+    /// The number of times the reader wrapped around the end of the ring
+    pub wraps: u32,
+    /// The total number of elements consumed
+    pub total: u64,
+}
+
+/// A future that resolves when a [`Reader`] has enough elements
+///
+/// Use [`wait_available`](Reader::wait_available) to create this future.
+pub struct WaitAvailable<'r, 'a, E, const N: usize>
+where
+    Length<E, N>: ValidLength,
+{
+    reader: &'r Reader<'a, E, N>,
+    count: usize,
+}
+
+impl<E: Element, const N: usize> Future for WaitAvailable<'_, '_, E, N>
+where
+    Length<E, N>: ValidLength,
+{
+    type Output = usize;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Register before checking, so we can't miss an interrupt
+        // that happens between the check and the registration.
+        self.reader.channel.register_waker(cx.waker());
+        let available = self.reader.available();
+        if available >= self.count {
+            Poll::Ready(available)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Split `len` elements starting at `index` into two ranges
+///
+/// The first range runs from `index` towards the end of the ring. The
+/// second range, which may be empty, starts at the beginning of the ring.
+fn segments<const N: usize>(index: usize, len: usize) -> (Range<usize>, Range<usize>) {
+    let start = index % N;
+    let first = len.min(N - start);
+    (start..start + first, 0..len - first)
+}
+
+/// Describes the length of a [`DmaRingBuffer`]
+///
+/// `Length` pairs an element type with an element count. It only
+/// implements [`ValidLength`] when the resulting buffer is usable for
+/// modulo addressing.
+pub struct Length<E, const N: usize>(core::marker::PhantomData<E>);
+
+/// Implemented for every [`Length`] that's usable as a [`DmaRingBuffer`]
+///
+/// This trait is sealed, and cannot be implemented outside of this crate.
+pub trait ValidLength: private::Sealed {
+    /// A zero-sized type that has the buffer's alignment
+    type Alignment;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Zero-sized alignment types
+pub mod alignment {
+    macro_rules! alignments {
+        ($($name:ident => $align:literal),* $(,)?) => {
+            $(
+                #[doc = concat!("A zero-sized type aligned to ", stringify!($align), " bytes")]
+                #[derive(Debug, Clone, Copy)]
+                #[repr(align($align))]
+                pub struct $name;
+            )*
+        };
+    }
+
+    alignments! {
+        A2 => 2, A4 => 4, A8 => 8, A16 => 16, A32 => 32, A64 => 64, A128 => 128,
+        A256 => 256, A512 => 512, A1024 => 1024, A2048 => 2048, A4096 => 4096,
+        A8192 => 8192, A16384 => 16384, A32768 => 32768, A65536 => 65536,
+    }
+}
+
+macro_rules! valid_lengths {
+    (@elem $elem:ty; $($len:literal => $align:ident),* $(,)?) => {
+        $(
+            impl private::Sealed for Length<$elem, $len> {}
+            impl ValidLength for Length<$elem, $len> {
+                type Alignment = alignment::$align;
+            }
+        )*
+    };
+    (; $($table:tt)*) => {};
+    ($elem:ty $(, $rest:ty)*; $($table:tt)*) => {
+        valid_lengths!(@elem $elem; $($table)*);
+        valid_lengths!($($rest),*; $($table)*);
+    };
+}
+
+valid_lengths! { u8, i8;
+    2 => A2, 4 => A4, 8 => A8, 16 => A16, 32 => A32, 64 => A64, 128 => A128,
+    256 => A256, 512 => A512, 1024 => A1024, 2048 => A2048, 4096 => A4096,
+    8192 => A8192, 16384 => A16384, 32768 => A32768, 65536 => A65536,
+}
+
+valid_lengths! { u16, i16;
+    1 => A2, 2 => A4, 4 => A8, 8 => A16, 16 => A32, 32 => A64, 64 => A128,
+    128 => A256, 256 => A512, 512 => A1024, 1024 => A2048, 2048 => A4096,
+    4096 => A8192, 8192 => A16384, 16384 => A32768, 32768 => A65536,
+}
+
+valid_lengths! { u32, i32, f32;
+    1 => A4, 2 => A8, 4 => A16, 8 => A32, 16 => A64, 32 => A128,
+    64 => A256, 128 => A512, 256 => A1024, 512 => A2048, 1024 => A4096,
+    2048 => A8192, 4096 => A16384, 8192 => A32768, 16384 => A65536,
+}
+
+valid_lengths! { u64, i64, f64;
+    1 => A8, 2 => A16, 4 => A32, 8 => A64, 16 => A128,
+    32 => A256, 64 => A512, 128 => A1024, 256 => A2048, 512 => A4096,
+    1024 => A8192, 2048 => A16384, 4096 => A32768, 8192 => A65536,
+}
+
+#[cfg(feature = "edma34")]
+valid_lengths! { [u8; 16];
+    1 => A16, 2 => A32, 4 => A64, 8 => A128, 16 => A256,
+    32 => A512, 64 => A1024, 128 => A2048, 256 => A4096,
+    512 => A8192, 1024 => A16384, 2048 => A32768, 4096 => A65536,
+}
+
+valid_lengths! { [u8; 32];
+    1 => A32, 2 => A64, 4 => A128, 8 => A256, 16 => A512,
+    32 => A1024, 64 => A2048, 128 => A4096,
+    256 => A8192, 512 => A16384, 1024 => A32768, 2048 => A65536,
+}
@@ -0,0 +1,289 @@
+//! Scatter / gather transfer chains
+//!
+//! A scatter / gather transfer copies a list of [`Segment`]s, one after the
+//! other, on a single channel. The driver prepares a transfer control
+//! descriptor for each segment, and links each descriptor to the next. When
+//! a segment completes, the DMA controller loads the next descriptor itself,
+//! so the chain runs without CPU involvement. The [`ScatterGather`] future
+//! resolves when the final segment completes.
+//!
+//! ```no_run
+//! use imxrt_dma::{
+//!     armed::ArmedPool,
+//!     scatter_gather::{self, Segment},
+//! };
+//! # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+//!
+//! # async fn f() -> imxrt_dma::Result<()> {
+//! let mut channel = DMA.try_channel(7).unwrap();
+//! channel.set_interrupt_on_completion(true);
+//! // TODO call DMA.on_interrupt(7) from the DMA7 interrupt!
+//!
+//! let header = [0xA5u8; 4];
+//! let payload = [0x42u8; 64];
+//! let mut packet = [0u8; 68];
+//! let (head, body) = packet.split_at_mut(4);
+//!
+//! let mut segments = [Segment::new(&header, head), Segment::new(&payload, body)];
+//! let mut tcds = ArmedPool::<2>::new();
+//! scatter_gather::try_scatter_gather(&mut segments, tcds.tcds_mut(), &mut channel)?.await?;
+//! # Ok(()) }
+//! ```
+//!
+//! # Memory
+//!
+//! The DMA controller reads the descriptors from memory. Place the descriptors
+//! in memory that the DMA controller can access. With the `"cache"` feature,
+//! the driver cleans the descriptors and the sources from the data cache, and
+//! invalidates the destinations when the transfer completes.
+
+use crate::{
+    armed::ArmedTcd,
+    channel::{self, Channel},
+    interrupt::Transfer,
+    ConfigurationError, Element, Error,
+};
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// One copy in a scatter / gather transfer
+///
+/// The segment copies the minimum number of elements between its source and
+/// destination.
+pub struct Segment<'a, E> {
+    source: &'a [E],
+    destination: &'a mut [E],
+}
+
+impl<'a, E> Segment<'a, E> {
+    /// Copy `source` into `destination`
+    pub fn new(source: &'a [E], destination: &'a mut [E]) -> Self {
+        Segment {
+            source,
+            destination,
+        }
+    }
+
+    /// Returns the number of elements copied by this segment
+    pub fn len(&self) -> usize {
+        self.source.len().min(self.destination.len())
+    }
+
+    /// Returns `true` if this segment doesn't copy any elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A scatter / gather transfer
+///
+/// Use [`scatter_gather`] to define the transfer.
+pub struct ScatterGather<'a, E> {
+    transfer: Transfer<'a>,
+    channel: &'a Channel,
+    /// Set once the future starts the first segment
+    started: bool,
+    #[cfg_attr(not(feature = "cache"), allow(dead_code))]
+    segments: &'a mut [Segment<'a, E>],
+}
+
+/// Copy each of `segments`, in order, using one descriptor from `tcds` per segment
+///
+/// The final segment disables the channel, and it interrupts if the channel
+/// [interrupts on completion](Channel::set_interrupt_on_completion). You're
+/// responsible for calling [`on_interrupt`](crate::Dma::on_interrupt) if the
+/// interrupt fires. Otherwise, you may poll the transfer until completion.
+///
+/// `scatter_gather` overwrites the channel's transfer control descriptor, and
+/// the first `segments.len()` descriptors in `tcds`.
+///
+/// # Panics
+///
+/// Panics if
+///
+/// - `segments` is empty, or if there are fewer `tcds` than `segments`.
+/// - a segment is empty. The DMA controller treats a zero byte minor loop as
+///   a 4 GiB minor loop.
+/// - a segment is larger than the channel's minor loop byte count. See
+///   [`set_minor_loop_bytes`](Channel::set_minor_loop_bytes).
+///
+/// See [`try_scatter_gather`] for a function that doesn't panic.
+///
+/// # Example
+///
+/// Assemble a packet from three buffers, using the simulator from the `"sim"` feature.
+///
+/// ```
+/// # #[cfg(feature = "sim")] {
+/// use imxrt_dma::{
+///     armed::ArmedPool,
+///     scatter_gather::{self, Segment},
+///     sim::Simulator,
+/// };
+///
+/// let sim = Simulator::<32>::new();
+/// let mut channel = sim.dma().try_channel(3).unwrap();
+///
+/// let header = [0xA5u8; 4];
+/// let payload = [0x42u8; 24];
+/// let trailer = [0x5Au8; 4];
+/// let mut packet = [0u8; 32];
+/// let mut tcds = ArmedPool::<3>::new();
+/// // Safety: buffers and descriptors outlive the transfer.
+/// unsafe {
+///     sim.map(&header);
+///     sim.map(&payload);
+///     sim.map(&trailer);
+///     sim.map(&packet);
+///     sim.map(tcds.tcds_mut());
+/// }
+///
+/// let (head, rest) = packet.split_at_mut(4);
+/// let (body, tail) = rest.split_at_mut(24);
+/// let mut segments = [
+///     Segment::new(&header, head),
+///     Segment::new(&payload, body),
+///     Segment::new(&trailer, tail),
+/// ];
+/// sim.block_on(scatter_gather::scatter_gather(&mut segments, tcds.tcds_mut(), &mut channel))
+///     .unwrap();
+///
+/// assert_eq!(packet[..4], header);
+/// assert_eq!(packet[4..28], payload);
+/// assert_eq!(packet[28..], trailer);
+/// # }
+/// ```
+pub fn scatter_gather<'a, E: Element>(
+    segments: &'a mut [Segment<'a, E>],
+    tcds: &'a mut [ArmedTcd],
+    channel: &'a mut Channel,
+) -> ScatterGather<'a, E> {
+    assert!(!segments.is_empty(), "No scatter / gather segments");
+    assert!(
+        tcds.len() >= segments.len(),
+        "Not enough TCDs for the scatter / gather segments"
+    );
+    let tcds = &mut tcds[..segments.len()];
+    for segment in segments.iter() {
+        assert!(!segment.is_empty(), "Empty scatter / gather segment");
+        assert!(
+            segment_bytes(segment).is_some_and(|bytes| bytes <= channel.minor_loop_bytes_limit()),
+            "Scatter / gather segment is larger than the minor loop byte limit"
+        );
+    }
+
+    channel.disable();
+    channel.claim_clock();
+    channel.set_channel_configuration(channel::Configuration::Off);
+
+    let interrupt = channel.is_interrupt_on_completion();
+    for (index, (tcd, segment)) in tcds.iter_mut().zip(segments.iter()).enumerate() {
+        let size = core::mem::size_of::<E>();
+        let last = index == segments.len() - 1;
+        *tcd = ArmedTcd::new()
+            .source::<E>(segment.source.as_ptr() as u32, size as i16)
+            .destination::<E>(segment.destination.as_ptr() as u32, size as i16)
+            .minor_loop_bytes((size * segment.len()) as u32)
+            .iterations(1)
+            // The first segment starts when the transfer starts.
+            .start(index != 0)
+            .interrupt_on_completion(last && interrupt)
+            .disable_on_completion(last);
+    }
+    for index in 1..tcds.len() {
+        let (current, next) = tcds.split_at_mut(index);
+        current[index - 1].chain(&next[0]);
+    }
+
+    #[cfg(feature = "cache")]
+    {
+        crate::cache::clean(tcds.as_ptr().cast(), core::mem::size_of_val(tcds));
+        for segment in segments.iter() {
+            crate::cache::Region::of(segment.source).before_dma_read();
+            crate::cache::Region::of(segment.destination).before_dma_write();
+        }
+    }
+
+    channel.clear_complete();
+    channel.tcd().restore(&tcds[0].snapshot());
+
+    crate::trace::start(
+        channel.channel(),
+        crate::trace::MEMORY_TO_MEMORY,
+        segments.iter().map(Segment::len).sum(),
+    );
+
+    ScatterGather {
+        // Safety: the segments and descriptors are borrowed for the life of
+        // the transfer, and the final descriptor disables the channel.
+        transfer: unsafe { Transfer::new(channel) },
+        channel,
+        started: false,
+        segments,
+    }
+}
+
+/// Like [`scatter_gather()`], but checks the transfer before touching the hardware
+///
+/// Returns [`InvalidLength`](ConfigurationError::InvalidLength) if there are no
+/// segments, if there are fewer `tcds` than `segments`, or if a segment is empty
+/// or larger than the channel's minor loop byte count. Returns
+/// [`UnreachableMemory`](ConfigurationError::UnreachableMemory) if a buffer,
+/// or a descriptor, is outside of the driver's
+/// [memory map](crate::Dma::set_memory_map). If there's an error, the channel,
+/// the descriptors, and the buffers are unchanged.
+pub fn try_scatter_gather<'a, E: Element>(
+    segments: &'a mut [Segment<'a, E>],
+    tcds: &'a mut [ArmedTcd],
+    channel: &'a mut Channel,
+) -> Result<ScatterGather<'a, E>, Error> {
+    if segments.is_empty() || tcds.len() < segments.len() {
+        return Err(Error::configuration(ConfigurationError::InvalidLength));
+    }
+    channel.check_buffer(&tcds[..segments.len()])?;
+    for segment in segments.iter() {
+        channel.check_buffer(segment.source)?;
+        channel.check_buffer(segment.destination)?;
+        match segment_bytes(segment) {
+            Some(bytes) if bytes != 0 && bytes <= channel.minor_loop_bytes_limit() => {}
+            _ => return Err(Error::configuration(ConfigurationError::InvalidLength)),
+        }
+    }
+    Ok(scatter_gather(segments, tcds, channel))
+}
+
+/// Returns the number of bytes copied by `segment`, if it fits in a minor loop
+fn segment_bytes<E>(segment: &Segment<'_, E>) -> Option<u32> {
+    let bytes = core::mem::size_of::<E>().checked_mul(segment.len())?;
+    u32::try_from(bytes).ok()
+}
+
+impl<E> Future for ScatterGather<'_, E> {
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: data not moved
+        let transfer = unsafe { self.as_mut().map_unchecked_mut(|this| &mut this.transfer) };
+        let poll = transfer.poll(cx);
+        // Only start the first segment. Later segments start themselves, and
+        // a second start after the final segment would run it again, past the
+        // end of its buffers.
+        if poll.is_pending() && !self.started {
+            // Safety: the flag isn't pinned.
+            unsafe { self.as_mut().get_unchecked_mut().started = true };
+            self.channel.start();
+        }
+        #[cfg(feature = "cache")]
+        if let Poll::Ready(Ok(())) = poll {
+            for segment in self.segments.iter() {
+                // Safety: the transfer is complete.
+                unsafe { crate::cache::Region::of(segment.destination).after_dma_write() };
+            }
+        }
+        poll
+    }
+}
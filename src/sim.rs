@@ -0,0 +1,1091 @@
+//! A host-side DMA simulator
+//!
+//! [`Simulator`] emulates an eDMA controller and its DMAMUX in ordinary memory.
+//! The simulator's [`Dma`] driver programs that memory just like it would
+//! program the hardware. When you [`step`](Simulator::step) the simulator, it
+//! performs a minor loop for the highest priority channel that has a service
+//! request. It copies memory according to the channel's TCD, sets DONE, INT,
+//! and ERR, and calls [`on_interrupt`](Dma::on_interrupt) when the channel
+//! would interrupt. Use the simulator to test transfer futures, `memcpy`, and
+//! ring buffer logic on a host, without hardware.
+//!
+//! This module requires the `"sim"` feature. On a host, critical sections
+//! are a global lock, so the driver works outside of an interrupt context.
+//!
+//! ```
+//! use imxrt_dma::{memcpy, sim::Simulator};
+//!
+//! let sim = Simulator::<32>::new();
+//! let mut channel = sim.dma().try_channel(7).unwrap();
+//!
+//! let source = [4u32, 5, 6, 7, 8];
+//! let mut destination = [0u32; 5];
+//! // Safety: buffers outlive the transfer.
+//! unsafe {
+//!     sim.map(&source);
+//!     sim.map(&destination);
+//! }
+//!
+//! sim.block_on(memcpy::memcpy(&source, &mut destination, &mut channel)).unwrap();
+//! assert_eq!(destination, source);
+//! ```
+//!
+//! # Memory
+//!
+//! A TCD holds 32-bit addresses, but host pointers may be wider. The simulator
+//! only accesses memory that you [`map`](Simulator::map). It finds mapped memory
+//! by the low 32 bits of its address. An access to unmapped memory is a bus
+//! error, just like on hardware.
+//!
+//! # Hardware requests
+//!
+//! A channel that's enabled, and whose DMAMUX configuration is enabled, needs
+//! a hardware request to perform a minor loop. Use [`request`](Simulator::request)
+//! to simulate requests from a peripheral. Each request is consumed by one minor
+//! loop. An always-on configuration always has a request.
+//!
+//! To simulate a peripheral more closely, implement [`Peripheral`] and
+//! [`attach`](Simulator::attach) it. The simulator routes the peripheral's
+//! requests through the DMAMUX, and sends DMA accesses of the peripheral's
+//! registers to the peripheral. With the `"test-util"` feature, the `mock`
+//! module has ready-made peripherals.
+//!
+//! # Register writes
+//!
+//! The simulator sees every register write that the driver performs. To check
+//! how the driver programs the hardware, [`start_recording`](Simulator::start_recording),
+//! use the driver, then [`stop_recording`](Simulator::stop_recording). Compare
+//! the recorded [`RegisterWrite`]s against known-good values.
+//!
+//! ```
+//! use imxrt_dma::{channel, sim::{Block, Simulator}};
+//!
+//! let sim = Simulator::<32>::new();
+//! let mut channel = sim.dma().try_channel(2).unwrap();
+//!
+//! sim.start_recording();
+//! // Safety: the transfer never starts.
+//! unsafe { channel.set_transfer_iterations(16) };
+//! let writes = sim.stop_recording();
+//!
+//! let tcd: Vec<_> = writes
+//!     .iter()
+//!     .filter_map(|write| Some((write.tcd()?, write.value)))
+//!     .collect();
+//! assert_eq!(tcd, [((2, "CITER"), 16), ((2, "BITER"), 16)]);
+//! assert!(writes.iter().all(|write| write.block == Block::Dma));
+//! ```
+//!
+//! The simulator's own register updates, like completing a transfer, aren't
+//! recorded.
+//!
+//! # Fault injection
+//!
+//! To exercise error handling, [`inject`](Simulator::inject) a [`Fault`] into a
+//! channel. The simulator counts the channel's beats, and fails the beat that
+//! the fault names. A channel's faults happen in the order that you inject them.
+//!
+//! ```
+//! use imxrt_dma::{memcpy, sim::{Fault, Simulator}};
+//!
+//! let sim = Simulator::<32>::new();
+//! let mut channel = sim.dma().try_channel(7).unwrap();
+//!
+//! let source = [4u32, 5, 6, 7, 8];
+//! let mut destination = [0u32; 5];
+//! // Safety: buffers outlive the transfer.
+//! unsafe {
+//!     sim.map(&source);
+//!     sim.map(&destination);
+//! }
+//!
+//! // The fourth write fails.
+//! sim.inject(7, Fault::DestinationBusError { beat: 3 });
+//! let error = sim
+//!     .block_on(memcpy::memcpy(&source, &mut destination, &mut channel))
+//!     .unwrap_err();
+//! assert!(error.is_destination_bus_error());
+//! assert_eq!(destination, [4, 5, 6, 0, 0]);
+//!
+//! // The fault happened, so the next transfer succeeds.
+//! assert_eq!(sim.pending_faults(7), 0);
+//! sim.block_on(memcpy::memcpy(&source, &mut destination, &mut channel)).unwrap();
+//! assert_eq!(destination, source);
+//! ```
+//!
+//! # Limitations
+//!
+//! The simulator performs each minor loop at once, so channels are never
+//! active between steps. It doesn't simulate channel linking, bandwidth
+//! control, preemption, or eDMA3 and eDMA4 controllers. Use the simulator
+//! from one thread.
+
+use crate::{
+    ral::{dma, dmamux, tcd, Access, Mmio, RORegister, Value, WORegister},
+    Dma,
+};
+
+use core::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+use std::{
+    boxed::Box,
+    collections::VecDeque,
+    rc::{Rc, Weak},
+    vec::Vec,
+};
+
+/// The number of consecutive idle polls before [`block_on`](Simulator::block_on) gives up
+const STALL_LIMIT: usize = 1000;
+
+/// Written to a command register once the simulator executes the command
+const NOP: u8 = 1 << 7;
+/// Set in a command value to select all channels
+const ALL: u8 = 1 << 6;
+
+// TCD CSR bits.
+const START: u16 = 1 << 0;
+const INTMAJOR: u16 = 1 << 1;
+const INTHALF: u16 = 1 << 2;
+const DREQ: u16 = 1 << 3;
+const ESG: u16 = 1 << 4;
+const DONE: u16 = 1 << 7;
+
+// ES bits.
+const DBE: u32 = 1 << 0;
+const SBE: u32 = 1 << 1;
+const SGE: u32 = 1 << 2;
+const NCE: u32 = 1 << 3;
+const DOE: u32 = 1 << 4;
+const DAE: u32 = 1 << 5;
+const SOE: u32 = 1 << 6;
+const SAE: u32 = 1 << 7;
+/// Set in ES when the controller cancels a transfer with an error
+const ES_ECX: u32 = 1 << 16;
+const VLD: u32 = 1 << 31;
+
+// CR bits.
+const EMLM: u32 = 1 << 7;
+const GRP1PRI: u32 = 1 << 10;
+const ECX: u32 = 1 << 16;
+const CX: u32 = 1 << 17;
+
+std::thread_local! {
+    /// The simulators on this thread
+    static SIMULATORS: RefCell<Vec<Weak<Inner>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Register access that lets simulators observe the driver's accesses
+///
+/// Simulators react to the driver's accesses like the hardware would. A write
+/// to a command register, like SERQ or CDNE, or to CR, executes the command.
+/// A read of HRS sees the peripherals' current requests.
+pub(crate) struct Bus;
+
+/// Call `f` with each simulator on this thread
+fn for_each(f: impl Fn(&Inner)) {
+    // The thread-local is gone if a register is accessed while the
+    // thread exits. There's nothing to simulate.
+    let _ = SIMULATORS.try_with(|simulators| {
+        let simulators: Vec<_> = simulators
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+        for inner in simulators {
+            f(&inner);
+        }
+    });
+}
+
+impl Access for Bus {
+    unsafe fn read<T: Value>(register: *const T) -> T {
+        let address = register as usize;
+        for_each(|inner| inner.before_read(address));
+        // Safety: caller ensures the register is valid.
+        unsafe { Mmio::read(register) }
+    }
+    unsafe fn write<T: Value>(register: *mut T, value: T) {
+        // Safety: caller ensures the register is valid.
+        unsafe { register.write_volatile(value) };
+        let address = register as usize;
+        for_each(|inner| inner.after_write(address, core::mem::size_of::<T>(), value.bits()));
+    }
+}
+
+/// A simulated register block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Block {
+    /// The eDMA controller
+    Dma,
+    /// The DMA multiplexer
+    Dmamux,
+}
+
+/// A register write performed by the driver
+///
+/// Use [`start_recording`](Simulator::start_recording) to record writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterWrite {
+    /// The register block that was written
+    pub block: Block,
+    /// The register's offset from the start of the block, in bytes
+    pub offset: usize,
+    /// The register's size, in bytes
+    pub size: usize,
+    /// The written value, zero-extended
+    pub value: u32,
+}
+
+impl RegisterWrite {
+    /// If this is a TCD write, returns the channel and the register name
+    ///
+    /// Register names match [`TcdSnapshot`](crate::TcdSnapshot)'s fields.
+    pub fn tcd(&self) -> Option<(usize, &'static str)> {
+        const TCDS: usize = core::mem::offset_of!(dma::RegisterBlock, TCD);
+        const SIZE: usize = core::mem::size_of::<tcd::RegisterBlock>();
+        if self.block != Block::Dma || self.offset < TCDS {
+            return None;
+        }
+        let channel = (self.offset - TCDS) / SIZE;
+        Some((channel, tcd::register_name((self.offset - TCDS) % SIZE)?))
+    }
+}
+
+/// Simulated register memory
+#[repr(C, align(32))]
+struct Memory {
+    dma: [u32; core::mem::size_of::<dma::RegisterBlock>() / 4],
+    dmamux: [u32; core::mem::size_of::<dmamux::RegisterBlock>() / 4],
+}
+
+/// Memory that the simulator may access
+struct Region {
+    /// The address that the DMA controller sees
+    address: u32,
+    /// The address on the host
+    host: *mut u8,
+    len: usize,
+}
+
+/// A fault that the simulator injects into a channel
+///
+/// Beats count from the moment that the fault is injected, across minor loops.
+/// A beat of zero fails the channel's next access. See
+/// [`inject`](Simulator::inject) for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fault {
+    /// The source read `beat` has a bus error
+    SourceBusError {
+        /// The number of successful source reads before the error
+        beat: usize,
+    },
+    /// The destination write `beat` has a bus error
+    DestinationBusError {
+        /// The number of successful destination writes before the error
+        beat: usize,
+    },
+    /// The controller cancels the channel's minor loop before source read `beat`
+    ///
+    /// The cancel behaves like an error cancel from
+    /// [`Channel::cancel`](crate::channel::Channel::cancel). The controller
+    /// writes the data that it already read, then reports a cancelled error.
+    Cancel {
+        /// The number of successful source reads before the cancel
+        beat: usize,
+    },
+    /// The channel interrupts without performing any work
+    ///
+    /// The interrupt happens on the next [`step`](Simulator::step), even if
+    /// the channel has no service request.
+    SpuriousInterrupt,
+}
+
+/// A channel access that may fault
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Beat {
+    Read,
+    Write,
+}
+
+/// A simulated peripheral
+///
+/// [`attach`](Simulator::attach) a peripheral to a simulator. The simulator
+/// routes the peripheral's DMA requests through the DMAMUX, and sends DMA
+/// accesses of the peripheral's registers to the peripheral.
+pub trait Peripheral {
+    /// Returns `true` if the peripheral asserts the DMA request `signal`
+    fn is_requesting(&self, signal: u32) -> bool;
+    /// Handle a DMA read of `bytes.len()` bytes at `address`
+    ///
+    /// Returns `None` if `address` isn't one of the peripheral's registers.
+    /// Returns `Some(false)` to signal a bus error.
+    fn read(&self, address: u32, bytes: &mut [u8]) -> Option<bool>;
+    /// Handle a DMA write of `bytes` at `address`
+    ///
+    /// Returns `None` if `address` isn't one of the peripheral's registers.
+    /// Returns `Some(false)` to signal a bus error.
+    fn write(&self, address: u32, bytes: &[u8]) -> Option<bool>;
+}
+
+/// A simulated eDMA controller and DMAMUX
+///
+/// `CHANNELS` is the number of DMA channels, up to 32.
+pub struct Simulator<const CHANNELS: usize> {
+    dma: &'static Dma<CHANNELS>,
+    inner: Rc<Inner>,
+}
+
+/// The simulator state that's independent of the channel count
+struct Inner {
+    memory: *mut Memory,
+    channels: usize,
+    regions: RefCell<Vec<Region>>,
+    peripherals: RefCell<Vec<Box<dyn Peripheral>>>,
+    requests: Vec<Cell<usize>>,
+    /// Register writes, if recording
+    recording: RefCell<Option<Vec<RegisterWrite>>>,
+    /// Set while the simulator updates its own registers
+    quiet: Cell<bool>,
+    /// Injected faults for each channel, in order
+    faults: Vec<RefCell<VecDeque<Fault>>>,
+}
+
+impl<const CHANNELS: usize> Simulator<CHANNELS> {
+    const VALID: () = assert!(CHANNELS <= 32, "The simulator supports up to 32 channels");
+
+    /// Create a simulator with registers in their reset state
+    ///
+    /// The simulator's registers and driver are never freed. Create
+    /// one simulator for each test.
+    pub fn new() -> Self {
+        let () = Self::VALID;
+        let memory = Box::into_raw(Box::new(Memory {
+            dma: [0; core::mem::size_of::<dma::RegisterBlock>() / 4],
+            dmamux: [0; core::mem::size_of::<dmamux::RegisterBlock>() / 4],
+        }));
+        // Safety: memory is valid and never freed. The register blocks fit in
+        // their arrays.
+        let dma = Box::leak(Box::new(unsafe {
+            Dma::new(
+                core::ptr::addr_of!((*memory).dma).cast(),
+                core::ptr::addr_of!((*memory).dmamux).cast(),
+            )
+        }));
+        let inner = Rc::new(Inner {
+            memory,
+            channels: CHANNELS,
+            regions: RefCell::new(Vec::new()),
+            peripherals: RefCell::new(Vec::new()),
+            requests: (0..CHANNELS).map(|_| Cell::new(0)).collect(),
+            recording: RefCell::new(None),
+            quiet: Cell::new(false),
+            faults: (0..CHANNELS)
+                .map(|_| RefCell::new(VecDeque::new()))
+                .collect(),
+        });
+
+        let controller = inner.controller();
+        controller.CR.write(GRP1PRI);
+        for channel in 0..CHANNELS {
+            controller.DCHPRI[channel].write((channel % 16) as u8);
+        }
+        for register in inner.commands() {
+            register.write(NOP);
+        }
+        SIMULATORS.with_borrow_mut(|simulators| {
+            simulators.retain(|inner| inner.strong_count() > 0);
+            simulators.push(Rc::downgrade(&inner));
+        });
+        Simulator { dma, inner }
+    }
+
+    /// Returns the DMA driver for the simulated controller
+    pub fn dma(&self) -> &'static Dma<CHANNELS> {
+        self.dma
+    }
+
+    /// Allow the simulator to access `memory`
+    ///
+    /// # Safety
+    ///
+    /// The simulator accesses mapped memory like the DMA controller: through
+    /// its address, outside of Rust's borrowing rules. The memory must stay
+    /// valid until you [`unmap_all`](Self::unmap_all) or drop the simulator.
+    pub unsafe fn map<T>(&self, memory: &[T]) {
+        self.inner.regions.borrow_mut().push(Region {
+            address: memory.as_ptr() as usize as u32,
+            host: memory.as_ptr().cast_mut().cast(),
+            len: core::mem::size_of_val(memory),
+        });
+    }
+
+    /// Remove all memory mappings
+    pub fn unmap_all(&self) {
+        self.inner.regions.borrow_mut().clear();
+    }
+
+    /// Attach a simulated peripheral
+    ///
+    /// The peripheral stays attached until the simulator drops.
+    pub fn attach<P: Peripheral + 'static>(&self, peripheral: P) {
+        self.inner
+            .peripherals
+            .borrow_mut()
+            .push(Box::new(peripheral));
+        self.inner.quietly(|| self.inner.update_hardware_requests());
+    }
+
+    /// Start recording the driver's register writes
+    ///
+    /// This discards any writes that were already recorded.
+    pub fn start_recording(&self) {
+        *self.inner.recording.borrow_mut() = Some(Vec::new());
+    }
+
+    /// Stop recording, and return the register writes in the order they happened
+    ///
+    /// Returns an empty collection if the simulator wasn't recording.
+    pub fn stop_recording(&self) -> Vec<RegisterWrite> {
+        self.inner.recording.borrow_mut().take().unwrap_or_default()
+    }
+
+    /// Add `count` hardware requests for `channel`
+    ///
+    /// ```
+    /// use imxrt_dma::{peripheral::{self, Source}, sim::Simulator};
+    ///
+    /// struct Uart {
+    ///     data: u32,
+    /// }
+    ///
+    /// unsafe impl Source<u32> for Uart {
+    ///     fn source_signal(&self) -> u32 { 3 }
+    ///     fn source_address(&self) -> *const u32 { &self.data }
+    ///     fn enable_source(&mut self) {}
+    ///     fn disable_source(&mut self) {}
+    /// }
+    ///
+    /// let sim = Simulator::<32>::new();
+    /// let mut channel = sim.dma().try_channel(0).unwrap();
+    /// let mut uart = Uart { data: 0xAB };
+    /// let mut buffer = [0u32; 4];
+    /// // Safety: memory outlives the transfer.
+    /// unsafe {
+    ///     sim.map(core::slice::from_ref(&uart.data));
+    ///     sim.map(&buffer);
+    /// }
+    ///
+    /// // The UART has four words for the DMA controller.
+    /// sim.request(0, 4);
+    /// sim.block_on(peripheral::read(&mut channel, &mut uart, &mut buffer)).unwrap();
+    /// assert_eq!(buffer, [0xAB; 4]);
+    /// assert_eq!(sim.pending_requests(0), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than or equal to `CHANNELS`.
+    pub fn request(&self, channel: usize, count: usize) {
+        let requests = &self.inner.requests[channel];
+        requests.set(requests.get() + count);
+        self.inner.quietly(|| self.inner.update_hardware_requests());
+    }
+
+    /// Returns the number of hardware requests that `channel` hasn't serviced
+    ///
+    /// This doesn't include requests from attached peripherals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than or equal to `CHANNELS`.
+    pub fn pending_requests(&self, channel: usize) -> usize {
+        self.inner.requests[channel].get()
+    }
+
+    /// Inject a fault into `channel`
+    ///
+    /// The fault waits for any faults that you already injected into `channel`.
+    /// Once the fault happens, it's removed.
+    ///
+    /// ```
+    /// use imxrt_dma::{memcpy, sim::{Fault, Simulator}};
+    ///
+    /// let sim = Simulator::<32>::new();
+    /// let mut channel = sim.dma().try_channel(3).unwrap();
+    /// let source = [1u8; 16];
+    /// let mut destination = [0u8; 16];
+    /// // Safety: buffers outlive the transfer.
+    /// unsafe {
+    ///     sim.map(&source);
+    ///     sim.map(&destination);
+    /// }
+    ///
+    /// sim.inject(3, Fault::SpuriousInterrupt);
+    /// sim.inject(3, Fault::Cancel { beat: 4 });
+    /// let error = sim
+    ///     .block_on(memcpy::memcpy(&source, &mut destination, &mut channel))
+    ///     .unwrap_err();
+    /// assert!(error.is_cancelled());
+    /// assert_eq!(destination[..5], [1, 1, 1, 1, 0]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than or equal to `CHANNELS`.
+    pub fn inject(&self, channel: usize, fault: Fault) {
+        self.inner.faults[channel].borrow_mut().push_back(fault);
+    }
+
+    /// Returns the number of injected faults that haven't happened on `channel`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than or equal to `CHANNELS`.
+    pub fn pending_faults(&self, channel: usize) -> usize {
+        self.inner.faults[channel].borrow().len()
+    }
+
+    /// Remove all faults that haven't happened
+    pub fn clear_faults(&self) {
+        for faults in &self.inner.faults {
+            faults.borrow_mut().clear();
+        }
+    }
+
+    /// Perform one minor loop for the highest priority channel with a service request
+    ///
+    /// Returns the channel that was serviced, or `None` if no channel has a
+    /// service request.
+    pub fn step(&self) -> Option<usize> {
+        if let Some(channel) = self.inner.spurious_interrupt() {
+            // Safety: the simulator plays the role of the interrupt controller.
+            unsafe { self.dma.on_interrupt(channel) };
+            return Some(channel);
+        }
+        let channel = self.inner.next_channel()?;
+        let interrupted = self.inner.quietly(|| {
+            let interrupted = self.inner.service(channel);
+            self.inner.update_hardware_requests();
+            interrupted
+        });
+        if interrupted {
+            // Safety: the simulator plays the role of the interrupt controller.
+            unsafe { self.dma.on_interrupt(channel) };
+        }
+        Some(channel)
+    }
+
+    /// Step until no channel has a service request
+    ///
+    /// Returns the number of minor loops that the simulator performed.
+    pub fn run(&self) -> usize {
+        let mut loops = 0;
+        while self.step().is_some() {
+            loops += 1;
+        }
+        loops
+    }
+
+    /// Poll `future` until it completes, stepping the simulator between polls
+    ///
+    /// # Panics
+    ///
+    /// Panics if the simulator has nothing to do for many consecutive polls,
+    /// since the future would never complete.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut idle = 0;
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            if self.step().is_some() {
+                idle = 0;
+            } else {
+                idle += 1;
+                assert!(idle < STALL_LIMIT, "DMA simulator stalled");
+            }
+        }
+    }
+}
+
+impl<const CHANNELS: usize> Default for Simulator<CHANNELS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inner {
+    fn controller(&self) -> &dma::RegisterBlock {
+        // Safety: memory is valid for the life of the program.
+        unsafe { &*core::ptr::addr_of!((*self.memory).dma).cast() }
+    }
+
+    fn multiplexer(&self) -> &dmamux::RegisterBlock {
+        // Safety: memory is valid for the life of the program.
+        unsafe { &*core::ptr::addr_of!((*self.memory).dmamux).cast() }
+    }
+
+    fn commands(&self) -> [&WORegister<u8>; 8] {
+        let controller = self.controller();
+        [
+            &controller.CEEI,
+            &controller.SEEI,
+            &controller.CERQ,
+            &controller.SERQ,
+            &controller.CDNE,
+            &controller.SSRT,
+            &controller.CERR,
+            &controller.CINT,
+        ]
+    }
+
+    /// Returns `true` if the channel's peripheral is requesting service
+    fn is_requesting(&self, channel: usize) -> bool {
+        let chcfg = self.multiplexer().chcfg[channel].read();
+        let signal = chcfg & dmamux::RegisterBlock::SOURCE_MASK;
+        chcfg & dmamux::RegisterBlock::ENBL != 0
+            && (chcfg & dmamux::RegisterBlock::A_ON != 0
+                || self.requests[channel].get() > 0
+                || self
+                    .peripherals
+                    .borrow()
+                    .iter()
+                    .any(|peripheral| peripheral.is_requesting(signal)))
+    }
+
+    fn has_service_request(&self, channel: usize) -> bool {
+        let controller = self.controller();
+        controller.TCD[channel].CSR.read() & START != 0
+            || (controller.ERQ.read() & (1 << channel) != 0 && self.is_requesting(channel))
+    }
+
+    /// Returns the highest priority channel with a service request
+    fn next_channel(&self) -> Option<usize> {
+        let controller = self.controller();
+        if controller.CR.read() & dma::CR::HALT::mask != 0 {
+            return None;
+        }
+        (0..self.channels)
+            .filter(|&channel| self.has_service_request(channel))
+            .max_by_key(|&channel| {
+                let group = if channel < 16 {
+                    (controller.CR.read() & dma::CR::GRP0PRI::mask) >> dma::CR::GRP0PRI::offset
+                } else {
+                    (controller.CR.read() & dma::CR::GRP1PRI::mask) >> dma::CR::GRP1PRI::offset
+                };
+                let priority =
+                    u32::from(controller.DCHPRI[channel].read()) & dma::DCHPRI::CHPRI::mask as u32;
+                (group, priority)
+            })
+    }
+
+    fn update_hardware_requests(&self) {
+        let hrs = (0..self.channels)
+            .filter(|&channel| self.is_requesting(channel))
+            .fold(0, |hrs, channel| hrs | 1 << channel);
+        write_ro(&self.controller().HRS, hrs);
+    }
+
+    /// Find mapped memory for `len` bytes at `address`
+    fn translate(&self, address: u32, len: usize) -> Option<*mut u8> {
+        self.regions.borrow().iter().find_map(|region| {
+            let offset = address.checked_sub(region.address)? as usize;
+            (offset + len <= region.len).then(|| region.host.wrapping_add(offset))
+        })
+    }
+
+    fn read(&self, address: u32, bytes: &mut [u8]) -> bool {
+        let peripherals = self.peripherals.borrow();
+        if let Some(ok) = peripherals
+            .iter()
+            .find_map(|peripheral| peripheral.read(address, bytes))
+        {
+            return ok;
+        }
+        let Some(host) = self.translate(address, bytes.len()) else {
+            return false;
+        };
+        // Safety: mapped memory is valid, according to the map contract.
+        unsafe { core::ptr::copy(host, bytes.as_mut_ptr(), bytes.len()) };
+        true
+    }
+
+    fn write(&self, address: u32, bytes: &[u8]) -> bool {
+        let peripherals = self.peripherals.borrow();
+        if let Some(ok) = peripherals
+            .iter()
+            .find_map(|peripheral| peripheral.write(address, bytes))
+        {
+            return ok;
+        }
+        let Some(host) = self.translate(address, bytes.len()) else {
+            return false;
+        };
+        // Safety: mapped memory is valid, according to the map contract.
+        unsafe { core::ptr::copy(bytes.as_ptr(), host, bytes.len()) };
+        true
+    }
+
+    /// Returns the fault for `channel`'s next `beat`, if it faults
+    ///
+    /// Counts the beat toward the channel's next fault.
+    fn fault(&self, channel: usize, beat: Beat) -> Option<Fault> {
+        let mut faults = self.faults[channel].borrow_mut();
+        let remaining = match (faults.front_mut()?, beat) {
+            (Fault::SourceBusError { beat } | Fault::Cancel { beat }, Beat::Read) => beat,
+            (Fault::DestinationBusError { beat }, Beat::Write) => beat,
+            _ => return None,
+        };
+        if *remaining > 0 {
+            *remaining -= 1;
+            None
+        } else {
+            faults.pop_front()
+        }
+    }
+
+    /// Raise the next spurious interrupt, if one is scripted
+    ///
+    /// Returns the channel that interrupted.
+    fn spurious_interrupt(&self) -> Option<usize> {
+        let channel = self
+            .faults
+            .iter()
+            .position(|faults| faults.borrow().front() == Some(&Fault::SpuriousInterrupt))?;
+        self.faults[channel].borrow_mut().pop_front();
+        let controller = self.controller();
+        self.quietly(|| controller.INT.write(controller.INT.read() | 1 << channel));
+        Some(channel)
+    }
+
+    /// Record an error for `channel`
+    fn error(&self, channel: usize, status: u32) {
+        let controller = self.controller();
+        controller.ERR.write(controller.ERR.read() | 1 << channel);
+        write_ro(&controller.ES, VLD | (channel as u32) << 8 | status);
+    }
+
+    /// Perform one minor loop for `channel`
+    ///
+    /// Returns `true` if the channel would interrupt.
+    fn service(&self, channel: usize) -> bool {
+        let controller = self.controller();
+        let tcd = &controller.TCD[channel];
+        let csr = tcd.CSR.read();
+        if csr & START != 0 {
+            tcd.CSR.write(csr & !START);
+        } else if self.requests[channel].get() > 0 {
+            self.requests[channel].set(self.requests[channel].get() - 1);
+        }
+
+        match self.minor_loop(channel) {
+            Ok(interrupt) => interrupt,
+            Err(status) => {
+                self.error(channel, status);
+                controller.EEI.read() & (1 << channel) != 0
+            }
+        }
+    }
+
+    fn minor_loop(&self, channel: usize) -> Result<bool, u32> {
+        let controller = self.controller();
+        let tcd = &controller.TCD[channel];
+
+        let (ssize, smod) = attributes(tcd.SATTR.read());
+        let (dsize, dmod) = attributes(tcd.DATTR.read());
+        let nbytes = tcd.NBYTES.read();
+        let (nbytes, smloff, dmloff) = if controller.CR.read() & EMLM != 0
+            && nbytes & (tcd::NBYTES::SMLOE::mask | tcd::NBYTES::DMLOE::mask) != 0
+        {
+            // Sign-extend the 20-bit offset.
+            let mloff = ((nbytes & tcd::NBYTES::MLOFF::mask) << 2) as i32 >> 12;
+            (
+                nbytes & 0x3FF,
+                if nbytes & tcd::NBYTES::SMLOE::mask != 0 {
+                    mloff
+                } else {
+                    0
+                },
+                if nbytes & tcd::NBYTES::DMLOE::mask != 0 {
+                    mloff
+                } else {
+                    0
+                },
+            )
+        } else if controller.CR.read() & EMLM != 0 {
+            (nbytes & 0x3FFF_FFFF, 0, 0)
+        } else {
+            (nbytes, 0, 0)
+        };
+        let (nbytes, ssize, dsize) = (nbytes as usize, ssize as usize, dsize as usize);
+        let linked = tcd.CITER.read() & tcd::CITER::ELINK::mask != 0;
+        let count = if linked {
+            tcd::CITER::CITER_ELINK::mask
+        } else {
+            tcd::CITER::CITER::mask
+        };
+        let citer = tcd.CITER.read() & count;
+
+        let mut saddr = tcd.SADDR.read();
+        let mut daddr = tcd.DADDR.read();
+        let soff = tcd.SOFF.read();
+        let doff = tcd.DOFF.read();
+        if nbytes == 0 || nbytes % ssize != 0 || nbytes % dsize != 0 || citer == 0 {
+            return Err(NCE);
+        }
+        if !(saddr as usize).is_multiple_of(ssize) {
+            return Err(SAE);
+        }
+        if !(soff as usize).is_multiple_of(ssize) {
+            return Err(SOE);
+        }
+        if !(daddr as usize).is_multiple_of(dsize) {
+            return Err(DAE);
+        }
+        if !(doff as usize).is_multiple_of(dsize) {
+            return Err(DOE);
+        }
+
+        let mut data = std::vec![0; nbytes];
+        let mut cancelled = false;
+        let mut read = 0;
+        for chunk in data.chunks_mut(ssize) {
+            match self.fault(channel, Beat::Read) {
+                Some(Fault::SourceBusError { .. }) => return Err(SBE),
+                Some(Fault::Cancel { .. }) => {
+                    cancelled = true;
+                    break;
+                }
+                _ => {}
+            }
+            if !self.read(saddr, chunk) {
+                return Err(SBE);
+            }
+            saddr = advance(saddr, soff.into(), smod);
+            read += chunk.len();
+        }
+        for chunk in data[..read - read % dsize].chunks(dsize) {
+            if self.fault(channel, Beat::Write).is_some() || !self.write(daddr, chunk) {
+                return Err(DBE);
+            }
+            daddr = advance(daddr, doff.into(), dmod);
+        }
+        if cancelled {
+            return Err(ES_ECX);
+        }
+        saddr = saddr.wrapping_add_signed(smloff);
+        daddr = daddr.wrapping_add_signed(dmloff);
+
+        let csr = tcd.CSR.read();
+        let biter = tcd.BITER.read() & count;
+        let citer = citer - 1;
+        let mut interrupt = csr & INTHALF != 0 && citer == biter / 2;
+        if citer != 0 {
+            tcd.SADDR.write(saddr);
+            tcd.DADDR.write(daddr);
+            tcd.CITER.write(tcd.CITER.read() & !count | citer);
+            if linked {
+                let link =
+                    (tcd.CITER.read() & tcd::CITER::LINKCH::mask) >> tcd::CITER::LINKCH::offset;
+                let linked = &controller.TCD[usize::from(link)];
+                linked.CSR.write(linked.CSR.read() | START);
+            }
+        } else {
+            tcd.SADDR.write(saddr.wrapping_add_signed(tcd.SLAST.read()));
+            if csr & ESG != 0 {
+                let mut next = [0; core::mem::size_of::<tcd::RegisterBlock>()];
+                let sga = tcd.DLAST_SGA.read() as u32;
+                if !(sga as usize).is_multiple_of(next.len()) || !self.read(sga, &mut next) {
+                    return Err(SGE);
+                }
+                // Safety: a TCD is plain memory, and `next` is the size of a TCD.
+                unsafe {
+                    core::ptr::copy(
+                        next.as_ptr(),
+                        (tcd as *const tcd::RegisterBlock).cast_mut().cast(),
+                        next.len(),
+                    )
+                };
+            } else {
+                tcd.DADDR
+                    .write(daddr.wrapping_add_signed(tcd.DLAST_SGA.read()));
+                tcd.CITER.write(tcd.BITER.read());
+                tcd.CSR.write(tcd.CSR.read() | DONE);
+            }
+            if csr & DREQ != 0 {
+                controller
+                    .ERQ
+                    .write(controller.ERQ.read() & !(1 << channel));
+            }
+            interrupt |= csr & INTMAJOR != 0;
+        }
+        if interrupt {
+            controller.INT.write(controller.INT.read() | 1 << channel);
+        }
+        Ok(interrupt)
+    }
+}
+
+/// Returns the transfer size in bytes, and the modulo
+fn attributes(attr: u8) -> (u32, u8) {
+    let size = (attr & tcd::SATTR::SIZE::mask) >> tcd::SATTR::SIZE::offset;
+    let modulo = (attr & tcd::SATTR::MOD::mask) >> tcd::SATTR::MOD::offset;
+    (1 << size, modulo)
+}
+
+/// Add `offset` to `address`, keeping the upper bits fixed for a non-zero modulo
+fn advance(address: u32, offset: i32, modulo: u8) -> u32 {
+    let next = address.wrapping_add_signed(offset);
+    if modulo == 0 {
+        next
+    } else {
+        let mask = (1 << modulo) - 1;
+        (address & !mask) | (next & mask)
+    }
+}
+
+/// Write a register that's read-only to software
+fn write_ro(register: &RORegister<u32>, value: u32) {
+    // Safety: simulated registers are plain memory.
+    unsafe {
+        (register as *const RORegister<u32>)
+            .cast_mut()
+            .cast::<u32>()
+            .write_volatile(value)
+    };
+}
+
+impl Inner {
+    /// Update HRS before the driver reads it
+    fn before_read(&self, address: usize) {
+        if !self.quiet.get() && address == &self.controller().HRS as *const _ as usize {
+            self.quietly(|| self.update_hardware_requests());
+        }
+    }
+
+    /// Record the driver's write, and execute any command that it wrote
+    fn after_write(&self, address: usize, size: usize, value: u32) {
+        if self.quiet.get() {
+            return;
+        }
+        self.observe(address, size, value);
+        let controller = self.controller();
+        let is_command = core::iter::once(&controller.CR as *const _ as usize)
+            .chain(
+                self.commands()
+                    .map(|register| register as *const _ as usize),
+            )
+            .any(|register| register == address);
+        if is_command {
+            self.quietly(|| {
+                self.execute_commands();
+                self.update_hardware_requests();
+            });
+        }
+    }
+
+    /// Run `f` without recording register writes
+    fn quietly<R>(&self, f: impl FnOnce() -> R) -> R {
+        let quiet = self.quiet.replace(true);
+        let result = f();
+        self.quiet.set(quiet);
+        result
+    }
+
+    /// Record the driver's write of `size` bytes at `address`, if it's in this simulator
+    fn observe(&self, address: usize, size: usize, value: u32) {
+        let blocks = [
+            (
+                Block::Dma,
+                self.controller() as *const _ as usize,
+                core::mem::size_of::<dma::RegisterBlock>(),
+            ),
+            (
+                Block::Dmamux,
+                self.multiplexer() as *const _ as usize,
+                core::mem::size_of::<dmamux::RegisterBlock>(),
+            ),
+        ];
+        let Some(&(block, start, _)) = blocks
+            .iter()
+            .find(|&&(_, start, len)| (start..start + len).contains(&address))
+        else {
+            return;
+        };
+        #[cfg(feature = "command-log")]
+        crate::command_log::record(address, size, value);
+        if let Some(writes) = self.recording.borrow_mut().as_mut() {
+            writes.push(RegisterWrite {
+                block,
+                offset: address - start,
+                size,
+                value,
+            });
+        }
+    }
+
+    fn execute_commands(&self) {
+        let controller = self.controller();
+        let take = |register: &WORegister<u8>| {
+            // Safety: simulated registers are plain memory.
+            let value = unsafe {
+                (register as *const WORegister<u8>)
+                    .cast::<u8>()
+                    .read_volatile()
+            };
+            register.write(NOP);
+            (value & NOP == 0).then(|| {
+                if value & ALL != 0 {
+                    u32::MAX
+                } else {
+                    1 << (value & 0x1F)
+                }
+            })
+        };
+        let tcds = |channels: u32, update: &dyn Fn(u16) -> u16| {
+            for (channel, tcd) in controller.TCD.iter().enumerate() {
+                if channels & (1 << channel) != 0 {
+                    tcd.CSR.write(update(tcd.CSR.read()));
+                }
+            }
+        };
+
+        if let Some(channels) = take(&controller.CEEI) {
+            controller.EEI.write(controller.EEI.read() & !channels);
+        }
+        if let Some(channels) = take(&controller.SEEI) {
+            controller.EEI.write(controller.EEI.read() | channels);
+        }
+        if let Some(channels) = take(&controller.CERQ) {
+            controller.ERQ.write(controller.ERQ.read() & !channels);
+        }
+        if let Some(channels) = take(&controller.SERQ) {
+            controller.ERQ.write(controller.ERQ.read() | channels);
+        }
+        if let Some(channels) = take(&controller.CDNE) {
+            tcds(channels, &|csr| csr & !DONE);
+        }
+        if let Some(channels) = take(&controller.SSRT) {
+            tcds(channels, &|csr| csr | START);
+        }
+        if let Some(channels) = take(&controller.CERR) {
+            let err = controller.ERR.read() & !channels;
+            controller.ERR.write(err);
+            if err == 0 {
+                write_ro(&controller.ES, controller.ES.read() & !VLD);
+            }
+        }
+        if let Some(channels) = take(&controller.CINT) {
+            controller.INT.write(controller.INT.read() & !channels);
+        }
+        // Minor loops finish within a step, so cancels finish immediately.
+        controller.CR.write(controller.CR.read() & !(CX | ECX));
+    }
+}
@@ -0,0 +1,149 @@
+//! Adapters for `heapless` single-producer, single-consumer queues.
+//!
+//! Use these adapters if you already move data between tasks with a
+//! [`heapless::spsc::Queue`]. [`receive`] moves a DMA-completed chunk
+//! of elements into a queue [`Producer`]. [`transmit`] takes a chunk
+//! of elements from a queue [`Consumer`] and sends it to a peripheral.
+//!
+//! A queue's storage isn't contiguous, so the DMA channel can't move data
+//! directly into or out of the queue. Instead, each adapter uses a caller-provided
+//! `buffer` as the DMA buffer, and copies between that buffer and the queue.
+//! The size of `buffer` limits the size of each chunk.
+//!
+//! This module requires the `"heapless"` feature.
+
+use crate::{
+    channel::Channel,
+    peripheral::{self, Destination, Source},
+    Element, Result,
+};
+
+use heapless::spsc::{Consumer, Producer};
+
+/// Receive a chunk of elements from `source`, and enqueue them into `producer`
+///
+/// The chunk size is the smaller of the `buffer` length and the number of free
+/// slots in the queue. Returns the number of elements enqueued. If either
+/// the buffer is empty or the queue is full, this returns `Ok(0)` without
+/// starting a transfer.
+///
+/// Elements are enqueued only after the DMA transfer completes. If the
+/// transfer fails, nothing is enqueued.
+///
+/// # Example
+///
+/// ```no_run
+/// use imxrt_dma::{channel::Channel, spsc};
+/// use heapless::spsc::Queue;
+/// # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+/// # struct X;
+/// # unsafe impl imxrt_dma::peripheral::Source<u8> for X {
+/// #   fn source_signal(&self) -> u32 { 0 }
+/// #   fn source_address(&self) -> *const u8 { panic!() }
+/// #   fn enable_source(&mut self) { panic!() }
+/// #   fn disable_source(&mut self) { panic!() }
+/// # }
+///
+/// # async fn f() -> imxrt_dma::Result<()> {
+/// let mut lpuart = // A LPUART peripheral
+///     # X;
+/// let mut channel_7: Channel = // DMA channel 7
+///     # unsafe { DMA.channel(7) };
+///
+/// let mut queue: Queue<u8, 64> = Queue::new();
+/// let (mut producer, _consumer) = queue.split();
+///
+/// let mut buffer = [0u8; 16];
+/// let received = spsc::receive(&mut channel_7, &mut lpuart, &mut buffer, &mut producer).await?;
+/// # Ok(()) }
+/// ```
+pub async fn receive<S, E, const N: usize>(
+    channel: &mut Channel,
+    source: &mut S,
+    buffer: &mut [E],
+    producer: &mut Producer<'_, E, N>,
+) -> Result<usize>
+where
+    S: Source<E>,
+    E: Element,
+{
+    let free = producer.capacity() - producer.len();
+    let len = buffer.len().min(free);
+    if len == 0 {
+        return Ok(0);
+    }
+
+    let chunk = &mut buffer[..len];
+    peripheral::read(channel, source, chunk).await?;
+
+    for elem in chunk.iter() {
+        // Safety: we're the only producer, and we checked that there's
+        // room for this many elements. A consumer can only free up space.
+        unsafe { producer.enqueue_unchecked(*elem) };
+    }
+    Ok(len)
+}
+
+/// Dequeue a chunk of elements from `consumer`, and send them to `destination`
+///
+/// The chunk size is the smaller of the `buffer` length and the number of
+/// elements in the queue. Returns the number of elements sent. If either
+/// the buffer or the queue is empty, this returns `Ok(0)` without starting
+/// a transfer.
+///
+/// Elements are dequeued before the DMA transfer starts. If the transfer
+/// fails, the dequeued elements are lost.
+///
+/// # Example
+///
+/// ```no_run
+/// use imxrt_dma::{channel::Channel, spsc};
+/// use heapless::spsc::Queue;
+/// # static DMA: imxrt_dma::Dma<32> = unsafe { imxrt_dma::Dma::new(core::ptr::null(), core::ptr::null()) };
+/// # struct X;
+/// # unsafe impl imxrt_dma::peripheral::Destination<u8> for X {
+/// #   fn destination_signal(&self) -> u32 { 0 }
+/// #   fn destination_address(&self) -> *const u8 { panic!() }
+/// #   fn enable_destination(&mut self) { panic!() }
+/// #   fn disable_destination(&mut self) { panic!() }
+/// # }
+///
+/// # async fn f() -> imxrt_dma::Result<()> {
+/// let mut lpuart = // A LPUART peripheral
+///     # X;
+/// let mut channel_8: Channel = // DMA channel 8
+///     # unsafe { DMA.channel(8) };
+///
+/// let mut queue: Queue<u8, 64> = Queue::new();
+/// let (mut producer, mut consumer) = queue.split();
+/// producer.enqueue(b'!').unwrap();
+///
+/// let mut buffer = [0u8; 16];
+/// let sent = spsc::transmit(&mut channel_8, &mut consumer, &mut buffer, &mut lpuart).await?;
+/// # Ok(()) }
+/// ```
+pub async fn transmit<D, E, const N: usize>(
+    channel: &mut Channel,
+    consumer: &mut Consumer<'_, E, N>,
+    buffer: &mut [E],
+    destination: &mut D,
+) -> Result<usize>
+where
+    D: Destination<E>,
+    E: Element,
+{
+    let len = buffer.len().min(consumer.len());
+    if len == 0 {
+        return Ok(0);
+    }
+
+    let chunk = &mut buffer[..len];
+    for elem in chunk.iter_mut() {
+        // Safety: we're the only consumer, and we checked that there's
+        // at least this many elements. A producer can only add elements.
+        *elem = unsafe { consumer.dequeue_unchecked() };
+    }
+
+    peripheral::write(channel, chunk, destination).await?;
+    Ok(len)
+}
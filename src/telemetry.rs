@@ -0,0 +1,144 @@
+//! Per-channel DMA activity counters
+
+use crate::{critical, Dma, DmaRef};
+use core::cell::Cell;
+
+use cortex_m::interrupt::Mutex;
+
+/// The activity of one DMA channel
+///
+/// Counts transfer futures, like [`memcpy`](crate::memcpy) and the
+/// [`peripheral`](crate::peripheral) transfers, that resolved on the channel.
+/// Counters wrap on overflow.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChannelTelemetry {
+    /// The bytes moved by completed transfers
+    pub bytes: u64,
+    /// The number of completed transfers
+    pub transfers: u32,
+    /// The number of transfers that failed with a hardware error
+    ///
+    /// Cancelled transfers aren't errors.
+    pub errors: u32,
+}
+
+impl ChannelTelemetry {
+    const fn new() -> Self {
+        ChannelTelemetry {
+            bytes: 0,
+            transfers: 0,
+            errors: 0,
+        }
+    }
+
+    /// Add `other`'s counts to these counts
+    fn add(&mut self, other: &ChannelTelemetry) {
+        self.bytes = self.bytes.wrapping_add(other.bytes);
+        self.transfers = self.transfers.wrapping_add(other.transfers);
+        self.errors = self.errors.wrapping_add(other.errors);
+    }
+}
+
+/// A snapshot of a driver's DMA activity
+///
+/// Use [`telemetry`](crate::Dma::telemetry) to get a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Telemetry<const CHANNELS: usize> {
+    /// The activity of each channel, indexed by channel number
+    pub channels: [ChannelTelemetry; CHANNELS],
+}
+
+impl<const CHANNELS: usize> Telemetry<CHANNELS> {
+    /// Returns the activity of all channels, combined
+    pub fn total(&self) -> ChannelTelemetry {
+        self.channels
+            .iter()
+            .fold(ChannelTelemetry::new(), |mut total, channel| {
+                total.add(channel);
+                total
+            })
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const CHANNELS: usize> defmt::Format for Telemetry<CHANNELS> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "Telemetry {{ channels: {} }}", self.channels.as_slice());
+    }
+}
+
+pub(crate) type SharedTelemetry = Mutex<Cell<ChannelTelemetry>>;
+#[allow(clippy::declare_interior_mutable_const)] // See NO_WAKER
+pub(crate) const NO_TELEMETRY: SharedTelemetry = Mutex::new(Cell::new(ChannelTelemetry::new()));
+
+/// Count a transfer that completed after moving `bytes`
+pub(crate) fn record_complete(telemetry: Option<&SharedTelemetry>, bytes: u64) {
+    update(telemetry, |counts| {
+        counts.bytes = counts.bytes.wrapping_add(bytes);
+        counts.transfers = counts.transfers.wrapping_add(1);
+    });
+}
+
+/// Count a transfer that failed with a hardware error
+pub(crate) fn record_error(telemetry: Option<&SharedTelemetry>) {
+    update(telemetry, |counts| {
+        counts.errors = counts.errors.wrapping_add(1)
+    });
+}
+
+fn update(telemetry: Option<&SharedTelemetry>, f: impl FnOnce(&mut ChannelTelemetry)) {
+    if let Some(telemetry) = telemetry {
+        critical::free(|cs| {
+            let cell = telemetry.borrow(cs);
+            let mut counts = cell.get();
+            f(&mut counts);
+            cell.set(counts);
+        });
+    }
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    Dma<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Returns a snapshot of each channel's activity
+    ///
+    /// Feed the snapshot into your health monitoring, or compare two snapshots
+    /// to compute DMA utilization over an interval.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::Dma;
+    /// static DMA: Dma<32> = // Handle to DMA driver.
+    /// # unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// let telemetry = DMA.telemetry();
+    /// let total = telemetry.total();
+    /// let channel_7 = telemetry.channels[7];
+    /// // Report the bytes moved, completed transfers, and errors...
+    /// ```
+    pub fn telemetry(&self) -> Telemetry<CHANNELS> {
+        critical::free(|cs| Telemetry {
+            channels: core::array::from_fn(|index| self.telemetry[index].borrow(cs).get()),
+        })
+    }
+
+    /// Reset every channel's activity counters to zero
+    pub fn clear_telemetry(&self) {
+        critical::free(|cs| {
+            for telemetry in &self.telemetry {
+                telemetry.borrow(cs).set(ChannelTelemetry::new());
+            }
+        });
+    }
+}
+
+impl DmaRef {
+    /// Returns the activity of `channel`
+    ///
+    /// Returns `None` if `channel` is greater than or equal to the number of
+    /// channels. See [`Dma::telemetry`] for more information.
+    pub fn channel_telemetry(&self, channel: usize) -> Option<ChannelTelemetry> {
+        let telemetry = self.telemetry.get(channel)?;
+        Some(critical::free(|cs| telemetry.borrow(cs).get()))
+    }
+}
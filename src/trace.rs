@@ -0,0 +1,38 @@
+//! Transfer event tracing
+//!
+//! With the `"trace-log"` feature, events go to the `log` crate. With the
+//! `"trace-defmt"` feature, events go to `defmt`. Without either feature,
+//! tracing compiles to nothing.
+//!
+//! All events are at the debug level, and they start with the channel
+//! number, like `DMA7: ...`.
+
+// Transfer directions, for `start`.
+pub(crate) const MEMORY_TO_MEMORY: &str = "memory to memory";
+pub(crate) const PERIPHERAL_TO_MEMORY: &str = "peripheral to memory";
+pub(crate) const MEMORY_TO_PERIPHERAL: &str = "memory to peripheral";
+
+/// Emit a debug event
+///
+/// The format string must be valid for both `log` and `defmt`. Stick to
+/// `{}` for numbers and strings, and `{:#X}` for hex numbers.
+macro_rules! event {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        #[cfg(feature = "trace-log")]
+        log::debug!($fmt $(, $arg)*);
+        #[cfg(feature = "trace-defmt")]
+        defmt::debug!($fmt $(, $arg)*);
+        #[cfg(not(any(feature = "trace-log", feature = "trace-defmt")))]
+        {
+            $(let _ = &$arg;)*
+        }
+    }};
+}
+
+pub(crate) use event;
+
+/// A transfer is about to start
+#[inline(always)]
+pub(crate) fn start(channel: usize, direction: &'static str, elements: usize) {
+    event!("DMA{}: start {}, {} elements", channel, direction, elements);
+}
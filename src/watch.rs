@@ -0,0 +1,197 @@
+//! Extra tasks waiting on a channel
+
+use crate::{critical, Dma, DmaRef};
+use core::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use cortex_m::interrupt::Mutex;
+
+/// A slot for one `Completion` future
+pub(crate) enum Watcher {
+    Vacant,
+    Waiting(Waker),
+    /// `on_interrupt` woke the watcher; the future hasn't seen it yet.
+    Woken,
+}
+
+pub(crate) type SharedWatcher = Mutex<RefCell<Watcher>>;
+#[allow(clippy::declare_interior_mutable_const)] // See NO_WAKER
+pub(crate) const NO_WATCHER: SharedWatcher = Mutex::new(RefCell::new(Watcher::Vacant));
+
+/// Returns the watcher slots of `channel`
+///
+/// `watchers` holds the same number of slots for each of the driver's `channels`.
+fn slots(watchers: &[SharedWatcher], channels: usize, channel: usize) -> &[SharedWatcher] {
+    let count = watchers.len() / channels;
+    &watchers[channel * count..][..count]
+}
+
+/// Wake every watcher of `channel`
+pub(crate) fn wake(dma: &DmaRef, channel: usize) {
+    for slot in slots(dma.watchers, dma.channels, channel) {
+        let watcher = critical::free(|cs| {
+            let mut watcher = slot.borrow(cs).borrow_mut();
+            match &*watcher {
+                Watcher::Waiting(_) => Some(core::mem::replace(&mut *watcher, Watcher::Woken)),
+                _ => None,
+            }
+        });
+        // Wake outside of the critical section.
+        if let Some(Watcher::Waiting(waker)) = watcher {
+            waker.wake();
+        }
+    }
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    Dma<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Wait for `channel` to signal its next event
+    ///
+    /// The future resolves the next time [`on_interrupt`](Self::on_interrupt)
+    /// finds that `channel` interrupted, completed, or errored. That's the
+    /// same event that wakes the channel's transfer future, so use this to let
+    /// another task, like a supervisor, follow a transfer that a different
+    /// task owns. Any number of these futures can wait on the same channel.
+    ///
+    /// If the channel already completed or errored, the future resolves when
+    /// it's first polled. Otherwise, it only sees events that happen while it's
+    /// waiting. The future doesn't change the channel.
+    ///
+    /// Each channel has `WATCHERS` slots for waiting futures; see [`Dma`]. Up
+    /// to `WATCHERS` futures wait on a channel at once. Any more wake themselves
+    /// each time they're polled, until one of the others resolves or is dropped.
+    ///
+    /// ```no_run
+    /// use imxrt_dma::Dma;
+    /// static DMA: Dma<32> = // Handle to DMA driver.
+    /// # unsafe { Dma::new(core::ptr::null(), core::ptr::null()) };
+    ///
+    /// # async fn f() {
+    /// DMA.wait_complete(7).await;
+    /// // Check on channel 7's transfer...
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than or equal to the maximum number of channels.
+    pub fn wait_complete(&'static self, channel: usize) -> Completion {
+        self.erase().wait_complete(channel)
+    }
+}
+
+impl DmaRef {
+    /// Wait for `channel` to signal its next event
+    ///
+    /// See [`Dma::wait_complete`] for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is greater than or equal to the maximum number of channels.
+    pub fn wait_complete(&self, channel: usize) -> Completion {
+        assert!(
+            channel < self.channels,
+            "DMA channel {channel} is out of range"
+        );
+        Completion {
+            dma: *self,
+            channel,
+            slot: None,
+        }
+    }
+}
+
+/// A future that resolves when a DMA channel signals its next event
+///
+/// Use [`wait_complete`](crate::Dma::wait_complete) to create this future.
+pub struct Completion {
+    dma: DmaRef,
+    channel: usize,
+    /// The channel's watcher slot, once the future has one.
+    slot: Option<usize>,
+}
+
+impl Completion {
+    /// Returns the DMA channel that this future waits on
+    pub fn channel(&self) -> usize {
+        self.channel
+    }
+
+    /// Returns this future's channel's watcher slots
+    fn slots(&self) -> &'static [SharedWatcher] {
+        slots(self.dma.watchers, self.dma.channels, self.channel)
+    }
+
+    /// Returns `true` if the channel already completed or errored
+    fn is_signaled(&self) -> bool {
+        // Automatic gating only gates the clock when every channel is disabled.
+        #[cfg(feature = "clock-gating")]
+        if self.dma.clock.is_gated() {
+            return false;
+        }
+        // Safety: we're only reading the channel's status.
+        let channel = unsafe { self.dma.channel(self.channel) };
+        channel.is_complete() || channel.is_error()
+    }
+}
+
+impl Future for Completion {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let slots = this.slots();
+        let (poll, _replaced) = critical::free(|cs| match this.slot {
+            Some(slot) => {
+                let mut watcher = slots[slot].borrow(cs).borrow_mut();
+                match &mut *watcher {
+                    Watcher::Woken => {
+                        *watcher = Watcher::Vacant;
+                        this.slot = None;
+                        (Poll::Ready(()), None)
+                    }
+                    Watcher::Waiting(waker) if !waker.will_wake(cx.waker()) => {
+                        let replaced = core::mem::replace(waker, cx.waker().clone());
+                        (Poll::Pending, Some(replaced))
+                    }
+                    _ => (Poll::Pending, None),
+                }
+            }
+            // Check inside the critical section, so that on_interrupt can't
+            // signal the channel before the future takes a slot.
+            None if this.is_signaled() => (Poll::Ready(()), None),
+            None => {
+                if let Some(slot) = slots
+                    .iter()
+                    .position(|slot| matches!(*slot.borrow(cs).borrow(), Watcher::Vacant))
+                {
+                    *slots[slot].borrow(cs).borrow_mut() = Watcher::Waiting(cx.waker().clone());
+                    this.slot = Some(slot);
+                } else {
+                    // Try again on the next poll.
+                    cx.waker().wake_by_ref();
+                }
+                (Poll::Pending, None)
+            }
+        });
+        poll
+    }
+}
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            // Drop the waker outside of the critical section.
+            let _vacated = critical::free(|cs| {
+                core::mem::replace(
+                    &mut *self.slots()[slot].borrow(cs).borrow_mut(),
+                    Watcher::Vacant,
+                )
+            });
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Stalled channel detection
+//!
+//! A channel can stop making progress if its peripheral stops requesting
+//! service. The transfer future never completes, and the firmware hangs.
+//! A [`Watchdog`] detects these channels, so that the firmware can recover.
+//!
+//! Call [`Watchdog::check`] periodically, at an interval that's longer than the
+//! slowest expected progress of your transfers. The watchdog samples each channel's
+//! current transfer iterations, source address, and destination address. A channel
+//! is stalled if it was enabled for two consecutive checks, and none of those
+//! values changed.
+//!
+//! ```no_run
+//! use imxrt_dma::{watchdog::Watchdog, Dma};
+//! # const DMA_PTR: *const () = core::ptr::null() as _;
+//! # const DMAMUX_PTR: *const () = core::ptr::null() as  _;
+//!
+//! static DMA: Dma<32> = unsafe { Dma::new(DMA_PTR, DMAMUX_PTR) };
+//!
+//! let mut watchdog = Watchdog::new(&DMA);
+//! loop {
+//!     // Wait for your interval...
+//!     let stalled = watchdog.check();
+//!     for channel in (0..32).filter(|channel| stalled & (1 << channel) != 0) {
+//!         // Recover the channel...
+//!     }
+//! }
+//! ```
+
+use crate::Dma;
+
+/// A sample of a channel's progress
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Sample {
+    iterations: u16,
+    source: usize,
+    destination: usize,
+}
+
+/// Detects enabled channels that aren't making progress
+///
+/// See the [module-level documentation](crate::watchdog) for more information.
+pub struct Watchdog<
+    const CHANNELS: usize,
+    const WAKERS: usize = CHANNELS,
+    const WATCHERS: usize = 1,
+> {
+    dma: &'static Dma<CHANNELS, WAKERS, WATCHERS>,
+    /// The previous sample for each channel, or `None` if the
+    /// channel wasn't enabled.
+    samples: [Option<Sample>; CHANNELS],
+}
+
+impl<const CHANNELS: usize, const WAKERS: usize, const WATCHERS: usize>
+    Watchdog<CHANNELS, WAKERS, WATCHERS>
+{
+    /// Create a watchdog that supervises all channels of `dma`
+    pub const fn new(dma: &'static Dma<CHANNELS, WAKERS, WATCHERS>) -> Self {
+        Self {
+            dma,
+            samples: [None; CHANNELS],
+        }
+    }
+
+    /// Sample all channels, and return a bitmask of stalled channels
+    ///
+    /// Bit `n` is set if channel `n` is stalled. A channel isn't stalled on the
+    /// first check after it's enabled, or after its transfer completes.
+    ///
+    /// `check` only reads channel registers. It doesn't disable or otherwise
+    /// change a stalled channel.
+    pub fn check(&mut self) -> u64 {
+        // Automatic gating only gates the clock when every channel is disabled.
+        #[cfg(feature = "clock-gating")]
+        if self.dma.clock.is_gated() {
+            self.reset();
+            return 0;
+        }
+        let mut stalled = 0;
+        for (index, previous) in self.samples.iter_mut().enumerate() {
+            // Safety: we're only reading the channel's status.
+            let channel = unsafe { self.dma.channel(index) };
+            let sample = (channel.is_enabled() && !channel.is_complete()).then(|| Sample {
+                iterations: channel.current_transfer_iterations(),
+                source: channel.source_address() as usize,
+                destination: channel.destination_address() as usize,
+            });
+            if sample.is_some() && sample == *previous {
+                stalled |= 1 << index;
+            }
+            *previous = sample;
+        }
+        stalled
+    }
+
+    /// Forget all samples
+    ///
+    /// After a reset, no channel is stalled until it's checked twice. Use this after
+    /// you recover a stalled channel.
+    pub fn reset(&mut self) {
+        self.samples = [None; CHANNELS];
+    }
+}